@@ -0,0 +1,49 @@
+/**
+ * All theseus API calls return serialized values (both return values and errors);
+ * So, for example, addDefaultInstance creates a blank Profile object, where the Rust struct is serialized,
+ *  and deserialized into a usable JS object.
+ */
+import { invoke } from '@tauri-apps/api/tauri'
+
+// Gets the disk usage in bytes of every profile and of the shared launcher data
+// Progress is reported through loading bar events. Results are cached until the profile's files
+// change, unless refresh is set
+/*
+DiskUsage {
+    profiles: [ProfileDiskUsage],
+    shared: {
+        versions, libraries, assets, natives, java_versions, caches, total
+    },
+    total: u64,
+}
+*/
+export async function get(refresh = false) {
+  return await invoke('plugin:disk_usage|disk_usage_get', { refresh })
+}
+
+// Gets the disk usage in bytes of a profile
+/*
+ProfileDiskUsage {
+    profile_path: String,
+    mods, resource_packs, shader_packs, saves, logs, crash_reports, screenshots, other, total
+}
+*/
+export async function get_profile(path, refresh = false) {
+  return await invoke('plugin:disk_usage|disk_usage_get_profile', { path, refresh })
+}
+
+// Removes the versions, libraries, assets, natives, icons and JREs that no instance uses, including
+// instances kept in snapshots and in the trash. If dryRun, only reports what would be removed
+/*
+GcReport {
+    dry_run: bool,
+    versions, libraries, assets, natives, icons, java_versions: {
+        paths: [String], // relative to their directory
+        size: u64,
+    },
+    total: u64, // bytes reclaimed, or reclaimable on a dry run
+}
+*/
+export async function collect_garbage(dryRun = false) {
+  return await invoke('plugin:disk_usage|disk_usage_collect_garbage', { dryRun })
+}
@@ -45,3 +45,40 @@ export async function install_from_file(path) {
   )
   return await invoke('plugin:pack|pack_install', { location, profile })
 }
+
+// Installs a packwiz pack from a local folder or the URL of its pack.toml
+export async function install_from_packwiz(source) {
+  const location = {
+    type: 'fromFile',
+    path: source,
+  }
+  const profile_creator = await invoke('plugin:pack|pack_get_profile_from_pack', { location })
+  const profile = await create(
+    profile_creator.name,
+    profile_creator.gameVersion,
+    profile_creator.modloader,
+    profile_creator.loaderVersion,
+    null,
+    true,
+  )
+  return await invoke('plugin:pack|pack_install_packwiz', { source, profile })
+}
+
+// Installs a CurseForge modpack zip from a path
+// Returns the files linked to Modrinth, downloaded from CurseForge, and missing
+export async function install_from_curseforge(path) {
+  const location = {
+    type: 'fromFile',
+    path: path,
+  }
+  const profile_creator = await invoke('plugin:pack|pack_get_profile_from_pack', { location })
+  const profile = await create(
+    profile_creator.name,
+    profile_creator.gameVersion,
+    profile_creator.modloader,
+    profile_creator.loaderVersion,
+    null,
+    true,
+  )
+  return await invoke('plugin:pack|pack_install_curseforge', { path, profile })
+}
@@ -21,3 +21,8 @@ export async function get_all() {
 export async function kill(pid) {
   return await invoke('plugin:process|process_kill', { pid })
 }
+
+/// Sends a command to a process's console, such as a running server's
+export async function send_command(pid, command) {
+  return await invoke('plugin:process|process_send_command', { pid, command })
+}
@@ -14,9 +14,17 @@ import { invoke } from '@tauri-apps/api/tauri'
     loader_version: String, // the modloader version to use, set to "latest", "stable", or the ID of your chosen loader
     icon: Path,  // the icon for the profile
     - icon is a path to an image file, which will be copied into the profile directory
+    profile_type: ProfileType, // 'client' (the default) or 'server', for a dedicated server
 */
-
-export async function create(name, gameVersion, modloader, loaderVersion, iconPath, skipInstall) {
+export async function create(
+  name,
+  gameVersion,
+  modloader,
+  loaderVersion,
+  iconPath,
+  skipInstall,
+  profileType = null,
+) {
   //Trim string name to avoid "Unable to find directory"
   name = name.trim()
   return await invoke('plugin:profile_create|profile_create', {
@@ -26,6 +34,7 @@ export async function create(name, gameVersion, modloader, loaderVersion, iconPa
     loaderVersion,
     iconPath,
     skipInstall,
+    profileType,
   })
 }
 
@@ -98,10 +107,101 @@ export async function update_project(path, projectPath) {
   return await invoke('plugin:profile|profile_update_project', { path, projectPath })
 }
 
+// Gets the changelogs of every project of a profile with an update available
+// Returns a list of { project_path, project_id, installed_version_id, update_version_id, versions }
+export async function get_pending_changelogs(path) {
+  return await invoke('plugin:profile|profile_get_pending_changelogs', { path })
+}
+
+// Gets how a project of a profile is updated
+// Returns { profile_path, project_id, channels, mode }
+export async function get_update_policy(path, projectId) {
+  return await invoke('plugin:profile|profile_get_update_policy', { path, projectId })
+}
+
+// Sets how a project of a profile is updated
+// channels is a list of 'release', 'beta' and 'alpha'
+// mode is 'compatible', 'pin' (never update) or 'follow' (newest version for any game version)
+export async function set_update_policy(path, projectId, channels, mode) {
+  return await invoke('plugin:profile|profile_set_update_policy', {
+    path,
+    projectId,
+    channels,
+    mode,
+  })
+}
+
+// Gets the updates of a profile that can be rolled back, newest first
+// Returns a list of { generation, project_path, new_project_path, old_version_id, ... }
+export async function get_update_history(path) {
+  return await invoke('plugin:profile|profile_get_update_history', { path })
+}
+
+// Rolls back the latest update of a project
+// Returns the path of the restored project file
+export async function rollback_project(path, projectPath) {
+  return await invoke('plugin:profile|profile_rollback_project', { path, projectPath })
+}
+
+// Rolls back every project replaced by an update, such as update_all
+export async function rollback_update(path, generation) {
+  return await invoke('plugin:profile|profile_rollback_update', { path, generation })
+}
+
 // Add a project to a profile from a version
+// Datapacks are installed into the world with the folder name world, when it is set
 // Returns a path to the new project file
-export async function add_project_from_version(path, versionId) {
-  return await invoke('plugin:profile|profile_add_project_from_version', { path, versionId })
+export async function add_project_from_version(path, versionId, world = null) {
+  return await invoke('plugin:profile|profile_add_project_from_version', {
+    path,
+    versionId,
+    world,
+  })
+}
+
+// Get the versions that adding a version to a profile would install, following required dependencies
+// Returns { install, already_installed, unresolved }
+export async function resolve_dependencies(path, versionId) {
+  return await invoke('plugin:profile|profile_resolve_dependencies', { path, versionId })
+}
+
+// Add a project to a profile from a version, along with its missing required dependencies
+// Datapacks are installed into the world with the folder name world, when it is set
+// If dryRun is set, nothing is installed and only the plan is returned
+export async function add_project_with_dependencies(
+  path,
+  versionId,
+  world = null,
+  dryRun = false,
+) {
+  return await invoke('plugin:profile|profile_add_project_with_dependencies', {
+    path,
+    versionId,
+    world,
+    dryRun,
+  })
+}
+
+// Checks a profile's enabled projects for problems
+// Returns a list of { severity, type, ...details }
+export async function diagnose(path) {
+  return await invoke('plugin:profile|profile_diagnose', { path })
+}
+
+// Migrates a profile to another game version and/or loader, replacing every project with a
+// compatible version and disabling those without one
+// gameVersion, loader and loaderVersion can be null to keep the current ones
+// Fails if pinned projects don't work with the target, which dry runs list in pinned
+// Returns { changes, unavailable, unknown, pinned, snapshot_id, ... }. Restoring the snapshot
+// rolls the migration back. If dryRun, nothing is changed
+export async function migrate(path, gameVersion, loader, loaderVersion, dryRun = false) {
+  return await invoke('plugin:profile|profile_migrate', {
+    path,
+    gameVersion,
+    loader,
+    loaderVersion,
+    dryRun,
+  })
 }
 
 // Add a project to a profile from a path + project_type
@@ -155,6 +255,53 @@ export async function export_profile_mrpack(
   })
 }
 
+// Export a profile as a packwiz pack into a folder, such as a git checkout
+// Takes the same included_overrides as export_profile_mrpack. Files of a previous export are overwritten
+export async function export_profile_packwiz(
+  path,
+  exportLocation,
+  includedOverrides,
+  versionId,
+  description,
+  author,
+) {
+  return await invoke('plugin:profile|profile_export_packwiz', {
+    path,
+    exportLocation,
+    includedOverrides,
+    versionId,
+    description,
+    author,
+  })
+}
+
+// Export a profile as a MultiMC/Prism instance, with the game files in .minecraft/
+// If zip is set, exportLocation is a zip that Prism can import directly, otherwise a folder
+export async function export_profile_mmc(path, exportLocation, includedOverrides, zip = true) {
+  return await invoke('plugin:profile|profile_export_mmc', {
+    path,
+    exportLocation,
+    includedOverrides,
+    zip,
+  })
+}
+
+// Export a profile as a ready-to-run dedicated server, with start scripts using its memory settings
+// Client-only projects and files are left out. If zip is set, exportLocation is a zip, otherwise a folder
+export async function export_profile_server_pack(
+  path,
+  exportLocation,
+  includedOverrides,
+  zip = true,
+) {
+  return await invoke('plugin:profile|profile_export_server_pack', {
+    path,
+    exportLocation,
+    includedOverrides,
+    zip,
+  })
+}
+
 // Given a folder path, populate an array of all the subfolders
 // Intended to be used for finding potential override folders
 // profile
@@ -168,9 +315,11 @@ export async function get_pack_export_candidates(profilePath) {
 }
 
 // Run Minecraft using a pathed profile
+// quickPlay optionally starts the game straight into a world, server or realm:
+// { type: 'singleplayer', world }, { type: 'multiplayer', address } or { type: 'realm', id }
 // Returns PID of child
-export async function run(path) {
-  return await invoke('plugin:profile|profile_run', { path })
+export async function run(path, quickPlay = null) {
+  return await invoke('plugin:profile|profile_run', { path, quickPlay })
 }
 
 export async function kill(path) {
@@ -186,3 +335,276 @@ export async function edit(path, editProfile) {
 export async function edit_icon(path, iconPath) {
   return await invoke('plugin:profile|profile_edit_icon', { path, iconPath })
 }
+
+// Lists a profile's snapshots, newest first
+// Returns an array of ProfileSnapshot
+export async function list_snapshots(path) {
+  return await invoke('plugin:profile_snapshot|profile_snapshot_list', { path })
+}
+
+// Get a snapshot by id
+export async function get_snapshot(id) {
+  return await invoke('plugin:profile_snapshot|profile_snapshot_get', { id })
+}
+
+// Snapshots a profile's files and settings
+// name is optional
+export async function create_snapshot(path, name) {
+  return await invoke('plugin:profile_snapshot|profile_snapshot_create', { path, name })
+}
+
+// Restores a profile to a snapshot
+export async function restore_snapshot(id) {
+  return await invoke('plugin:profile_snapshot|profile_snapshot_restore', { id })
+}
+
+// Removes a snapshot
+export async function remove_snapshot(id) {
+  return await invoke('plugin:profile_snapshot|profile_snapshot_remove', { id })
+}
+
+// Removes all but the newest `keep` snapshots of a profile
+// Returns the ids of the removed snapshots
+export async function prune_snapshots(path, keep) {
+  return await invoke('plugin:profile_snapshot|profile_snapshot_prune', { path, keep })
+}
+
+// Lists a profile's mod sets, oldest first
+// Returns an array of ModSet
+export async function list_mod_sets(path) {
+  return await invoke('plugin:profile_mod_sets|profile_mod_sets_list', { path })
+}
+
+// Get a mod set by id
+export async function get_mod_set(id) {
+  return await invoke('plugin:profile_mod_sets|profile_mod_sets_get', { id })
+}
+
+// Creates a named set of projects that are enabled or disabled together
+// entries is an array of { project_id, hash, enabled }, matched by project id, or by hash for
+// files not on Modrinth. If exclusive, activating the set disables every mod not in it
+export async function create_mod_set(path, name, exclusive, entries) {
+  return await invoke('plugin:profile_mod_sets|profile_mod_sets_create', {
+    path,
+    name,
+    exclusive,
+    entries,
+  })
+}
+
+// Edits a mod set. Any of name, exclusive and entries can be null to leave it unchanged
+export async function edit_mod_set(id, name, exclusive, entries) {
+  return await invoke('plugin:profile_mod_sets|profile_mod_sets_edit', {
+    id,
+    name,
+    exclusive,
+    entries,
+  })
+}
+
+// Removes a mod set
+export async function remove_mod_set(id) {
+  return await invoke('plugin:profile_mod_sets|profile_mod_sets_remove', { id })
+}
+
+// Enables and disables a profile's projects as a mod set describes, all at once
+// Returns a map of the switched projects' previous paths to their new ones
+export async function activate_mod_set(id) {
+  return await invoke('plugin:profile_mod_sets|profile_mod_sets_activate', { id })
+}
+
+// Lists the worlds in a profile's saves folder, most recently played first
+// Returns an array of World
+export async function list_worlds(path) {
+  return await invoke('plugin:profile_worlds|profile_worlds_list', { path })
+}
+
+// Gets a world by its folder name
+export async function get_world(path, world) {
+  return await invoke('plugin:profile_worlds|profile_worlds_get', { path, world })
+}
+
+// Backs up a world to the profile's backups folder
+// Returns the path of the backup
+export async function backup_world(path, world) {
+  return await invoke('plugin:profile_worlds|profile_worlds_backup', { path, world })
+}
+
+// Deletes a world
+export async function delete_world(path, world) {
+  return await invoke('plugin:profile_worlds|profile_worlds_delete', { path, world })
+}
+
+// Renames a world (its display name, not its folder)
+export async function rename_world(path, world, newName) {
+  return await invoke('plugin:profile_worlds|profile_worlds_rename', { path, world, newName })
+}
+
+// Copies a world to another profile
+// Returns the folder name of the copy
+export async function copy_world(path, world, toPath) {
+  return await invoke('plugin:profile_worlds|profile_worlds_copy', { path, world, toPath })
+}
+
+// Lists a profile's multiplayer servers, in the order the game shows them
+// Returns an array of Server
+export async function list_servers(path) {
+  return await invoke('plugin:profile_servers|profile_servers_list', { path })
+}
+
+// Adds a server to a profile
+// server is { name, address, icon, accept_textures, hidden }
+// index is optional, defaulting to the end of the list
+export async function add_server(path, server, index) {
+  return await invoke('plugin:profile_servers|profile_servers_add', { path, server, index })
+}
+
+// Replaces a server's details
+export async function edit_server(path, index, server) {
+  return await invoke('plugin:profile_servers|profile_servers_edit', { path, index, server })
+}
+
+// Removes a server from a profile
+export async function remove_server(path, index) {
+  return await invoke('plugin:profile_servers|profile_servers_remove', { path, index })
+}
+
+// Moves a server to a new position in the list
+export async function reorder_server(path, from, to) {
+  return await invoke('plugin:profile_servers|profile_servers_reorder', { path, from, to })
+}
+
+// Sets whether the game uses a server's resource pack
+// policy is one of 'enabled', 'disabled', 'prompt'
+export async function set_server_accept_textures(path, index, policy) {
+  return await invoke('plugin:profile_servers|profile_servers_set_accept_textures', {
+    path,
+    index,
+    policy,
+  })
+}
+
+// Imports a list of servers into several profiles at once
+// If replace is set, each profile's list is replaced instead of merged
+export async function import_servers(paths, servers, replace) {
+  return await invoke('plugin:profile_servers|profile_servers_import', {
+    paths,
+    servers,
+    replace,
+  })
+}
+
+// Gets whether the Minecraft EULA is accepted for a server profile
+export async function get_server_eula(path) {
+  return await invoke('plugin:profile_server_config|profile_server_config_get_eula', { path })
+}
+
+// Accepts or declines the Minecraft EULA for a server profile. Servers only start once it is accepted
+export async function set_server_eula(path, accepted) {
+  return await invoke('plugin:profile_server_config|profile_server_config_set_eula', {
+    path,
+    accepted,
+  })
+}
+
+// Gets a server profile's server.properties, as an object
+// Empty until the server has started once
+export async function get_server_properties(path) {
+  return await invoke('plugin:profile_server_config|profile_server_config_get_properties', {
+    path,
+  })
+}
+
+// Edits a server profile's server.properties. Properties set to null are removed
+// Returns the properties after the edit
+export async function edit_server_properties(path, properties) {
+  return await invoke('plugin:profile_server_config|profile_server_config_edit_properties', {
+    path,
+    properties,
+  })
+}
+
+// Gets the options in one of a profile's options files, as [key, value] pairs in file order
+// file is 'vanilla' (options.txt), 'optifine' (optionsof.txt) or 'shaders' (optionsshaders.txt)
+export async function get_options(path, file) {
+  return await invoke('plugin:profile_options|profile_options_get', { path, file })
+}
+
+// Edits options in one of a profile's options files, keeping the rest of the file
+// Options set to null are removed. Returns the options after the edit
+export async function edit_options(path, file, options) {
+  return await invoke('plugin:profile_options|profile_options_edit', { path, file, options })
+}
+
+// Gets the common options of a profile's options.txt: GUI scale, FOV, language, fullscreen,
+// resource packs and keybinds
+export async function get_common_options(path) {
+  return await invoke('plugin:profile_options|profile_options_get_common', { path })
+}
+
+// Sets the common options that aren't null in a profile's options.txt
+// Returns the common options after the edit
+export async function edit_common_options(path, options) {
+  return await invoke('plugin:profile_options|profile_options_edit_common', { path, options })
+}
+
+// Lists a profile's resource packs: the enabled ones first, from the bottom of the list to the top,
+// then the other packs in resourcepacks/. Packs made for other game versions have a warning
+export async function get_resource_packs(path) {
+  return await invoke('plugin:profile_resource_packs|profile_resource_packs_list', { path })
+}
+
+// Enables or disables a resource pack by its id. Enabled packs go to the top of the list
+// Returns the resource packs after the change
+export async function set_resource_pack_enabled(path, id, enabled) {
+  return await invoke('plugin:profile_resource_packs|profile_resource_packs_set_enabled', {
+    path,
+    id,
+    enabled,
+  })
+}
+
+// Reorders a profile's enabled resource packs, given all of their ids from the bottom to the top
+// Returns the resource packs after the change
+export async function reorder_resource_packs(path, order) {
+  return await invoke('plugin:profile_resource_packs|profile_resource_packs_reorder', {
+    path,
+    order,
+  })
+}
+
+// Lists the datapacks in a world's datapacks/ folder, by their path relative to the profile
+export async function get_world_datapacks(path, world) {
+  return await invoke('plugin:profile_datapacks|profile_datapacks_list', { path, world })
+}
+
+// Adds a datapack file to a world
+// Returns the path to the datapack, relative to the profile
+export async function add_world_datapack_from_path(path, world, datapackPath) {
+  return await invoke('plugin:profile_datapacks|profile_datapacks_add_from_path', {
+    path,
+    world,
+    datapackPath,
+  })
+}
+
+// Removes a datapack from a world, moving it to the trash
+export async function remove_world_datapack(path, world, fileName) {
+  return await invoke('plugin:profile_datapacks|profile_datapacks_remove', {
+    path,
+    world,
+    fileName,
+  })
+}
+
+// Copies a datapack from a world into another world, of the same profile or another one
+// Returns the path to the copy, relative to the destination profile
+export async function copy_world_datapack(path, world, fileName, toPath, toWorld) {
+  return await invoke('plugin:profile_datapacks|profile_datapacks_copy', {
+    path,
+    world,
+    fileName,
+    toPath,
+    toWorld,
+  })
+}
@@ -0,0 +1,33 @@
+/**
+ * All theseus API calls return serialized values (both return values and errors);
+ * So, for example, addDefaultInstance creates a blank Profile object, where the Rust struct is serialized,
+ *  and deserialized into a usable JS object.
+ */
+import { invoke } from '@tauri-apps/api/tauri'
+
+// Lists the game options shared by the profiles that sync options
+// Which categories are shared is set in the settings' shared_option_categories:
+// 'keybinds', 'mouse', 'language', 'audio' and 'chat'
+// Returns an array of SharedOption
+/*
+SharedOption {
+    key: String, // key in options.txt
+    value: String,
+    modified: DateTime,
+}
+*/
+export async function list() {
+  return await invoke('plugin:shared_options|shared_options_list')
+}
+
+// Edits shared options by their options.txt key. Options set to null are removed
+// Returns the shared options after the edit
+export async function edit(options) {
+  return await invoke('plugin:shared_options|shared_options_edit', { options })
+}
+
+// Takes the shared categories' options from a profile's options.txt into the shared options
+// This happens on its own when a profile syncing options exits
+export async function pull(path) {
+  return await invoke('plugin:shared_options|shared_options_pull', { path })
+}
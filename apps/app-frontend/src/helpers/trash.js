@@ -0,0 +1,39 @@
+/**
+ * All theseus API calls return serialized values (both return values and errors);
+ * So, for example, addDefaultInstance creates a blank Profile object, where the Rust struct is serialized,
+ *  and deserialized into a usable JS object.
+ */
+import { invoke } from '@tauri-apps/api/tauri'
+
+// Lists the removed profiles and projects in the trash, newest first
+// Returns an array of TrashItem
+/*
+TrashItem {
+    id: String,
+    item_type: "profile" | "project",
+    profile_path: String,
+    project_path: String | null,
+    profile: Profile | null,
+    removed: DateTime,
+    size: u64,
+}
+*/
+export async function list() {
+  return await invoke('plugin:trash|trash_list')
+}
+
+// Restores an item from the trash
+// Returns the path of the restored profile, or of the restored project within its profile
+export async function restore(id) {
+  return await invoke('plugin:trash|trash_restore', { id })
+}
+
+// Permanently deletes an item from the trash
+export async function remove(id) {
+  return await invoke('plugin:trash|trash_delete', { id })
+}
+
+// Permanently deletes everything in the trash
+export async function empty() {
+  return await invoke('plugin:trash|trash_empty')
+}
@@ -85,6 +85,7 @@ async fn main() -> theseus::Result<()> {
         None,
         None,
         None,
+        None,
     )
     .await?;
     install_zipped_mrpack(pack, profile_path.to_string()).await?;
@@ -100,7 +101,7 @@ async fn main() -> theseus::Result<()> {
 
     println!("running");
     // Run a profile, running minecraft and store the RwLock to the process
-    let process = profile::run(&profile_path).await?;
+    let process = profile::run(&profile_path, None).await?;
 
     println!("Minecraft PID: {}", process.pid);
 
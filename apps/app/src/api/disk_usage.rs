@@ -0,0 +1,39 @@
+use crate::api::Result;
+use theseus::gc::GcReport;
+use theseus::prelude::*;
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("disk_usage")
+        .invoke_handler(tauri::generate_handler![
+            disk_usage_get,
+            disk_usage_get_profile,
+            disk_usage_collect_garbage,
+        ])
+        .build()
+}
+
+// Gets the disk usage of every profile and of the shared launcher data
+// Cached results are used unless refresh is set
+// invoke('plugin:disk_usage|disk_usage_get', { refresh })
+#[tauri::command]
+pub async fn disk_usage_get(refresh: bool) -> Result<DiskUsage> {
+    Ok(disk_usage::get(refresh).await?)
+}
+
+// Gets the disk usage of a profile
+// invoke('plugin:disk_usage|disk_usage_get_profile', { path, refresh })
+#[tauri::command]
+pub async fn disk_usage_get_profile(
+    path: &str,
+    refresh: bool,
+) -> Result<ProfileDiskUsage> {
+    Ok(disk_usage::get_profile(path, refresh).await?)
+}
+
+// Removes the versions, libraries, assets, natives, icons and JREs no profile uses
+// If dry_run is set, only reports what would be removed
+// invoke('plugin:disk_usage|disk_usage_collect_garbage', { dryRun })
+#[tauri::command]
+pub async fn disk_usage_collect_garbage(dry_run: bool) -> Result<GcReport> {
+    Ok(gc::collect(dry_run).await?)
+}
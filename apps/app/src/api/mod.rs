@@ -12,11 +12,22 @@ pub mod pack;
 pub mod process;
 pub mod profile;
 pub mod profile_create;
+pub mod profile_datapacks;
+pub mod profile_mod_sets;
+pub mod profile_options;
+pub mod profile_resource_packs;
+pub mod profile_server_config;
+pub mod profile_servers;
+pub mod profile_snapshot;
+pub mod profile_worlds;
 pub mod settings;
+pub mod shared_options;
 pub mod tags;
+pub mod trash;
 pub mod utils;
 
 pub mod cache;
+pub mod disk_usage;
 
 pub type Result<T> = std::result::Result<T, TheseusSerializableError>;
 
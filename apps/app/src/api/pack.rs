@@ -1,9 +1,14 @@
 use crate::api::Result;
+use std::path::PathBuf;
 
 use theseus::{
     pack::{
+        install_curseforge::{
+            install_zipped_curseforge, CurseForgeInstallReport,
+        },
         install_from::{CreatePackLocation, CreatePackProfile},
         install_mrpack::install_zipped_mrpack,
+        packwiz::install_packwiz,
     },
     prelude::*,
 };
@@ -12,6 +17,8 @@ pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
     tauri::plugin::Builder::new("pack")
         .invoke_handler(tauri::generate_handler![
             pack_install,
+            pack_install_packwiz,
+            pack_install_curseforge,
             pack_get_profile_from_pack,
         ])
         .build()
@@ -25,6 +32,26 @@ pub async fn pack_install(
     Ok(install_zipped_mrpack(location, profile).await?)
 }
 
+// Installs a packwiz pack into a profile, from a local folder or the URL of its pack.toml
+// invoke('plugin:pack|pack_install_packwiz', { source, profile })
+#[tauri::command]
+pub async fn pack_install_packwiz(
+    source: String,
+    profile: String,
+) -> Result<String> {
+    Ok(install_packwiz(source, profile).await?)
+}
+
+// Installs a CurseForge modpack zip into a profile, reporting which files were linked to Modrinth and which are missing
+// invoke('plugin:pack|pack_install_curseforge', { path, profile })
+#[tauri::command]
+pub async fn pack_install_curseforge(
+    path: PathBuf,
+    profile: String,
+) -> Result<CurseForgeInstallReport> {
+    Ok(install_zipped_curseforge(path, profile).await?)
+}
+
 #[tauri::command]
 pub fn pack_get_profile_from_pack(
     location: CreatePackLocation,
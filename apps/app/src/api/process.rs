@@ -8,6 +8,7 @@ pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
             process_get_by_profile_path,
             process_kill,
             process_wait_for,
+            process_send_command,
         ])
         .build()
 }
@@ -31,3 +32,10 @@ pub async fn process_kill(pid: i32) -> Result<()> {
 pub async fn process_wait_for(pid: i32) -> Result<()> {
     Ok(process::wait_for(pid).await?)
 }
+
+// Sends a command to a process's console, such as a server's
+// invoke('plugin:process|process_send_command', { pid, command })
+#[tauri::command]
+pub async fn process_send_command(pid: i32, command: &str) -> Result<()> {
+    Ok(process::send_command(pid, command).await?)
+}
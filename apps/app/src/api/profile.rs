@@ -4,6 +4,10 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use theseus::prelude::*;
+use theseus::profile::changelogs::ProjectChangelog;
+use theseus::profile::dependencies::DependencyPlan;
+use theseus::profile::doctor::Diagnostic;
+use theseus::profile::migrate::MigrationPlan;
 
 pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
     tauri::plugin::Builder::new("profile")
@@ -20,8 +24,18 @@ pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
             profile_install,
             profile_update_all,
             profile_update_project,
+            profile_get_pending_changelogs,
+            profile_get_update_policy,
+            profile_set_update_policy,
+            profile_get_update_history,
+            profile_rollback_project,
+            profile_rollback_update,
             profile_add_project_from_version,
+            profile_resolve_dependencies,
+            profile_add_project_with_dependencies,
             profile_add_project_from_path,
+            profile_diagnose,
+            profile_migrate,
             profile_toggle_disable_project,
             profile_remove_project,
             profile_update_managed_modrinth_version,
@@ -32,6 +46,9 @@ pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
             profile_edit,
             profile_edit_icon,
             profile_export_mrpack,
+            profile_export_packwiz,
+            profile_export_mmc,
+            profile_export_server_pack,
             profile_get_pack_export_candidates,
         ])
         .build()
@@ -149,14 +166,103 @@ pub async fn profile_update_project(
     Ok(profile::update_project(path, project_path, None).await?)
 }
 
+/// Gets the changelogs of every project of a profile with an update available
+/// invoke('plugin:profile|profile_get_pending_changelogs')
+#[tauri::command]
+pub async fn profile_get_pending_changelogs(
+    path: &str,
+) -> Result<Vec<ProjectChangelog>> {
+    Ok(profile::changelogs::get_pending(path).await?)
+}
+
+/// Gets how a project of a profile is updated
+/// invoke('plugin:profile|profile_get_update_policy')
+#[tauri::command]
+pub async fn profile_get_update_policy(
+    path: &str,
+    project_id: &str,
+) -> Result<UpdatePolicy> {
+    Ok(profile::get_update_policy(path, project_id).await?)
+}
+
+/// Sets how a project of a profile is updated
+/// invoke('plugin:profile|profile_set_update_policy')
+#[tauri::command]
+pub async fn profile_set_update_policy(
+    path: &str,
+    project_id: &str,
+    channels: Vec<ReleaseChannel>,
+    mode: UpdateMode,
+) -> Result<()> {
+    Ok(profile::set_update_policy(path, project_id, channels, mode).await?)
+}
+
+/// Gets the updates of a profile that can be rolled back, newest first
+/// invoke('plugin:profile|profile_get_update_history')
+#[tauri::command]
+pub async fn profile_get_update_history(
+    path: &str,
+) -> Result<Vec<ProjectUpdate>> {
+    Ok(profile::get_update_history(path).await?)
+}
+
+/// Rolls back the latest update of a project
+/// invoke('plugin:profile|profile_rollback_project')
+#[tauri::command]
+pub async fn profile_rollback_project(
+    path: &str,
+    project_path: &str,
+) -> Result<String> {
+    Ok(profile::rollback_project(path, project_path).await?)
+}
+
+/// Rolls back every project replaced by an update
+/// invoke('plugin:profile|profile_rollback_update')
+#[tauri::command]
+pub async fn profile_rollback_update(
+    path: &str,
+    generation: &str,
+) -> Result<HashMap<String, String>> {
+    Ok(profile::rollback_update(path, generation).await?)
+}
+
 // Adds a project to a profile from a version ID
+// Datapacks are installed into the given world, if any
 // invoke('plugin:profile|profile_add_project_from_version')
 #[tauri::command]
 pub async fn profile_add_project_from_version(
     path: &str,
     version_id: &str,
+    world: Option<&str>,
 ) -> Result<String> {
-    Ok(profile::add_project_from_version(path, version_id).await?)
+    Ok(profile::add_project_from_version(path, version_id, world).await?)
+}
+
+// Lists the versions that adding a version to a profile would install,
+// following its required dependencies
+// invoke('plugin:profile|profile_resolve_dependencies', {path, versionId})
+#[tauri::command]
+pub async fn profile_resolve_dependencies(
+    path: &str,
+    version_id: &str,
+) -> Result<DependencyPlan> {
+    Ok(profile::dependencies::resolve(path, version_id).await?)
+}
+
+// Adds a project to a profile from a version ID, along with its missing required dependencies
+// Datapacks are installed into the given world, if any
+// invoke('plugin:profile|profile_add_project_with_dependencies', {path, versionId, world, dryRun})
+#[tauri::command]
+pub async fn profile_add_project_with_dependencies(
+    path: &str,
+    version_id: &str,
+    world: Option<&str>,
+    dry_run: bool,
+) -> Result<DependencyPlan> {
+    Ok(profile::dependencies::add_project_with_dependencies(
+        path, version_id, world, dry_run,
+    )
+    .await?)
 }
 
 // Adds a project to a profile from a path
@@ -172,6 +278,35 @@ pub async fn profile_add_project_from_path(
     Ok(res)
 }
 
+// Checks a profile's enabled projects for incompatibilities, duplicates,
+// missing dependencies, wrong loaders and missing shader loaders
+// invoke('plugin:profile|profile_diagnose', {path})
+#[tauri::command]
+pub async fn profile_diagnose(path: &str) -> Result<Vec<Diagnostic>> {
+    Ok(profile::doctor::diagnose(path).await?)
+}
+
+// Migrates a profile to another game version and/or loader, replacing its
+// projects with compatible versions
+// invoke('plugin:profile|profile_migrate', {path, gameVersion, loader, loaderVersion, dryRun})
+#[tauri::command]
+pub async fn profile_migrate(
+    path: &str,
+    game_version: Option<String>,
+    loader: Option<ModLoader>,
+    loader_version: Option<String>,
+    dry_run: bool,
+) -> Result<MigrationPlan> {
+    Ok(profile::migrate::migrate(
+        path,
+        game_version,
+        loader,
+        loader_version,
+        dry_run,
+    )
+    .await?)
+}
+
 // Toggles disabling a project from its path
 // invoke('plugin:profile|profile_toggle_disable_project')
 #[tauri::command]
@@ -234,6 +369,67 @@ pub async fn profile_export_mrpack(
     Ok(())
 }
 
+// Exports a profile as a packwiz pack into a folder
+// invoke('plugin:profile|profile_export_packwiz', { path, exportLocation, includedOverrides, versionId, description, author })
+#[tauri::command]
+pub async fn profile_export_packwiz(
+    path: &str,
+    export_location: PathBuf,
+    included_overrides: Vec<String>,
+    version_id: Option<String>,
+    description: Option<String>,
+    author: Option<String>,
+) -> Result<()> {
+    pack::packwiz::export_packwiz(
+        path,
+        export_location,
+        included_overrides,
+        version_id,
+        description,
+        author,
+    )
+    .await?;
+    Ok(())
+}
+
+// Exports a profile as a MultiMC/Prism instance, into a folder or a zip Prism can import
+// invoke('plugin:profile|profile_export_mmc', { path, exportLocation, includedOverrides, zip })
+#[tauri::command]
+pub async fn profile_export_mmc(
+    path: &str,
+    export_location: PathBuf,
+    included_overrides: Vec<String>,
+    zip: bool,
+) -> Result<()> {
+    pack::export_mmc::export_mmc(
+        path,
+        export_location,
+        included_overrides,
+        zip,
+    )
+    .await?;
+    Ok(())
+}
+
+// Exports a profile as a ready-to-run dedicated server, into a folder or a zip
+// invoke('plugin:profile|profile_export_server_pack', { path, exportLocation, includedOverrides, zip })
+#[tauri::command]
+pub async fn profile_export_server_pack(
+    path: &str,
+    export_location: PathBuf,
+    included_overrides: Vec<String>,
+    zip: bool,
+) -> Result<()> {
+    pack::server_pack::export_server_pack(
+        path,
+        export_location,
+        included_overrides,
+        zip,
+    )
+    .await?;
+    Ok(())
+}
+
 /// See [`profile::get_pack_export_candidates`]
 #[tauri::command]
 pub async fn profile_get_pack_export_candidates(
@@ -244,12 +440,16 @@ pub async fn profile_get_pack_export_candidates(
 }
 
 // Run minecraft using a profile using the default credentials
+// If a quick play target is given, the game starts straight into it
 // Returns the UUID, which can be used to poll
 // for the actual Child in the state.
-// invoke('plugin:profile|profile_run', path)
+// invoke('plugin:profile|profile_run', {path, quickPlay})
 #[tauri::command]
-pub async fn profile_run(path: &str) -> Result<Process> {
-    let process = profile::run(path).await?;
+pub async fn profile_run(
+    path: &str,
+    quick_play: Option<QuickPlayType>,
+) -> Result<Process> {
+    let process = profile::run(path, quick_play).await?;
 
     Ok(process)
 }
@@ -257,13 +457,15 @@ pub async fn profile_run(path: &str) -> Result<Process> {
 // Run Minecraft using a profile using chosen credentials
 // Returns the UUID, which can be used to poll
 // for the actual Child in the state.
-// invoke('plugin:profile|profile_run_credentials', {path, credentials})')
+// invoke('plugin:profile|profile_run_credentials', {path, credentials, quickPlay})')
 #[tauri::command]
 pub async fn profile_run_credentials(
     path: &str,
     credentials: Credentials,
+    quick_play: Option<QuickPlayType>,
 ) -> Result<Process> {
-    let process = profile::run_credentials(path, &credentials).await?;
+    let process =
+        profile::run_credentials(path, &credentials, quick_play).await?;
 
     Ok(process)
 }
@@ -295,6 +497,9 @@ pub struct EditProfile {
     pub force_fullscreen: Option<bool>,
     pub game_resolution: Option<WindowSize>,
     pub hooks: Option<Hooks>,
+
+    pub default_options: Option<Vec<(String, String)>>,
+    pub sync_options: Option<bool>,
 }
 
 // Edits a profile
@@ -333,6 +538,11 @@ pub async fn profile_edit(path: &str, edit_profile: EditProfile) -> Result<()> {
             .clone_from(&edit_profile.custom_env_vars);
         prof.extra_launch_args
             .clone_from(&edit_profile.extra_launch_args);
+        prof.default_options
+            .clone_from(&edit_profile.default_options);
+        if let Some(sync_options) = edit_profile.sync_options {
+            prof.sync_options = sync_options;
+        }
 
         async { Ok(()) }
     })
@@ -20,6 +20,7 @@ pub async fn profile_create(
     loader_version: Option<String>, // the modloader version to use, set to "latest", "stable", or the ID of your chosen loader
     icon: Option<String>,           // the icon for the profile
     skip_install: Option<bool>,
+    profile_type: Option<ProfileType>, // whether the profile is a dedicated server
 ) -> Result<String> {
     let res = profile::create::profile_create(
         name,
@@ -29,6 +30,7 @@ pub async fn profile_create(
         icon,
         None,
         skip_install,
+        profile_type,
     )
     .await?;
     Ok(res)
@@ -0,0 +1,63 @@
+use crate::api::Result;
+use dashmap::DashMap;
+use std::path::Path;
+use theseus::prelude::*;
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("profile_datapacks")
+        .invoke_handler(tauri::generate_handler![
+            profile_datapacks_list,
+            profile_datapacks_add_from_path,
+            profile_datapacks_remove,
+            profile_datapacks_copy,
+        ])
+        .build()
+}
+
+// Lists the datapacks in a world's datapacks folder
+// invoke('plugin:profile_datapacks|profile_datapacks_list', { path, world })
+#[tauri::command]
+pub async fn profile_datapacks_list(
+    path: &str,
+    world: &str,
+) -> Result<DashMap<String, ProfileFile>> {
+    Ok(profile::datapacks::list(path, world).await?)
+}
+
+// Adds a datapack file to a world
+// invoke('plugin:profile_datapacks|profile_datapacks_add_from_path', { path, world, datapackPath })
+#[tauri::command]
+pub async fn profile_datapacks_add_from_path(
+    path: &str,
+    world: &str,
+    datapack_path: &Path,
+) -> Result<String> {
+    Ok(profile::datapacks::add_from_path(path, world, datapack_path).await?)
+}
+
+// Removes a datapack from a world
+// invoke('plugin:profile_datapacks|profile_datapacks_remove', { path, world, fileName })
+#[tauri::command]
+pub async fn profile_datapacks_remove(
+    path: &str,
+    world: &str,
+    file_name: &str,
+) -> Result<()> {
+    Ok(profile::datapacks::remove(path, world, file_name).await?)
+}
+
+// Copies a datapack from a world into another, of the same profile or another one
+// invoke('plugin:profile_datapacks|profile_datapacks_copy', { path, world, fileName, toPath, toWorld })
+#[tauri::command]
+pub async fn profile_datapacks_copy(
+    path: &str,
+    world: &str,
+    file_name: &str,
+    to_path: &str,
+    to_world: &str,
+) -> Result<String> {
+    Ok(
+        profile::datapacks::copy(path, world, file_name, to_path, to_world)
+            .await?,
+    )
+}
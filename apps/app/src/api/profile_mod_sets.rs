@@ -0,0 +1,71 @@
+use crate::api::Result;
+use std::collections::HashMap;
+use theseus::prelude::*;
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("profile_mod_sets")
+        .invoke_handler(tauri::generate_handler![
+            profile_mod_sets_list,
+            profile_mod_sets_get,
+            profile_mod_sets_create,
+            profile_mod_sets_edit,
+            profile_mod_sets_remove,
+            profile_mod_sets_activate,
+        ])
+        .build()
+}
+
+// Lists the mod sets of a profile, oldest first
+// invoke('plugin:profile_mod_sets|profile_mod_sets_list', { path })
+#[tauri::command]
+pub async fn profile_mod_sets_list(path: &str) -> Result<Vec<ModSet>> {
+    Ok(profile::mod_sets::list(path).await?)
+}
+
+// Gets a mod set by id
+// invoke('plugin:profile_mod_sets|profile_mod_sets_get', { id })
+#[tauri::command]
+pub async fn profile_mod_sets_get(id: &str) -> Result<Option<ModSet>> {
+    Ok(profile::mod_sets::get_mod_set(id).await?)
+}
+
+// Creates a mod set for a profile
+// invoke('plugin:profile_mod_sets|profile_mod_sets_create', { path, name, exclusive, entries })
+#[tauri::command]
+pub async fn profile_mod_sets_create(
+    path: &str,
+    name: String,
+    exclusive: bool,
+    entries: Vec<ModSetEntry>,
+) -> Result<ModSet> {
+    Ok(profile::mod_sets::create(path, name, exclusive, entries).await?)
+}
+
+// Edits a mod set. Fields left out are unchanged
+// invoke('plugin:profile_mod_sets|profile_mod_sets_edit', { id, name, exclusive, entries })
+#[tauri::command]
+pub async fn profile_mod_sets_edit(
+    id: &str,
+    name: Option<String>,
+    exclusive: Option<bool>,
+    entries: Option<Vec<ModSetEntry>>,
+) -> Result<ModSet> {
+    Ok(profile::mod_sets::edit(id, name, exclusive, entries).await?)
+}
+
+// Removes a mod set
+// invoke('plugin:profile_mod_sets|profile_mod_sets_remove', { id })
+#[tauri::command]
+pub async fn profile_mod_sets_remove(id: &str) -> Result<()> {
+    profile::mod_sets::remove(id).await?;
+    Ok(())
+}
+
+// Enables and disables the projects of a profile as a mod set describes
+// invoke('plugin:profile_mod_sets|profile_mod_sets_activate', { id })
+#[tauri::command]
+pub async fn profile_mod_sets_activate(
+    id: &str,
+) -> Result<HashMap<String, String>> {
+    Ok(profile::mod_sets::activate(id).await?)
+}
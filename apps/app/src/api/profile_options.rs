@@ -0,0 +1,53 @@
+use crate::api::Result;
+use std::collections::HashMap;
+use theseus::prelude::*;
+use theseus::profile::options::{CommonOptions, OptionsFile};
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("profile_options")
+        .invoke_handler(tauri::generate_handler![
+            profile_options_get,
+            profile_options_edit,
+            profile_options_get_common,
+            profile_options_edit_common,
+        ])
+        .build()
+}
+
+// Gets the options in one of a profile's options files
+// invoke('plugin:profile_options|profile_options_get', { path, file })
+#[tauri::command]
+pub async fn profile_options_get(
+    path: &str,
+    file: OptionsFile,
+) -> Result<Vec<(String, String)>> {
+    Ok(profile::options::get(path, file).await?)
+}
+
+// Edits options in one of a profile's options files. Options set to null are removed
+// invoke('plugin:profile_options|profile_options_edit', { path, file, options })
+#[tauri::command]
+pub async fn profile_options_edit(
+    path: &str,
+    file: OptionsFile,
+    options: HashMap<String, Option<String>>,
+) -> Result<Vec<(String, String)>> {
+    Ok(profile::options::edit(path, file, options).await?)
+}
+
+// Gets the common options of a profile's options.txt
+// invoke('plugin:profile_options|profile_options_get_common', { path })
+#[tauri::command]
+pub async fn profile_options_get_common(path: &str) -> Result<CommonOptions> {
+    Ok(profile::options::get_common(path).await?)
+}
+
+// Sets the common options that are set in options in a profile's options.txt
+// invoke('plugin:profile_options|profile_options_edit_common', { path, options })
+#[tauri::command]
+pub async fn profile_options_edit_common(
+    path: &str,
+    options: CommonOptions,
+) -> Result<CommonOptions> {
+    Ok(profile::options::edit_common(path, options).await?)
+}
@@ -0,0 +1,43 @@
+use crate::api::Result;
+use theseus::prelude::*;
+use theseus::profile::resource_packs::ResourcePack;
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("profile_resource_packs")
+        .invoke_handler(tauri::generate_handler![
+            profile_resource_packs_list,
+            profile_resource_packs_set_enabled,
+            profile_resource_packs_reorder,
+        ])
+        .build()
+}
+
+// Lists a profile's resource packs, the enabled ones first in order
+// invoke('plugin:profile_resource_packs|profile_resource_packs_list', { path })
+#[tauri::command]
+pub async fn profile_resource_packs_list(
+    path: &str,
+) -> Result<Vec<ResourcePack>> {
+    Ok(profile::resource_packs::list(path).await?)
+}
+
+// Enables or disables a resource pack by its id
+// invoke('plugin:profile_resource_packs|profile_resource_packs_set_enabled', { path, id, enabled })
+#[tauri::command]
+pub async fn profile_resource_packs_set_enabled(
+    path: &str,
+    id: &str,
+    enabled: bool,
+) -> Result<Vec<ResourcePack>> {
+    Ok(profile::resource_packs::set_enabled(path, id, enabled).await?)
+}
+
+// Reorders a profile's enabled resource packs, from the bottom of the list to the top
+// invoke('plugin:profile_resource_packs|profile_resource_packs_reorder', { path, order })
+#[tauri::command]
+pub async fn profile_resource_packs_reorder(
+    path: &str,
+    order: Vec<String>,
+) -> Result<Vec<ResourcePack>> {
+    Ok(profile::resource_packs::reorder(path, order).await?)
+}
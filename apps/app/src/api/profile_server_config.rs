@@ -0,0 +1,50 @@
+use crate::api::Result;
+use std::collections::HashMap;
+use theseus::prelude::*;
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("profile_server_config")
+        .invoke_handler(tauri::generate_handler![
+            profile_server_config_get_eula,
+            profile_server_config_set_eula,
+            profile_server_config_get_properties,
+            profile_server_config_edit_properties,
+        ])
+        .build()
+}
+
+// Gets whether the Minecraft EULA is accepted for a server profile
+// invoke('plugin:profile_server_config|profile_server_config_get_eula', { path })
+#[tauri::command]
+pub async fn profile_server_config_get_eula(path: &str) -> Result<bool> {
+    Ok(profile::server_config::get_eula(path).await?)
+}
+
+// Accepts or declines the Minecraft EULA for a server profile
+// invoke('plugin:profile_server_config|profile_server_config_set_eula', { path, accepted })
+#[tauri::command]
+pub async fn profile_server_config_set_eula(
+    path: &str,
+    accepted: bool,
+) -> Result<()> {
+    Ok(profile::server_config::set_eula(path, accepted).await?)
+}
+
+// Gets a server profile's server.properties
+// invoke('plugin:profile_server_config|profile_server_config_get_properties', { path })
+#[tauri::command]
+pub async fn profile_server_config_get_properties(
+    path: &str,
+) -> Result<HashMap<String, String>> {
+    Ok(profile::server_config::get_properties(path).await?)
+}
+
+// Edits a server profile's server.properties. Properties set to null are removed
+// invoke('plugin:profile_server_config|profile_server_config_edit_properties', { path, properties })
+#[tauri::command]
+pub async fn profile_server_config_edit_properties(
+    path: &str,
+    properties: HashMap<String, Option<String>>,
+) -> Result<HashMap<String, String>> {
+    Ok(profile::server_config::edit_properties(path, properties).await?)
+}
@@ -0,0 +1,91 @@
+use crate::api::Result;
+use theseus::prelude::*;
+use theseus::profile::servers::{ResourcePackPolicy, Server};
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("profile_servers")
+        .invoke_handler(tauri::generate_handler![
+            profile_servers_list,
+            profile_servers_add,
+            profile_servers_edit,
+            profile_servers_remove,
+            profile_servers_reorder,
+            profile_servers_set_accept_textures,
+            profile_servers_import,
+        ])
+        .build()
+}
+
+// Lists a profile's multiplayer servers
+// invoke('plugin:profile_servers|profile_servers_list', { path })
+#[tauri::command]
+pub async fn profile_servers_list(path: &str) -> Result<Vec<Server>> {
+    Ok(profile::servers::list(path).await?)
+}
+
+// Adds a server to a profile, at index or at the end of the list
+// invoke('plugin:profile_servers|profile_servers_add', { path, server, index })
+#[tauri::command]
+pub async fn profile_servers_add(
+    path: &str,
+    server: Server,
+    index: Option<usize>,
+) -> Result<Vec<Server>> {
+    Ok(profile::servers::add(path, server, index).await?)
+}
+
+// Replaces a server's details
+// invoke('plugin:profile_servers|profile_servers_edit', { path, index, server })
+#[tauri::command]
+pub async fn profile_servers_edit(
+    path: &str,
+    index: usize,
+    server: Server,
+) -> Result<Vec<Server>> {
+    Ok(profile::servers::edit(path, index, server).await?)
+}
+
+// Removes a server from a profile
+// invoke('plugin:profile_servers|profile_servers_remove', { path, index })
+#[tauri::command]
+pub async fn profile_servers_remove(
+    path: &str,
+    index: usize,
+) -> Result<Vec<Server>> {
+    Ok(profile::servers::remove(path, index).await?)
+}
+
+// Moves a server to a new position in the list
+// invoke('plugin:profile_servers|profile_servers_reorder', { path, from, to })
+#[tauri::command]
+pub async fn profile_servers_reorder(
+    path: &str,
+    from: usize,
+    to: usize,
+) -> Result<Vec<Server>> {
+    Ok(profile::servers::reorder(path, from, to).await?)
+}
+
+// Sets whether the game uses a server's resource pack
+// invoke('plugin:profile_servers|profile_servers_set_accept_textures', { path, index, policy })
+#[tauri::command]
+pub async fn profile_servers_set_accept_textures(
+    path: &str,
+    index: usize,
+    policy: ResourcePackPolicy,
+) -> Result<Vec<Server>> {
+    Ok(profile::servers::set_accept_textures(path, index, policy).await?)
+}
+
+// Imports a list of servers into several profiles at once
+// invoke('plugin:profile_servers|profile_servers_import', { paths, servers, replace })
+#[tauri::command]
+pub async fn profile_servers_import(
+    paths: Vec<String>,
+    servers: Vec<Server>,
+    replace: bool,
+) -> Result<()> {
+    let paths = paths.iter().map(|x| &**x).collect::<Vec<&str>>();
+    profile::servers::import(&paths, servers, replace).await?;
+    Ok(())
+}
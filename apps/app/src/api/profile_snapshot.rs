@@ -0,0 +1,65 @@
+use crate::api::Result;
+use theseus::prelude::*;
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("profile_snapshot")
+        .invoke_handler(tauri::generate_handler![
+            profile_snapshot_list,
+            profile_snapshot_get,
+            profile_snapshot_create,
+            profile_snapshot_restore,
+            profile_snapshot_remove,
+            profile_snapshot_prune,
+        ])
+        .build()
+}
+
+// Lists the snapshots of a profile, newest first
+// invoke('plugin:profile_snapshot|profile_snapshot_list', { path })
+#[tauri::command]
+pub async fn profile_snapshot_list(path: &str) -> Result<Vec<ProfileSnapshot>> {
+    Ok(profile::snapshot::list(path).await?)
+}
+
+// Gets a snapshot by id
+// invoke('plugin:profile_snapshot|profile_snapshot_get', { id })
+#[tauri::command]
+pub async fn profile_snapshot_get(id: &str) -> Result<Option<ProfileSnapshot>> {
+    Ok(profile::snapshot::get_snapshot(id).await?)
+}
+
+// Snapshots a profile
+// invoke('plugin:profile_snapshot|profile_snapshot_create', { path, name })
+#[tauri::command]
+pub async fn profile_snapshot_create(
+    path: &str,
+    name: Option<String>,
+) -> Result<ProfileSnapshot> {
+    Ok(profile::snapshot::create(path, name).await?)
+}
+
+// Restores the profile a snapshot was taken of to that snapshot
+// invoke('plugin:profile_snapshot|profile_snapshot_restore', { id })
+#[tauri::command]
+pub async fn profile_snapshot_restore(id: &str) -> Result<()> {
+    profile::snapshot::restore(id).await?;
+    Ok(())
+}
+
+// Removes a snapshot
+// invoke('plugin:profile_snapshot|profile_snapshot_remove', { id })
+#[tauri::command]
+pub async fn profile_snapshot_remove(id: &str) -> Result<()> {
+    profile::snapshot::remove(id).await?;
+    Ok(())
+}
+
+// Removes all but the newest `keep` snapshots of a profile
+// invoke('plugin:profile_snapshot|profile_snapshot_prune', { path, keep })
+#[tauri::command]
+pub async fn profile_snapshot_prune(
+    path: &str,
+    keep: usize,
+) -> Result<Vec<String>> {
+    Ok(profile::snapshot::prune(path, keep).await?)
+}
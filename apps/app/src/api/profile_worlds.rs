@@ -0,0 +1,69 @@
+use crate::api::Result;
+use std::path::PathBuf;
+use theseus::prelude::*;
+use theseus::profile::worlds::World;
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("profile_worlds")
+        .invoke_handler(tauri::generate_handler![
+            profile_worlds_list,
+            profile_worlds_get,
+            profile_worlds_backup,
+            profile_worlds_delete,
+            profile_worlds_rename,
+            profile_worlds_copy,
+        ])
+        .build()
+}
+
+// Lists the worlds in a profile's saves folder
+// invoke('plugin:profile_worlds|profile_worlds_list', { path })
+#[tauri::command]
+pub async fn profile_worlds_list(path: &str) -> Result<Vec<World>> {
+    Ok(profile::worlds::list(path).await?)
+}
+
+// Gets a world by its folder name
+// invoke('plugin:profile_worlds|profile_worlds_get', { path, world })
+#[tauri::command]
+pub async fn profile_worlds_get(path: &str, world: &str) -> Result<World> {
+    Ok(profile::worlds::get_world(path, world).await?)
+}
+
+// Backs up a world to the profile's backups folder
+// invoke('plugin:profile_worlds|profile_worlds_backup', { path, world })
+#[tauri::command]
+pub async fn profile_worlds_backup(path: &str, world: &str) -> Result<PathBuf> {
+    Ok(profile::worlds::backup(path, world).await?)
+}
+
+// Deletes a world
+// invoke('plugin:profile_worlds|profile_worlds_delete', { path, world })
+#[tauri::command]
+pub async fn profile_worlds_delete(path: &str, world: &str) -> Result<()> {
+    profile::worlds::delete(path, world).await?;
+    Ok(())
+}
+
+// Renames a world
+// invoke('plugin:profile_worlds|profile_worlds_rename', { path, world, newName })
+#[tauri::command]
+pub async fn profile_worlds_rename(
+    path: &str,
+    world: &str,
+    new_name: &str,
+) -> Result<()> {
+    profile::worlds::rename(path, world, new_name).await?;
+    Ok(())
+}
+
+// Copies a world to another profile, returning the new world's folder name
+// invoke('plugin:profile_worlds|profile_worlds_copy', { path, world, toPath })
+#[tauri::command]
+pub async fn profile_worlds_copy(
+    path: &str,
+    world: &str,
+    to_path: &str,
+) -> Result<String> {
+    Ok(profile::worlds::copy(path, world, to_path).await?)
+}
@@ -0,0 +1,36 @@
+use crate::api::Result;
+use std::collections::HashMap;
+use theseus::prelude::*;
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("shared_options")
+        .invoke_handler(tauri::generate_handler![
+            shared_options_list,
+            shared_options_edit,
+            shared_options_pull,
+        ])
+        .build()
+}
+
+// Lists the game options shared by the profiles that sync options
+// invoke('plugin:shared_options|shared_options_list')
+#[tauri::command]
+pub async fn shared_options_list() -> Result<Vec<SharedOption>> {
+    Ok(shared_options::list().await?)
+}
+
+// Edits shared options by their options.txt key. Options set to null are removed
+// invoke('plugin:shared_options|shared_options_edit', { options })
+#[tauri::command]
+pub async fn shared_options_edit(
+    options: HashMap<String, Option<String>>,
+) -> Result<Vec<SharedOption>> {
+    Ok(shared_options::edit(options).await?)
+}
+
+// Takes the shared categories' options from a profile's options.txt into the shared options
+// invoke('plugin:shared_options|shared_options_pull', { path })
+#[tauri::command]
+pub async fn shared_options_pull(path: &str) -> Result<()> {
+    Ok(shared_options::pull(path).await?)
+}
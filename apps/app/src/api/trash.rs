@@ -0,0 +1,44 @@
+use crate::api::Result;
+use theseus::prelude::*;
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("trash")
+        .invoke_handler(tauri::generate_handler![
+            trash_list,
+            trash_restore,
+            trash_delete,
+            trash_empty,
+        ])
+        .build()
+}
+
+// Lists the removed profiles and projects in the trash, newest first
+// invoke('plugin:trash|trash_list')
+#[tauri::command]
+pub async fn trash_list() -> Result<Vec<TrashItem>> {
+    Ok(trash::list().await?)
+}
+
+// Restores an item from the trash
+// Returns the path of the restored profile, or of the restored project within its profile
+// invoke('plugin:trash|trash_restore', { id })
+#[tauri::command]
+pub async fn trash_restore(id: &str) -> Result<String> {
+    Ok(trash::restore(id).await?)
+}
+
+// Permanently deletes an item from the trash
+// invoke('plugin:trash|trash_delete', { id })
+#[tauri::command]
+pub async fn trash_delete(id: &str) -> Result<()> {
+    trash::delete(id).await?;
+    Ok(())
+}
+
+// Permanently deletes everything in the trash
+// invoke('plugin:trash|trash_empty')
+#[tauri::command]
+pub async fn trash_empty() -> Result<()> {
+    trash::empty().await?;
+    Ok(())
+}
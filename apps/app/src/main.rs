@@ -138,10 +138,21 @@ fn main() {
         .plugin(api::process::init())
         .plugin(api::profile::init())
         .plugin(api::profile_create::init())
+        .plugin(api::profile_datapacks::init())
+        .plugin(api::profile_mod_sets::init())
+        .plugin(api::profile_options::init())
+        .plugin(api::profile_resource_packs::init())
+        .plugin(api::profile_server_config::init())
+        .plugin(api::profile_servers::init())
+        .plugin(api::profile_snapshot::init())
+        .plugin(api::profile_worlds::init())
         .plugin(api::settings::init())
+        .plugin(api::shared_options::init())
         .plugin(api::tags::init())
+        .plugin(api::trash::init())
         .plugin(api::utils::init())
         .plugin(api::cache::init())
+        .plugin(api::disk_usage::init())
         .invoke_handler(tauri::generate_handler![
             initialize_state,
             is_dev,
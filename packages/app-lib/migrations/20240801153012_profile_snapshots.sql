@@ -0,0 +1,21 @@
+CREATE TABLE profile_snapshots (
+    id TEXT NOT NULL,
+    profile_path TEXT NOT NULL,
+    name TEXT NULL,
+    created INTEGER NOT NULL,
+
+    game_version TEXT NOT NULL,
+    mod_loader TEXT NOT NULL,
+    mod_loader_version TEXT NULL,
+
+    -- serialized profile row at the time of the snapshot
+    profile JSONB NOT NULL,
+    -- map of relative project path -> profile file
+    projects JSONB NOT NULL,
+
+    -- size of the archive on disk, in bytes
+    size INTEGER NOT NULL,
+
+    PRIMARY KEY (id)
+);
+CREATE INDEX profile_snapshots_profile_path ON profile_snapshots(profile_path);
@@ -0,0 +1,10 @@
+CREATE TABLE content_store_refs (
+    profile_path TEXT NOT NULL,
+    -- path of the file relative to the profile, without any .disabled suffix
+    project_path TEXT NOT NULL,
+    -- sha1 of the file, which is also its key in the content store
+    hash TEXT NOT NULL,
+
+    PRIMARY KEY (profile_path, project_path)
+);
+CREATE INDEX content_store_refs_hash ON content_store_refs(hash);
@@ -0,0 +1,22 @@
+CREATE TABLE project_update_history (
+    -- id shared by every project replaced by the same update
+    generation TEXT NOT NULL,
+    profile_path TEXT NOT NULL,
+    created INTEGER NOT NULL,
+
+    -- path of the replaced file relative to the profile
+    project_path TEXT NOT NULL,
+    -- path of the file that replaced it
+    new_project_path TEXT NOT NULL,
+    -- sha1 of the replaced file, which is kept in the content store
+    hash TEXT NOT NULL,
+
+    old_version_id TEXT NULL,
+    new_version_id TEXT NOT NULL,
+
+    PRIMARY KEY (generation, project_path)
+);
+CREATE INDEX project_update_history_profile_path ON project_update_history(profile_path);
+CREATE INDEX project_update_history_hash ON project_update_history(hash);
+
+ALTER TABLE settings ADD COLUMN update_history_generations INTEGER NOT NULL DEFAULT 5;
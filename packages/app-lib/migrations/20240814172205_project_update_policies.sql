@@ -0,0 +1,11 @@
+CREATE TABLE project_update_policies (
+    profile_path TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+
+    -- array of the release channels updates may come from: release, beta, alpha
+    channels JSONB NOT NULL,
+    -- compatible, pin or follow
+    mode TEXT NOT NULL,
+
+    PRIMARY KEY (profile_path, project_id)
+);
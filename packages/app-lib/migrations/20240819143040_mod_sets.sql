@@ -0,0 +1,14 @@
+CREATE TABLE mod_sets (
+    id TEXT NOT NULL,
+    profile_path TEXT NOT NULL,
+    name TEXT NOT NULL,
+    created INTEGER NOT NULL,
+
+    -- whether activating the set disables the profile's mods that are not in it
+    exclusive INTEGER NOT NULL,
+    -- array of { project_id, hash, enabled }, matched by project id, or by hash for files not on Modrinth
+    entries JSONB NOT NULL,
+
+    PRIMARY KEY (id)
+);
+CREATE INDEX mod_sets_profile_path ON mod_sets(profile_path);
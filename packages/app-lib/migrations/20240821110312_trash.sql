@@ -0,0 +1,18 @@
+CREATE TABLE trash_items (
+    id TEXT NOT NULL,
+    -- profile or project
+    item_type TEXT NOT NULL,
+    profile_path TEXT NOT NULL,
+    -- path of the file relative to the profile, for projects
+    project_path TEXT NULL,
+    -- serialized profile row, for profiles
+    profile JSONB NULL,
+    removed INTEGER NOT NULL,
+
+    -- size of the item on disk, in bytes
+    size INTEGER NOT NULL,
+
+    PRIMARY KEY (id)
+);
+
+ALTER TABLE settings ADD COLUMN trash_retention_days INTEGER NOT NULL DEFAULT 30;
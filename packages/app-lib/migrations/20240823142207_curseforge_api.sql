@@ -0,0 +1,2 @@
+ALTER TABLE settings ADD COLUMN curseforge_api_url TEXT NOT NULL DEFAULT 'https://api.curseforge.com';
+ALTER TABLE settings ADD COLUMN curseforge_api_key TEXT NULL;
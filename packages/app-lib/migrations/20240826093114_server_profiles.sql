@@ -0,0 +1 @@
+ALTER TABLE profiles ADD COLUMN profile_type TEXT NOT NULL DEFAULT 'client';
@@ -0,0 +1 @@
+ALTER TABLE profiles ADD COLUMN default_options JSONB NULL;
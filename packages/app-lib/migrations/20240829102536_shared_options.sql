@@ -0,0 +1,11 @@
+CREATE TABLE shared_options (
+    -- key of the option in options.txt
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    modified INTEGER NOT NULL,
+
+    PRIMARY KEY (key)
+);
+
+ALTER TABLE settings ADD COLUMN shared_option_categories JSONB NOT NULL DEFAULT '["keybinds","mouse","language","audio","chat"]';
+ALTER TABLE profiles ADD COLUMN sync_options INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,9 @@
+CREATE TABLE shared_options_applied (
+    profile_path TEXT NOT NULL,
+    -- key of the option in options.txt
+    key TEXT NOT NULL,
+    -- value of the option in the profile's options.txt when it launched
+    value TEXT NOT NULL,
+
+    PRIMARY KEY (profile_path, key)
+);
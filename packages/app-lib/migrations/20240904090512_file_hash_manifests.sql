@@ -0,0 +1,4 @@
+-- file hashes cached before manifests were read from files have no manifest
+-- key, so they are hashed again along with their manifest
+DELETE FROM cache
+WHERE data_type = 'file_hash' AND json_type(data, '$.manifest') IS NULL;
@@ -0,0 +1,209 @@
+//! Theseus disk usage of profiles and of the launcher's shared data
+use crate::event::emit::{emit_loading, init_loading};
+use crate::event::LoadingBarType;
+use crate::state::{
+    DiskUsage, Profile, ProfileDiskUsage, ProjectType, SharedDiskUsage,
+};
+use crate::util::io::{self, IOError};
+use crate::State;
+use std::path::{Path, PathBuf};
+
+/// Gets the disk usage of every profile and of the shared data
+/// Results are cached until the file watcher reports changes to a profile's
+/// content folders or crash reports, or the profile's game exits, as the
+/// game writes its worlds, logs and screenshots while running.
+/// Shared data is not watched, so `refresh` recomputes everything
+#[tracing::instrument]
+pub async fn get(refresh: bool) -> crate::Result<DiskUsage> {
+    let state = State::get().await?;
+    let profiles = Profile::get_all(&state.pool).await?;
+
+    if refresh {
+        let mut cache = state.disk_usage.write().await;
+        cache.profiles.clear();
+        cache.shared = None;
+    }
+
+    let (missing, shared) = {
+        let cache = state.disk_usage.read().await;
+        (
+            profiles
+                .iter()
+                .filter(|x| !cache.profiles.contains_key(&x.path))
+                .map(|x| x.path.clone())
+                .collect::<Vec<_>>(),
+            cache.shared.clone(),
+        )
+    };
+
+    if !missing.is_empty() || shared.is_none() {
+        let loading_bar = init_loading(
+            LoadingBarType::DiskUsage,
+            (missing.len() + shared.is_none() as usize) as f64,
+            "Computing disk usage",
+        )
+        .await?;
+
+        for profile_path in missing {
+            let usage = compute_profile(&profile_path).await?;
+            state
+                .disk_usage
+                .write()
+                .await
+                .profiles
+                .insert(profile_path.clone(), usage);
+
+            emit_loading(&loading_bar, 1.0, Some(&profile_path)).await?;
+        }
+
+        if shared.is_none() {
+            let usage = compute_shared().await?;
+            state.disk_usage.write().await.shared = Some(usage);
+
+            emit_loading(&loading_bar, 1.0, Some("Shared data")).await?;
+        }
+    }
+
+    let cache = state.disk_usage.read().await;
+    let profiles = profiles
+        .iter()
+        .filter_map(|x| cache.profiles.get(&x.path).cloned())
+        .collect::<Vec<_>>();
+    let shared = cache.shared.clone().unwrap_or_default();
+    let total = profiles.iter().map(|x| x.total).sum::<u64>() + shared.total;
+
+    Ok(DiskUsage {
+        profiles,
+        shared,
+        total,
+    })
+}
+
+/// Gets the disk usage of a single profile, cached like `get`
+#[tracing::instrument]
+pub async fn get_profile(
+    profile_path: &str,
+    refresh: bool,
+) -> crate::Result<ProfileDiskUsage> {
+    let state = State::get().await?;
+
+    if !refresh {
+        if let Some(usage) =
+            state.disk_usage.read().await.profiles.get(profile_path)
+        {
+            return Ok(usage.clone());
+        }
+    }
+
+    let usage = compute_profile(profile_path).await?;
+    state
+        .disk_usage
+        .write()
+        .await
+        .profiles
+        .insert(profile_path.to_string(), usage.clone());
+
+    Ok(usage)
+}
+
+/// Forgets the cached disk usage of a profile, as its files have changed
+pub(crate) async fn invalidate(profile_path: &str) -> crate::Result<()> {
+    let state = State::get().await?;
+    state.disk_usage.write().await.profiles.remove(profile_path);
+    Ok(())
+}
+
+async fn compute_profile(
+    profile_path: &str,
+) -> crate::Result<ProfileDiskUsage> {
+    let state = State::get().await?;
+    let full_path = state.directories.profiles_dir().join(profile_path);
+
+    let mut usage = ProfileDiskUsage {
+        profile_path: profile_path.to_string(),
+        ..Default::default()
+    };
+    if !full_path.exists() {
+        return Ok(usage);
+    }
+
+    let mut read_dir = io::read_dir(&full_path).await?;
+    while let Some(entry) = read_dir
+        .next_entry()
+        .await
+        .map_err(|e| IOError::with_path(e, &full_path))?
+    {
+        let path = entry.path();
+        let size = path_size(&path).await?;
+        let name = entry.file_name();
+
+        let category = match name.to_string_lossy().as_ref() {
+            x if x == ProjectType::Mod.get_folder() => &mut usage.mods,
+            x if x == ProjectType::ResourcePack.get_folder() => {
+                &mut usage.resource_packs
+            }
+            x if x == ProjectType::ShaderPack.get_folder() => {
+                &mut usage.shader_packs
+            }
+            "saves" => &mut usage.saves,
+            "logs" => &mut usage.logs,
+            "crash-reports" => &mut usage.crash_reports,
+            "screenshots" => &mut usage.screenshots,
+            _ => &mut usage.other,
+        };
+        *category += size;
+        usage.total += size;
+    }
+
+    Ok(usage)
+}
+
+async fn compute_shared() -> crate::Result<SharedDiskUsage> {
+    let state = State::get().await?;
+    let dirs = &state.directories;
+
+    let mut usage = SharedDiskUsage {
+        versions: path_size(&dirs.versions_dir()).await?,
+        libraries: path_size(&dirs.libraries_dir()).await?,
+        assets: path_size(&dirs.assets_dir()).await?,
+        natives: path_size(&dirs.natives_dir()).await?,
+        java_versions: path_size(&dirs.java_versions_dir()).await?,
+        caches: path_size(&dirs.caches_dir()).await?,
+        total: 0,
+    };
+    usage.total = usage.versions
+        + usage.libraries
+        + usage.assets
+        + usage.natives
+        + usage.java_versions
+        + usage.caches;
+
+    Ok(usage)
+}
+
+/// Size of a file, or of everything in a folder. Symlinks are not followed
+pub(crate) async fn path_size(path: &Path) -> crate::Result<u64> {
+    let mut size = 0;
+    let mut stack: Vec<PathBuf> = vec![path.to_path_buf()];
+
+    while let Some(path) = stack.pop() {
+        let Ok(metadata) = tokio::fs::symlink_metadata(&path).await else {
+            continue;
+        };
+
+        if metadata.is_dir() {
+            let mut read_dir = io::read_dir(&path).await?;
+            while let Some(entry) = read_dir
+                .next_entry()
+                .await
+                .map_err(|e| IOError::with_path(e, &path))?
+            {
+                stack.push(entry.path());
+            }
+        } else {
+            size += metadata.len();
+        }
+    }
+
+    Ok(size)
+}
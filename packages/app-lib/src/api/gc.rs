@@ -0,0 +1,419 @@
+//! Theseus garbage collection of the game data shared between profiles:
+//! versions, libraries, assets, natives, icons and auto-installed JREs
+use crate::disk_usage::path_size;
+use crate::state::{
+    CachedEntry, DirectoryInfo, JavaVersion, ModLoader, Profile,
+    ProfileInstallStage, ProfileSnapshot, TrashItem,
+};
+use crate::util::io::{self, IOError};
+use crate::State;
+use daedalus::minecraft::{AssetsIndex, VersionInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// Icons newer than this are kept, as they may belong to a profile still
+// being created
+const ICON_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// What garbage collection removed, or would remove on a dry run
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GcReport {
+    pub dry_run: bool,
+    pub versions: GcTarget,
+    pub libraries: GcTarget,
+    pub assets: GcTarget,
+    pub natives: GcTarget,
+    pub icons: GcTarget,
+    pub java_versions: GcTarget,
+    /// Bytes reclaimed, or reclaimable on a dry run
+    pub total: u64,
+}
+
+/// Unreachable files or folders of a shared directory
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GcTarget {
+    /// Paths relative to the directory
+    pub paths: Vec<String>,
+    pub size: u64,
+}
+
+/// Removes the versions, libraries, assets, natives, icons and auto-installed
+/// JREs that no profile uses
+/// Profiles kept in snapshots and in the trash count as used, so they still
+/// work once restored. If `dry_run` is set, only reports what would be removed
+#[tracing::instrument]
+pub async fn collect(dry_run: bool) -> crate::Result<GcReport> {
+    let state = State::get().await?;
+    let dirs = &state.directories;
+
+    let mut profiles = Profile::get_all(&state.pool).await?;
+    if profiles.iter().any(|x| {
+        matches!(
+            x.install_stage,
+            ProfileInstallStage::Installing
+                | ProfileInstallStage::PackInstalling
+        )
+    }) {
+        return Err(crate::ErrorKind::InputError(
+            "Unable to collect garbage while a profile is installing"
+                .to_string(),
+        )
+        .as_error());
+    }
+    profiles.extend(
+        ProfileSnapshot::get_all(&state.pool)
+            .await?
+            .into_iter()
+            .map(|x| x.profile),
+    );
+    profiles.extend(
+        TrashItem::get_all(&state.pool)
+            .await?
+            .into_iter()
+            .filter_map(|x| x.profile),
+    );
+
+    let reachable = Reachable::find(&profiles, dirs).await?;
+
+    let mut report = GcReport {
+        dry_run,
+        versions: collect_dir_entries(&dirs.versions_dir(), |path| {
+            reachable.versions.contains(&file_name(path))
+        })
+        .await?,
+        libraries: collect_files(&dirs.libraries_dir(), |path| {
+            reachable.libraries.contains(path)
+        })
+        .await?,
+        assets: collect_assets(&reachable, dirs).await?,
+        natives: collect_dir_entries(&dirs.natives_dir(), |path| {
+            reachable.versions.contains(&file_name(path))
+        })
+        .await?,
+        icons: collect_icons(&profiles, &state.pool, dirs).await?,
+        java_versions: collect_dir_entries(&dirs.java_versions_dir(), |path| {
+            reachable.java.iter().any(|x| x.starts_with(path))
+        })
+        .await?,
+        total: 0,
+    };
+    report.total = report.versions.size
+        + report.libraries.size
+        + report.assets.size
+        + report.natives.size
+        + report.icons.size
+        + report.java_versions.size;
+
+    if dry_run {
+        return Ok(report);
+    }
+
+    remove(&dirs.versions_dir(), &report.versions).await?;
+    remove(&dirs.libraries_dir(), &report.libraries).await?;
+    remove(&dirs.assets_dir(), &report.assets).await?;
+    remove(&dirs.natives_dir(), &report.natives).await?;
+    remove(&dirs.caches_dir().join("icons"), &report.icons).await?;
+    remove(&dirs.java_versions_dir(), &report.java_versions).await?;
+
+    state.disk_usage.write().await.shared = None;
+
+    Ok(report)
+}
+
+/// Everything in the shared directories the profiles use
+struct Reachable {
+    /// Version ids, naming folders of the versions and natives directories
+    versions: HashSet<String>,
+    /// Full paths of library files
+    libraries: HashSet<PathBuf>,
+    asset_indexes: HashSet<String>,
+    asset_objects: HashSet<String>,
+    legacy_assets: bool,
+    /// Full paths of Java executables
+    java: Vec<PathBuf>,
+}
+
+impl Reachable {
+    async fn find(
+        profiles: &[Profile],
+        dirs: &DirectoryInfo,
+    ) -> crate::Result<Self> {
+        let mut reachable = Reachable {
+            versions: HashSet::new(),
+            libraries: HashSet::new(),
+            asset_indexes: HashSet::new(),
+            asset_objects: HashSet::new(),
+            legacy_assets: false,
+            java: Vec::new(),
+        };
+
+        if let Ok(versions) = std::fs::read_dir(dirs.versions_dir()) {
+            for version in versions.flatten() {
+                let id = version.file_name().to_string_lossy().to_string();
+                if profiles.iter().any(|x| uses_version(x, &id)) {
+                    reachable.versions.insert(id);
+                }
+            }
+        }
+
+        for id in &reachable.versions.clone() {
+            let path = dirs.version_dir(id).join(format!("{id}.json"));
+            if !path.exists() {
+                continue;
+            }
+            let info: VersionInfo =
+                serde_json::from_slice(&io::read(&path).await?)?;
+            reachable.add_version_info(&info, dirs)?;
+        }
+
+        for index in &reachable.asset_indexes.clone() {
+            let path = dirs.assets_index_dir().join(format!("{index}.json"));
+            if !path.exists() {
+                continue;
+            }
+            let index: AssetsIndex =
+                serde_json::from_slice(&io::read(&path).await?)?;
+            reachable
+                .asset_objects
+                .extend(index.objects.into_values().map(|x| x.hash));
+        }
+
+        let state = State::get().await?;
+        reachable.java = JavaVersion::get_all(&state.pool)
+            .await?
+            .into_iter()
+            .map(|(_, x)| x.path)
+            .chain(profiles.iter().filter_map(|x| x.java_path.clone()))
+            .map(|x| {
+                let path = PathBuf::from(x);
+                io::canonicalize(&path).unwrap_or(path)
+            })
+            .collect();
+
+        Ok(reachable)
+    }
+
+    fn add_version_info(
+        &mut self,
+        info: &VersionInfo,
+        dirs: &DirectoryInfo,
+    ) -> crate::Result<()> {
+        let mut artifacts = info
+            .libraries
+            .iter()
+            .map(|x| x.name.clone())
+            .collect::<Vec<_>>();
+
+        // Processors of modded versions write their outputs to libraries
+        // named in brackets, such as `[net.minecraft:client:1.20.1:srg]`
+        for processor in info.processors.iter().flatten() {
+            artifacts.push(processor.jar.clone());
+            artifacts.extend(processor.classpath.iter().cloned());
+            artifacts
+                .extend(processor.args.iter().filter_map(|x| unbracket(x)));
+        }
+        for entry in info.data.iter().flat_map(|x| x.values()) {
+            artifacts.extend(unbracket(&entry.client));
+            artifacts.extend(unbracket(&entry.server));
+        }
+
+        for artifact in artifacts {
+            let path = daedalus::get_path_from_artifact(&artifact)?;
+            self.libraries.insert(dirs.libraries_dir().join(path));
+        }
+
+        self.asset_indexes.insert(info.asset_index.id.clone());
+        if info.assets == "legacy" {
+            self.legacy_assets = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether a profile launches a version id, which is the game version for
+/// vanilla profiles and the game version followed by the loader version
+/// otherwise
+fn uses_version(profile: &Profile, id: &str) -> bool {
+    let game_version = &profile.game_version;
+
+    match (profile.loader, profile.loader_version.as_deref()) {
+        (ModLoader::Vanilla, _) => id == game_version,
+        (_, Some(loader_version))
+            if loader_version != "latest" && loader_version != "stable" =>
+        {
+            id == format!("{game_version}-{loader_version}")
+        }
+        // The loader version is only known once resolved, so every loader
+        // version of the game version is kept
+        _ => id.starts_with(&format!("{game_version}-")),
+    }
+}
+
+fn unbracket(value: &str) -> Option<String> {
+    value
+        .strip_prefix('[')
+        .and_then(|x| x.strip_suffix(']'))
+        .map(|x| x.to_string())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Collects the files or folders directly inside a directory that are not
+/// reachable
+async fn collect_dir_entries(
+    dir: &Path,
+    reachable: impl Fn(&Path) -> bool,
+) -> crate::Result<GcTarget> {
+    let mut target = GcTarget::default();
+    if !dir.exists() {
+        return Ok(target);
+    }
+
+    let mut read_dir = io::read_dir(dir).await?;
+    while let Some(entry) = read_dir
+        .next_entry()
+        .await
+        .map_err(|e| IOError::with_path(e, dir))?
+    {
+        let path = entry.path();
+        let path = io::canonicalize(&path).unwrap_or(path);
+        if !reachable(&path) {
+            target.size += path_size(&path).await?;
+            target.paths.push(file_name(&path));
+        }
+    }
+
+    Ok(target)
+}
+
+/// Collects the files anywhere inside a directory that are not reachable
+async fn collect_files(
+    dir: &Path,
+    reachable: impl Fn(&Path) -> bool,
+) -> crate::Result<GcTarget> {
+    let mut target = GcTarget::default();
+    if !dir.exists() {
+        return Ok(target);
+    }
+
+    let mut paths = Vec::new();
+    crate::profile::add_all_recursive_folder_paths(dir, &mut paths).await?;
+    for path in paths {
+        if !reachable(&path) {
+            target.size += path_size(&path).await?;
+            target.paths.push(relative_path(dir, &path));
+        }
+    }
+
+    Ok(target)
+}
+
+async fn collect_assets(
+    reachable: &Reachable,
+    dirs: &DirectoryInfo,
+) -> crate::Result<GcTarget> {
+    let assets_dir = dirs.assets_dir();
+    let mut target = GcTarget::default();
+
+    for (dir, reachable) in [
+        (dirs.assets_index_dir(), &reachable.asset_indexes),
+        (dirs.objects_dir(), &reachable.asset_objects),
+    ] {
+        let dir_target = collect_files(&dir, |path| {
+            let name = path
+                .file_stem()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default();
+            reachable.contains(&name)
+        })
+        .await?;
+
+        target.size += dir_target.size;
+        target.paths.extend(
+            dir_target
+                .paths
+                .into_iter()
+                .map(|x| relative_path(&assets_dir, &dir.join(x))),
+        );
+    }
+
+    // Legacy assets are copied out of the objects for old versions
+    let legacy_dir = dirs.legacy_assets_dir();
+    if !reachable.legacy_assets && legacy_dir.exists() {
+        target.size += path_size(&legacy_dir).await?;
+        target.paths.push(relative_path(&assets_dir, &legacy_dir));
+    }
+
+    Ok(target)
+}
+
+async fn collect_icons(
+    profiles: &[Profile],
+    pool: &sqlx::SqlitePool,
+    dirs: &DirectoryInfo,
+) -> crate::Result<GcTarget> {
+    let used = profiles
+        .iter()
+        .filter_map(|x| x.icon_path.clone())
+        .chain(CachedEntry::get_manifest_icons(pool).await?)
+        .map(|x| file_name(Path::new(&x)))
+        .collect::<HashSet<_>>();
+
+    let now = SystemTime::now();
+    collect_dir_entries(&dirs.caches_dir().join("icons"), |path| {
+        let recent = std::fs::metadata(path)
+            .and_then(|x| x.modified())
+            .map(|x| {
+                now.duration_since(x).unwrap_or_default() < ICON_GRACE_PERIOD
+            })
+            .unwrap_or(true);
+
+        recent || used.contains(&file_name(path))
+    })
+    .await
+}
+
+/// Removes the collected paths of a directory, along with the folders they
+/// leave empty
+async fn remove(dir: &Path, target: &GcTarget) -> crate::Result<()> {
+    for path in &target.paths {
+        let path = dir.join(path);
+
+        if path.is_dir() {
+            io::remove_dir_all(&path).await?;
+        } else if path.exists() {
+            io::remove_file(&path).await?;
+        }
+
+        let mut parent = path.parent();
+        while let Some(folder) = parent {
+            if folder == dir
+                || !folder.starts_with(dir)
+                || std::fs::read_dir(folder)
+                    .map(|mut x| x.next().is_some())
+                    .unwrap_or(true)
+            {
+                break;
+            }
+
+            io::remove_dir_all(folder).await?;
+            parent = folder.parent();
+        }
+    }
+
+    Ok(())
+}
+
+fn relative_path(base: &Path, path: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
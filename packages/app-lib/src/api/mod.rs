@@ -1,5 +1,7 @@
 //! API for interacting with Theseus
 pub mod cache;
+pub mod disk_usage;
+pub mod gc;
 pub mod handler;
 pub mod jre;
 pub mod logs;
@@ -10,25 +12,33 @@ pub mod pack;
 pub mod process;
 pub mod profile;
 pub mod settings;
+pub mod shared_options;
 pub mod tags;
+pub mod trash;
 
 pub mod data {
     pub use crate::state::{
-        Credentials, Dependency, DirectoryInfo, Hooks, JavaVersion, LinkedData,
-        MemorySettings, ModLoader, ModrinthCredentials,
-        ModrinthCredentialsResult, Organization, Process, ProfileFile, Project,
-        ProjectType, SearchResult, SearchResults, Settings, TeamMember, Theme,
-        User, Version, WindowSize,
+        Credentials, Dependency, DirectoryInfo, DiskUsage, FileManifest, Hooks,
+        JavaVersion, LinkedData, MemorySettings, ModLoader, ModSet,
+        ModSetEntry, ModrinthCredentials, ModrinthCredentialsResult,
+        Organization, Process, ProfileDiskUsage, ProfileFile, ProfileSnapshot,
+        ProfileType, Project, ProjectType, ProjectUpdate, ReleaseChannel,
+        SearchResult, SearchResults, Settings, SharedDiskUsage, SharedOption,
+        SharedOptionCategory, TeamMember, Theme, TrashItem, TrashItemType,
+        UpdateMode, UpdatePolicy, User, Version, WindowSize,
     };
+
+    pub use crate::launcher::QuickPlayType;
 }
 
 pub mod prelude {
     pub use crate::{
         data::*,
+        disk_usage,
         event::CommandPayload,
         jre, metadata, minecraft_auth, mr_auth, pack, process,
         profile::{self, create, Profile},
-        settings,
+        settings, shared_options, trash,
         util::io::{canonicalize, IOError},
         State,
     };
@@ -0,0 +1,261 @@
+//! Export of profiles as MultiMC/Prism instances
+use crate::event::emit::{emit_loading, init_loading};
+use crate::event::LoadingBarType;
+use crate::launcher::get_loader_version_from_profile;
+use crate::pack::import::mmc::{MMCComponent, MMCPack};
+use crate::profile::{self, add_all_recursive_folder_paths};
+use crate::state::{ModLoader, Settings};
+use crate::util::io::{self, IOError};
+use crate::State;
+use async_zip::tokio::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use std::path::{Path, PathBuf};
+use tokio::fs::File;
+
+/// Exports a profile as a MultiMC/Prism instance: an `instance.cfg`, an
+/// `mmc-pack.json` with the profile's components and the included files in
+/// `.minecraft/`
+/// The instance is written into the `export_path` folder, or into a zip at
+/// `export_path` that Prism can import directly
+#[tracing::instrument]
+pub async fn export_mmc(
+    profile_path: &str,
+    export_path: PathBuf,
+    included_export_candidates: Vec<String>, // which folders/files to include in the export
+    zip: bool,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let profile = profile::get(profile_path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(profile_path.to_string())
+    })?;
+    let profile_base_path = profile::get_full_path(profile_path).await?;
+    let settings = Settings::get(&state.pool).await?;
+
+    let included = |path: &str| {
+        !path.ends_with(".DS_Store")
+            && included_export_candidates
+                .iter()
+                .any(|x| path == x || path.starts_with(&format!("{x}/")))
+    };
+
+    let mut path_list = Vec::new();
+    add_all_recursive_folder_paths(&profile_base_path, &mut path_list).await?;
+
+    let loading_bar = init_loading(
+        LoadingBarType::ZipExtract {
+            profile_path: profile.path.clone(),
+            profile_name: profile.name.clone(),
+        },
+        path_list.len() as f64,
+        "Exporting profile to MultiMC/Prism",
+    )
+    .await?;
+
+    let mut writer = if zip {
+        let file = File::create(&export_path)
+            .await
+            .map_err(|e| IOError::with_path(e, &export_path))?;
+        MMCExportWriter::Zip(ZipFileWriter::with_tokio(file))
+    } else {
+        io::create_dir_all(&export_path).await?;
+        MMCExportWriter::Directory(export_path)
+    };
+
+    // Prism picks up an icon named after the icon key from the instance's folder
+    let mut icon_key = None;
+    if let Some(icon_path) = &profile.icon_path {
+        let icon_path = Path::new(icon_path);
+        if let (Some(stem), Ok(data)) =
+            (icon_path.file_stem(), io::read(icon_path).await)
+        {
+            let key = stem.to_string_lossy().to_string();
+            let extension = icon_path
+                .extension()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_else(|| "png".to_string());
+            writer.write(&format!("{key}.{extension}"), &data).await?;
+            icon_key = Some(key);
+        }
+    }
+
+    let memory = profile.memory.unwrap_or(settings.memory).maximum;
+    let jvm_args = profile
+        .extra_launch_args
+        .as_ref()
+        .unwrap_or(&settings.extra_launch_args)
+        .iter()
+        .map(|x| {
+            if x.contains(' ') {
+                format!("\"{x}\"")
+            } else {
+                x.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut instance_cfg = vec![
+        "[General]".to_string(),
+        "ConfigVersion=1.2".to_string(),
+        "InstanceType=OneSix".to_string(),
+        format!("name={}", profile.name),
+        format!("iconKey={}", icon_key.as_deref().unwrap_or("default")),
+        "OverrideMemory=true".to_string(),
+        format!("MaxMemAlloc={memory}"),
+        format!("MinMemAlloc={}", memory.min(512)),
+    ];
+    if let Some(java_path) = &profile.java_path {
+        instance_cfg.push("OverrideJavaLocation=true".to_string());
+        instance_cfg.push(format!("JavaPath={java_path}"));
+    }
+    if !jvm_args.is_empty() {
+        instance_cfg.push("OverrideJavaArgs=true".to_string());
+        instance_cfg.push(format!("JvmArgs={jvm_args}"));
+    }
+    writer
+        .write("instance.cfg", instance_cfg.join("\n").as_bytes())
+        .await?;
+
+    // Prism needs an exact loader version, where profiles may ask for the
+    // latest or stable one
+    let loader_version = if profile.loader == ModLoader::Vanilla {
+        None
+    } else {
+        Some(
+            get_loader_version_from_profile(
+                &profile.game_version,
+                profile.loader,
+                profile.loader_version.as_deref(),
+            )
+            .await?
+            .ok_or_else(|| {
+                crate::ErrorKind::InputError(format!(
+                    "No {} version is available for {}",
+                    profile.loader.as_str(),
+                    profile.game_version
+                ))
+            })?
+            .id,
+        )
+    };
+    let mmc_pack = MMCPack {
+        components: mmc_components(
+            &profile.game_version,
+            profile.loader,
+            loader_version.as_deref(),
+        ),
+        format_version: 1,
+    };
+    writer
+        .write("mmc-pack.json", &serde_json::to_vec_pretty(&mmc_pack)?)
+        .await?;
+
+    for path in path_list {
+        emit_loading(&loading_bar, 1.0, None).await?;
+
+        let relative_path = path
+            .strip_prefix(&profile_base_path)?
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        if !included(&relative_path) || !path.is_file() {
+            continue;
+        }
+
+        let data = io::read(&path).await?;
+        writer
+            .write(&format!(".minecraft/{relative_path}"), &data)
+            .await?;
+    }
+
+    if let MMCExportWriter::Zip(writer) = writer {
+        writer.close().await?;
+    }
+
+    Ok(())
+}
+
+enum MMCExportWriter {
+    Directory(PathBuf),
+    Zip(ZipFileWriter<File>),
+}
+
+impl MMCExportWriter {
+    async fn write(&mut self, path: &str, data: &[u8]) -> crate::Result<()> {
+        match self {
+            MMCExportWriter::Directory(export_path) => {
+                let path = export_path.join(path);
+                if let Some(parent) = path.parent() {
+                    io::create_dir_all(parent).await?;
+                }
+                io::write(&path, data).await?;
+            }
+            MMCExportWriter::Zip(writer) => {
+                let builder =
+                    ZipEntryBuilder::new(path.into(), Compression::Deflate);
+                writer.write_entry_whole(builder, data).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The components of an instance for a game and loader version
+fn mmc_components(
+    game_version: &str,
+    loader: ModLoader,
+    loader_version: Option<&str>,
+) -> Vec<MMCComponent> {
+    let component = |uid: &str, version: &str| MMCComponent {
+        uid: uid.to_string(),
+        version: Some(version.to_string()),
+        dependency_only: false,
+        important: false,
+        disabled: false,
+        cached_name: None,
+        cached_version: None,
+        cached_requires: Vec::new(),
+        cached_conflicts: Vec::new(),
+    };
+
+    let mut components = vec![MMCComponent {
+        important: true,
+        ..component("net.minecraft", game_version)
+    }];
+
+    let Some(loader_version) = loader_version else {
+        return components;
+    };
+    // Forge versions are prefixed with the game version, MultiMC's are not
+    let loader_version = loader_version
+        .strip_prefix(&format!("{game_version}-"))
+        .unwrap_or(loader_version);
+
+    match loader {
+        ModLoader::Vanilla => {}
+        ModLoader::Forge => {
+            components.push(component("net.minecraftforge", loader_version))
+        }
+        ModLoader::NeoForge => {
+            components.push(component("net.neoforged", loader_version))
+        }
+        ModLoader::Fabric | ModLoader::Quilt => {
+            components.push(MMCComponent {
+                dependency_only: true,
+                ..component("net.fabricmc.intermediary", game_version)
+            });
+            components.push(component(
+                if loader == ModLoader::Fabric {
+                    "net.fabricmc.fabric-loader"
+                } else {
+                    "org.quiltmc.quilt-loader"
+                },
+                loader_version,
+            ));
+        }
+    }
+
+    components
+}
@@ -81,8 +81,8 @@ pub enum MMCManagedPackType {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct MMCPack {
-    components: Vec<MMCComponent>,
-    format_version: u32,
+    pub components: Vec<MMCComponent>,
+    pub format_version: u32,
 }
 
 // https://github.com/PrismLauncher/PrismLauncher/blob/develop/launcher/minecraft/Component.h
@@ -91,7 +91,7 @@ pub struct MMCPack {
 pub struct MMCComponent {
     pub uid: String,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
     #[serde(default)]
     pub dependency_only: bool,
@@ -101,7 +101,9 @@ pub struct MMCComponent {
     #[serde(default)]
     pub disabled: bool,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cached_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cached_version: Option<String>,
 
     #[serde(default)]
@@ -156,7 +156,7 @@ pub async fn import_instance(
         Ok(_) => {}
         Err(e) => {
             tracing::warn!("Import failed: {:?}", e);
-            let _ = crate::api::profile::remove(profile_path).await;
+            let _ = crate::api::profile::delete(profile_path).await;
             return Err(e);
         }
     }
@@ -282,6 +282,13 @@ pub async fn copy_dotminecraft(
             })?;
         let dst_child = profile_path.join(dst_child);
 
+        // Files that were already placed, such as ones linked from the content
+        // store, are left as-is
+        if dst_child.exists() {
+            emit_loading(&loading_bar, 1.0, None).await?;
+            continue;
+        }
+
         // sleep for cpu for 1 millisecond
         tokio::time::sleep(std::time::Duration::from_millis(1)).await;
 
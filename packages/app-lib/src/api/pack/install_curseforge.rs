@@ -0,0 +1,658 @@
+//! Install of CurseForge modpack zips: a `manifest.json` listing CurseForge
+//! files by project and file ID, and an overrides folder
+use crate::event::emit::{
+    emit_loading, emit_warning, init_loading, loading_try_for_each_concurrent,
+};
+use crate::event::LoadingBarType;
+use crate::pack::install_from::{
+    set_profile_information, CreatePackDescription, PackDependency,
+};
+use crate::state::{
+    cache_file_hash, CachedEntry, ContentRef, ProjectType, Settings,
+};
+use crate::util::fetch::{
+    fetch, fetch_advanced, fingerprint_async, sha1_async, write,
+};
+use crate::util::io;
+use crate::{profile, State};
+use async_zip::base::read::seek::ZipFileReader;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Component, Path, PathBuf};
+
+/// `manifest.json` of a CurseForge modpack
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeManifest {
+    pub minecraft: CurseForgeManifestMinecraft,
+    pub manifest_type: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub author: Option<String>,
+    #[serde(default)]
+    pub files: Vec<CurseForgeManifestFile>,
+    #[serde(default = "default_overrides")]
+    pub overrides: String,
+}
+
+fn default_overrides() -> String {
+    "overrides".to_string()
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeManifestMinecraft {
+    pub version: String,
+    #[serde(default)]
+    pub mod_loaders: Vec<CurseForgeManifestModLoader>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CurseForgeManifestModLoader {
+    /// The loader and its version, such as `forge-47.1.0`
+    pub id: String,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CurseForgeManifestFile {
+    #[serde(rename = "projectID")]
+    pub project_id: u32,
+    #[serde(rename = "fileID")]
+    pub file_id: u32,
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// What became of the files of an installed CurseForge modpack
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CurseForgeInstallReport {
+    pub profile_path: String,
+    /// Files found on Modrinth by their hash, downloaded from Modrinth and
+    /// linked to their projects
+    pub linked: Vec<CurseForgeFileReport>,
+    /// Files not found on Modrinth, downloaded from CurseForge
+    pub downloaded: Vec<CurseForgeFileReport>,
+    /// Files that could not be installed, as they are not on Modrinth and
+    /// their authors only allow downloads through CurseForge. They have to be
+    /// downloaded by hand
+    pub missing: Vec<CurseForgeFileReport>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CurseForgeFileReport {
+    pub project_id: u32,
+    pub file_id: u32,
+    /// Name of the CurseForge project, if it is known
+    pub name: Option<String>,
+    pub file_name: Option<String>,
+    /// Path the file was installed to, relative to the profile
+    pub path: Option<String>,
+    /// Page of the project on CurseForge
+    pub url: Option<String>,
+    pub required: bool,
+}
+
+#[derive(Deserialize)]
+struct CurseForgeResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeMod {
+    id: u32,
+    name: String,
+    class_id: Option<u32>,
+    links: Option<CurseForgeModLinks>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeModLinks {
+    website_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeFile {
+    id: u32,
+    file_name: String,
+    download_url: Option<String>,
+    #[serde(default)]
+    hashes: Vec<CurseForgeFileHash>,
+    file_fingerprint: u64,
+}
+
+#[derive(Deserialize)]
+struct CurseForgeFileHash {
+    value: String,
+    algo: u32,
+}
+
+/// CurseForge's hash algorithm ID for sha1
+const HASH_ALGO_SHA1: u32 = 1;
+
+/// Where a file of the pack is downloaded from
+enum FileSource {
+    /// A Modrinth download with the same sha1
+    Modrinth { url: String, sha1: String },
+    /// CurseForge's download, verified by sha1 if CurseForge knows it, or else
+    /// by its fingerprint
+    CurseForge {
+        url: String,
+        sha1: Option<String>,
+        fingerprint: u64,
+    },
+}
+
+struct ResolvedFile {
+    source: FileSource,
+    project_path: String,
+}
+
+/// Installs a CurseForge modpack zip into a profile
+/// Files are looked up with the CurseForge API set in the settings. Those
+/// found on Modrinth by their hash are downloaded from there and linked, the
+/// others from CurseForge where their authors allow it. Files that can't be
+/// downloaded are reported and warned about. If anything fails, the profile is
+/// removed
+#[tracing::instrument]
+pub async fn install_zipped_curseforge(
+    path: PathBuf,
+    profile_path: String,
+) -> crate::Result<CurseForgeInstallReport> {
+    let result = install_zipped_curseforge_files(&path, &profile_path).await;
+
+    match result {
+        Ok(report) => Ok(report),
+        Err(err) => {
+            let _ = crate::api::profile::delete(&profile_path).await;
+
+            Err(err)
+        }
+    }
+}
+
+async fn install_zipped_curseforge_files(
+    path: &Path,
+    profile_path: &str,
+) -> crate::Result<CurseForgeInstallReport> {
+    let state = &State::get().await?;
+    let file = bytes::Bytes::from(io::read(path).await?);
+
+    let reader: Cursor<&bytes::Bytes> = Cursor::new(&file);
+    let mut zip_reader =
+        ZipFileReader::with_tokio(reader).await.map_err(|_| {
+            crate::Error::from(crate::ErrorKind::InputError(
+                "Failed to read input modpack zip".to_string(),
+            ))
+        })?;
+
+    let zip_index = zip_reader
+        .file()
+        .entries()
+        .iter()
+        .position(|f| {
+            f.filename().as_str().unwrap_or_default() == "manifest.json"
+        })
+        .ok_or_else(|| {
+            crate::ErrorKind::InputError(
+                "No manifest.json found in CurseForge modpack".to_string(),
+            )
+        })?;
+
+    let mut manifest = String::new();
+    let mut reader = zip_reader.reader_with_entry(zip_index).await?;
+    reader.read_to_string_checked(&mut manifest).await?;
+    let manifest: CurseForgeManifest = serde_json::from_str(&manifest)?;
+
+    if manifest.manifest_type != "minecraftModpack" {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Unsupported CurseForge manifest type {}",
+            manifest.manifest_type
+        ))
+        .into());
+    }
+
+    let mut dependencies = HashMap::new();
+    dependencies.insert(
+        PackDependency::Minecraft,
+        manifest.minecraft.version.clone(),
+    );
+    let mod_loader = manifest
+        .minecraft
+        .mod_loaders
+        .iter()
+        .find(|x| x.primary)
+        .or_else(|| manifest.minecraft.mod_loaders.first());
+    if let Some(mod_loader) = mod_loader {
+        let (dependency, version) = match mod_loader.id.split_once('-') {
+            Some(("forge", version)) => (PackDependency::Forge, version),
+            Some(("neoforge", version)) => (PackDependency::NeoForge, version),
+            Some(("fabric", version)) => {
+                (PackDependency::FabricLoader, version)
+            }
+            Some(("quilt", version)) => (PackDependency::QuiltLoader, version),
+            _ => {
+                return Err(crate::ErrorKind::InputError(format!(
+                    "Unsupported mod loader {}",
+                    mod_loader.id
+                ))
+                .into())
+            }
+        };
+        dependencies.insert(dependency, version.to_string());
+    }
+
+    set_profile_information(
+        profile_path.to_string(),
+        &CreatePackDescription {
+            icon: None,
+            override_title: None,
+            project_id: None,
+            version_id: None,
+            existing_loading_bar: None,
+            profile_path: profile_path.to_string(),
+        },
+        &manifest.name,
+        &dependencies,
+        false,
+    )
+    .await?;
+
+    let loading_bar = init_loading(
+        LoadingBarType::PackDownload {
+            profile_path: profile_path.to_string(),
+            pack_name: manifest.name.clone(),
+            icon: None,
+            pack_id: None,
+            pack_version: manifest.version.clone(),
+        },
+        100.0,
+        "Resolving modpack files",
+    )
+    .await?;
+
+    let settings = Settings::get(&state.pool).await?;
+    let mut report = CurseForgeInstallReport {
+        profile_path: profile_path.to_string(),
+        linked: Vec::new(),
+        downloaded: Vec::new(),
+        missing: Vec::new(),
+    };
+    let mut resolved = Vec::new();
+
+    if !manifest.files.is_empty() {
+        let mut project_ids = manifest
+            .files
+            .iter()
+            .map(|x| x.project_id)
+            .collect::<Vec<_>>();
+        project_ids.sort_unstable();
+        project_ids.dedup();
+
+        let mods: Vec<CurseForgeMod> = curseforge_post(
+            "mods",
+            serde_json::json!({ "modIds": project_ids }),
+            &settings,
+        )
+        .await?;
+        let mods = mods
+            .into_iter()
+            .map(|x| (x.id, x))
+            .collect::<HashMap<_, _>>();
+
+        let file_ids =
+            manifest.files.iter().map(|x| x.file_id).collect::<Vec<_>>();
+        let files: Vec<CurseForgeFile> = curseforge_post(
+            "mods/files",
+            serde_json::json!({ "fileIds": file_ids }),
+            &settings,
+        )
+        .await?;
+        let files = files
+            .into_iter()
+            .map(|x| (x.id, x))
+            .collect::<HashMap<_, _>>();
+        emit_loading(&loading_bar, 5.0, None).await?;
+
+        // Looks up the files CurseForge knows the sha1 of on Modrinth
+        let hashes = files
+            .values()
+            .filter_map(|x| sha1_of(x).map(|x| &**x))
+            .collect::<Vec<_>>();
+        let modrinth_files = CachedEntry::get_file_many(
+            &hashes,
+            None,
+            &state.pool,
+            &state.api_semaphore,
+        )
+        .await?;
+        let mut version_ids = modrinth_files
+            .iter()
+            .map(|x| &*x.version_id)
+            .collect::<Vec<_>>();
+        version_ids.sort_unstable();
+        version_ids.dedup();
+        let versions = CachedEntry::get_version_many(
+            &version_ids,
+            None,
+            &state.pool,
+            &state.api_semaphore,
+        )
+        .await?;
+        let modrinth_urls = versions
+            .into_iter()
+            .flat_map(|x| x.files)
+            .filter_map(|x| Some((x.hashes.get("sha1")?.clone(), x.url)))
+            .collect::<HashMap<_, _>>();
+        emit_loading(&loading_bar, 5.0, None).await?;
+
+        for manifest_file in &manifest.files {
+            let project = mods.get(&manifest_file.project_id);
+            let file = files.get(&manifest_file.file_id);
+
+            let mut file_report = CurseForgeFileReport {
+                project_id: manifest_file.project_id,
+                file_id: manifest_file.file_id,
+                name: project.map(|x| x.name.clone()),
+                file_name: file.map(|x| x.file_name.clone()),
+                path: None,
+                url: project
+                    .and_then(|x| x.links.as_ref())
+                    .and_then(|x| x.website_url.clone()),
+                required: manifest_file.required,
+            };
+
+            let Some(file) = file else {
+                report.missing.push(file_report);
+                continue;
+            };
+
+            let project_type = match project.and_then(|x| x.class_id) {
+                Some(12) => ProjectType::ResourcePack,
+                Some(6552) => ProjectType::ShaderPack,
+                Some(6945) => ProjectType::DataPack,
+                _ => ProjectType::Mod,
+            };
+            let file_name = Path::new(&file.file_name)
+                .file_name()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default();
+            // Files that are not required are installed, but disabled
+            let mut project_path =
+                format!("{}/{}", project_type.get_folder(), file_name);
+            if !manifest_file.required {
+                project_path.push_str(".disabled");
+            }
+
+            let sha1 = sha1_of(file);
+            let source =
+                if let Some(url) = sha1.and_then(|x| modrinth_urls.get(x)) {
+                    FileSource::Modrinth {
+                        url: url.clone(),
+                        sha1: sha1.cloned().unwrap_or_default(),
+                    }
+                } else if let Some(url) = &file.download_url {
+                    FileSource::CurseForge {
+                        url: url.clone(),
+                        sha1: sha1.cloned(),
+                        fingerprint: file.file_fingerprint,
+                    }
+                } else {
+                    report.missing.push(file_report);
+                    continue;
+                };
+
+            file_report.path = Some(project_path.clone());
+            match source {
+                FileSource::Modrinth { .. } => report.linked.push(file_report),
+                FileSource::CurseForge { .. } => {
+                    report.downloaded.push(file_report)
+                }
+            }
+            resolved.push(ResolvedFile {
+                source,
+                project_path,
+            });
+        }
+    }
+
+    emit_loading(&loading_bar, 0.0, Some("Downloading modpack")).await?;
+
+    let profile_dir = profile::get_full_path(profile_path).await?;
+    let profile_dir = &profile_dir;
+    let num_files = resolved.len();
+
+    use futures::StreamExt;
+    loading_try_for_each_concurrent(
+        futures::stream::iter(resolved).map(Ok::<ResolvedFile, crate::Error>),
+        None,
+        Some(&loading_bar),
+        60.0,
+        num_files,
+        None,
+        |file| async move {
+            let known_sha1 = match &file.source {
+                FileSource::Modrinth { sha1, .. } => Some(sha1),
+                FileSource::CurseForge { sha1, .. } => sha1.as_ref(),
+            };
+            let stored = if let Some(sha1) = known_sha1 {
+                ContentRef::read_object(
+                    &state.directories,
+                    sha1,
+                    &state.io_semaphore,
+                )
+                .await?
+            } else {
+                None
+            };
+
+            let bytes = if let Some(bytes) = stored {
+                bytes
+            } else {
+                match &file.source {
+                    FileSource::Modrinth { url, sha1 } => {
+                        fetch(
+                            url,
+                            Some(sha1),
+                            &state.fetch_semaphore,
+                            &state.pool,
+                        )
+                        .await?
+                    }
+                    FileSource::CurseForge {
+                        url,
+                        sha1,
+                        fingerprint,
+                    } => {
+                        let bytes = fetch(
+                            url,
+                            sha1.as_deref(),
+                            &state.fetch_semaphore,
+                            &state.pool,
+                        )
+                        .await?;
+                        if sha1.is_none()
+                            && fingerprint_async(bytes.clone()).await?
+                                != *fingerprint
+                        {
+                            return Err(crate::ErrorKind::InputError(format!(
+                                "{} does not match its CurseForge fingerprint",
+                                file.project_path
+                            ))
+                            .into());
+                        }
+                        bytes
+                    }
+                }
+            };
+
+            let sha1 = match known_sha1 {
+                Some(sha1) => sha1.clone(),
+                None => sha1_async(bytes.clone()).await?,
+            };
+            cache_file_hash(
+                bytes.clone(),
+                profile_path,
+                &file.project_path,
+                Some(&sha1),
+                &state.pool,
+            )
+            .await?;
+            ContentRef::place(
+                profile_path,
+                &file.project_path,
+                &sha1,
+                &bytes,
+                profile_dir,
+                &state.directories,
+                &state.io_semaphore,
+                &state.pool,
+            )
+            .await?;
+
+            Ok(())
+        },
+    )
+    .await?;
+
+    emit_loading(&loading_bar, 0.0, Some("Extracting overrides")).await?;
+
+    let overrides_prefix = format!("{}/", manifest.overrides);
+    let overrides = zip_reader
+        .file()
+        .entries()
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            let filename = entry.filename().as_str().unwrap_or_default();
+            let relative = filename.strip_prefix(&overrides_prefix)?;
+            if relative.is_empty() || filename.ends_with('/') {
+                return None;
+            }
+
+            Some((index, relative.to_string()))
+        })
+        .collect::<Vec<_>>();
+
+    let total_len = overrides.len();
+    for (i, (index, relative)) in overrides.into_iter().enumerate() {
+        // Entries escaping the profile's folder are skipped
+        if Path::new(&relative)
+            .components()
+            .all(|x| matches!(x, Component::Normal(_) | Component::CurDir))
+        {
+            let mut content = Vec::new();
+            let mut reader = zip_reader.reader_with_entry(index).await?;
+            reader.read_to_end_checked(&mut content).await?;
+            let bytes = bytes::Bytes::from(content);
+
+            cache_file_hash(
+                bytes.clone(),
+                profile_path,
+                &relative,
+                None,
+                &state.pool,
+            )
+            .await?;
+            write(&profile_dir.join(&relative), &bytes, &state.io_semaphore)
+                .await?;
+        }
+
+        emit_loading(
+            &loading_bar,
+            30.0 / total_len as f64,
+            Some(&format!("Extracting override {}/{}", i + 1, total_len)),
+        )
+        .await?;
+    }
+
+    // CurseForge packs don't carry an icon, except as an override
+    let potential_icon = profile_dir.join("icon.png");
+    if potential_icon.exists() {
+        profile::edit_icon(profile_path, Some(&potential_icon)).await?;
+    }
+
+    if !report.missing.is_empty() {
+        emit_warning(&format!(
+            "{} files of {} could not be installed, as they are not on Modrinth and can only be downloaded through CurseForge: {}",
+            report.missing.len(),
+            manifest.name,
+            report
+                .missing
+                .iter()
+                .map(|x| x
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("project {}", x.project_id)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+        .await?;
+    }
+
+    if let Some(profile_val) = profile::get(profile_path).await? {
+        crate::launcher::install_minecraft(
+            &profile_val,
+            Some(loading_bar),
+            false,
+        )
+        .await?;
+    }
+
+    Ok(report)
+}
+
+/// Calls the CurseForge API set in the settings
+async fn curseforge_post<T: DeserializeOwned>(
+    route: &str,
+    body: serde_json::Value,
+    settings: &Settings,
+) -> crate::Result<T> {
+    let state = State::get().await?;
+    let url = format!(
+        "{}/v1/{route}",
+        settings.curseforge_api_url.trim_end_matches('/')
+    );
+
+    let bytes = fetch_advanced(
+        Method::POST,
+        &url,
+        None,
+        Some(body),
+        settings
+            .curseforge_api_key
+            .as_deref()
+            .map(|key| ("x-api-key", key)),
+        None,
+        &state.fetch_semaphore,
+        &state.pool,
+    )
+    .await?;
+
+    let response: CurseForgeResponse<T> = serde_json::from_slice(&bytes)
+        .map_err(|_| {
+            crate::ErrorKind::InputError(format!(
+                "Invalid response from the CurseForge API at {url}. Check the CurseForge API URL and key in the settings"
+            ))
+        })?;
+
+    Ok(response.data)
+}
+
+fn sha1_of(file: &CurseForgeFile) -> Option<&String> {
+    file.hashes
+        .iter()
+        .find(|x| x.algo == HASH_ALGO_SHA1)
+        .map(|x| &x.value)
+}
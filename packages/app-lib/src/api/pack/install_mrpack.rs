@@ -6,7 +6,8 @@ use crate::pack::install_from::{
     set_profile_information, EnvType, PackFile, PackFileHash,
 };
 use crate::state::{
-    cache_file_hash, CacheBehaviour, CachedEntry, ProfileInstallStage, SideType,
+    cache_file_hash, CacheBehaviour, CachedEntry, ContentRef,
+    ProfileInstallStage, SideType,
 };
 use crate::util::fetch::{fetch_mirrors, write};
 use crate::util::io;
@@ -59,7 +60,7 @@ pub async fn install_zipped_mrpack(
     match result {
         Ok(profile) => Ok(profile),
         Err(err) => {
-            let _ = crate::api::profile::remove(&profile_path).await;
+            let _ = crate::api::profile::delete(&profile_path).await;
 
             Err(err)
         }
@@ -161,17 +162,37 @@ pub async fn install_zipped_mrpack_files(
                         }
                     }
 
-                    let file = fetch_mirrors(
-                        &project
-                            .downloads
-                            .iter()
-                            .map(|x| &**x)
-                            .collect::<Vec<&str>>(),
-                        project.hashes.get(&PackFileHash::Sha1).map(|x| &**x),
-                        &state.fetch_semaphore,
-                        &state.pool,
-                    )
-                    .await?;
+                    let sha1 =
+                        project.hashes.get(&PackFileHash::Sha1).map(|x| &**x);
+
+                    // Files already in the content store don't need to be
+                    // downloaded again
+                    let stored = if let Some(sha1) = sha1 {
+                        ContentRef::read_object(
+                            &state.directories,
+                            sha1,
+                            &state.io_semaphore,
+                        )
+                        .await?
+                    } else {
+                        None
+                    };
+
+                    let file = if let Some(file) = stored {
+                        file
+                    } else {
+                        fetch_mirrors(
+                            &project
+                                .downloads
+                                .iter()
+                                .map(|x| &**x)
+                                .collect::<Vec<&str>>(),
+                            sha1,
+                            &state.fetch_semaphore,
+                            &state.pool,
+                        )
+                        .await?
+                    };
 
                     let project_path = project.path.to_string();
 
@@ -180,25 +201,39 @@ pub async fn install_zipped_mrpack_files(
                     if let Some(path) = path {
                         match path {
                             Component::CurDir | Component::Normal(_) => {
-                                let path =
+                                let profile_dir =
                                     profile::get_full_path(&profile_path)
-                                        .await?
-                                        .join(&project_path);
+                                        .await?;
 
                                 cache_file_hash(
                                     file.clone(),
                                     &profile_path,
                                     &project_path,
-                                    project
-                                        .hashes
-                                        .get(&PackFileHash::Sha1)
-                                        .map(|x| &**x),
+                                    sha1,
                                     &state.pool,
                                 )
                                 .await?;
 
-                                write(&path, &file, &state.io_semaphore)
+                                if let Some(sha1) = sha1 {
+                                    ContentRef::place(
+                                        &profile_path,
+                                        &project_path,
+                                        sha1,
+                                        &file,
+                                        &profile_dir,
+                                        &state.directories,
+                                        &state.io_semaphore,
+                                        &state.pool,
+                                    )
+                                    .await?;
+                                } else {
+                                    write(
+                                        &profile_dir.join(&project_path),
+                                        &file,
+                                        &state.io_semaphore,
+                                    )
                                     .await?;
+                                }
                             }
                             _ => {}
                         };
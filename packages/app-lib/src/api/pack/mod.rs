@@ -1,3 +1,7 @@
+pub mod export_mmc;
 pub mod import;
+pub mod install_curseforge;
 pub mod install_from;
 pub mod install_mrpack;
+pub mod packwiz;
+pub mod server_pack;
@@ -0,0 +1,720 @@
+//! Import and export of packwiz packs: a `pack.toml`, an `index.toml` and
+//! `.pw.toml` metafiles pointing to downloads
+//! <https://packwiz.infra.link/reference/pack-format/>
+use crate::event::emit::{
+    emit_loading, emit_warning, init_loading, init_or_edit_loading,
+    loading_try_for_each_concurrent,
+};
+use crate::event::LoadingBarType;
+use crate::pack::install_from::{
+    set_profile_information, CreatePackDescription, PackDependency,
+};
+use crate::profile::{self, add_all_recursive_folder_paths};
+use crate::state::{cache_file_hash, CachedEntry, ContentRef, ModLoader};
+use crate::util::fetch::{fetch, murmur2_fingerprint, sha1_async, write};
+use crate::util::io;
+use crate::State;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+const PACK_FORMAT: &str = "packwiz:1.1.0";
+const HASH_FORMAT: &str = "sha256";
+
+/// `pack.toml`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackwizPack {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pack_format: Option<String>,
+    pub index: PackwizIndexRef,
+    /// Game and loader versions, such as `minecraft` and `fabric`
+    pub versions: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackwizIndexRef {
+    pub file: String,
+    pub hash_format: String,
+    pub hash: String,
+}
+
+/// `index.toml`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackwizIndex {
+    pub hash_format: String,
+    #[serde(default)]
+    pub files: Vec<PackwizIndexFile>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackwizIndexFile {
+    /// Path relative to the index
+    pub file: String,
+    pub hash: String,
+    /// Overrides the index's hash format for this file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash_format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub metafile: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub preserve: bool,
+}
+
+/// A `.pw.toml` metafile, describing a file to download
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackwizMetafile {
+    pub name: String,
+    pub filename: String,
+    #[serde(default)]
+    pub side: PackwizSide,
+    pub download: PackwizDownload,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub option: Option<PackwizOption>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update: Option<PackwizUpdate>,
+}
+
+#[derive(
+    Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum PackwizSide {
+    Client,
+    Server,
+    #[default]
+    Both,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackwizDownload {
+    /// Missing for files only available through CurseForge's API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub hash_format: String,
+    pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackwizOption {
+    pub optional: bool,
+    #[serde(default)]
+    pub default: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackwizUpdate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modrinth: Option<PackwizModrinthUpdate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curseforge: Option<toml::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackwizModrinthUpdate {
+    pub mod_id: String,
+    pub version: String,
+}
+
+/// Where a packwiz pack is read from: a local folder holding `pack.toml`, or
+/// the URL of a `pack.toml`. Every other file is relative to it
+enum PackwizSource {
+    Directory(PathBuf),
+    Url(url::Url),
+}
+
+impl PackwizSource {
+    fn parse(source: &str) -> crate::Result<Self> {
+        if source.starts_with("http://") || source.starts_with("https://") {
+            let url = url::Url::parse(source)?;
+            // A URL without pack.toml is taken as the folder holding it
+            let url = if url.path().ends_with(".toml") {
+                url
+            } else if url.path().ends_with('/') {
+                url.join("pack.toml")?
+            } else {
+                url::Url::parse(&format!("{source}/"))?.join("pack.toml")?
+            };
+
+            Ok(PackwizSource::Url(url))
+        } else {
+            let path = PathBuf::from(source);
+            let path = if path.is_file() {
+                path
+            } else {
+                path.join("pack.toml")
+            };
+
+            Ok(PackwizSource::Directory(path))
+        }
+    }
+
+    /// Reads a file, relative to the folder holding `pack.toml`
+    async fn read(&self, path: &str) -> crate::Result<bytes::Bytes> {
+        match self {
+            PackwizSource::Directory(pack) => {
+                let relative = safe_relative_path(path)?;
+                let base = pack.parent().unwrap_or(Path::new(""));
+                Ok(bytes::Bytes::from(io::read(base.join(relative)).await?))
+            }
+            PackwizSource::Url(pack) => {
+                let state = State::get().await?;
+                fetch(
+                    pack.join(path)?.as_str(),
+                    None,
+                    &state.fetch_semaphore,
+                    &state.pool,
+                )
+                .await
+            }
+        }
+    }
+
+    async fn read_pack(&self) -> crate::Result<bytes::Bytes> {
+        match self {
+            PackwizSource::Directory(pack) => {
+                Ok(bytes::Bytes::from(io::read(pack).await?))
+            }
+            PackwizSource::Url(pack) => {
+                let state = State::get().await?;
+                fetch(pack.as_str(), None, &state.fetch_semaphore, &state.pool)
+                    .await
+            }
+        }
+    }
+}
+
+/// Installs a packwiz pack into a profile, from a local folder or the URL of
+/// its `pack.toml`
+/// Files are verified with the hash formats the pack declares. Files only
+/// available through CurseForge's API are skipped with a warning. If anything
+/// fails, the profile is removed
+#[tracing::instrument]
+pub async fn install_packwiz(
+    source: String,
+    profile_path: String,
+) -> crate::Result<String> {
+    let result = install_packwiz_files(&source, &profile_path).await;
+
+    match result {
+        Ok(profile) => Ok(profile),
+        Err(err) => {
+            let _ = crate::api::profile::delete(&profile_path).await;
+
+            Err(err)
+        }
+    }
+}
+
+async fn install_packwiz_files(
+    source: &str,
+    profile_path: &str,
+) -> crate::Result<String> {
+    let state = &State::get().await?;
+    let source = PackwizSource::parse(source)?;
+
+    let pack: PackwizPack =
+        toml::from_str(&String::from_utf8_lossy(&source.read_pack().await?))?;
+
+    let index_bytes = source.read(&pack.index.file).await?;
+    verify_hash(
+        &index_bytes,
+        &pack.index.hash_format,
+        &pack.index.hash,
+        &pack.index.file,
+    )?;
+    let index: PackwizIndex =
+        toml::from_str(&String::from_utf8_lossy(&index_bytes))?;
+    let index_dir = parent_path(&pack.index.file);
+
+    let mut dependencies = HashMap::new();
+    for (key, value) in &pack.versions {
+        let dependency = match &**key {
+            "minecraft" => PackDependency::Minecraft,
+            "forge" => PackDependency::Forge,
+            "neoforge" => PackDependency::NeoForge,
+            "fabric" => PackDependency::FabricLoader,
+            "quilt" => PackDependency::QuiltLoader,
+            _ => continue,
+        };
+        dependencies.insert(dependency, value.clone());
+    }
+
+    set_profile_information(
+        profile_path.to_string(),
+        &CreatePackDescription {
+            icon: None,
+            override_title: None,
+            project_id: None,
+            version_id: None,
+            existing_loading_bar: None,
+            profile_path: profile_path.to_string(),
+        },
+        &pack.name,
+        &dependencies,
+        false,
+    )
+    .await?;
+
+    let loading_bar = init_or_edit_loading(
+        None,
+        LoadingBarType::PackDownload {
+            profile_path: profile_path.to_string(),
+            pack_name: pack.name.clone(),
+            icon: None,
+            pack_id: None,
+            pack_version: pack.version.clone(),
+        },
+        100.0,
+        "Downloading modpack",
+    )
+    .await?;
+
+    let profile_dir = profile::get_full_path(profile_path).await?;
+    let num_files = index.files.len();
+    let source = &source;
+    let index_hash_format = &index.hash_format;
+    let index_dir = &index_dir;
+    let profile_dir = &profile_dir;
+
+    use futures::StreamExt;
+    loading_try_for_each_concurrent(
+        futures::stream::iter(index.files)
+            .map(Ok::<PackwizIndexFile, crate::Error>),
+        None,
+        Some(&loading_bar),
+        90.0,
+        num_files,
+        None,
+        |file| async move {
+            let path = join_path(index_dir, &file.file);
+            let bytes = source.read(&path).await?;
+            verify_hash(
+                &bytes,
+                file.hash_format.as_deref().unwrap_or(index_hash_format),
+                &file.hash,
+                &path,
+            )?;
+
+            let relative = file.alias.as_deref().unwrap_or(&file.file);
+            if !file.metafile {
+                let project_path = safe_relative_path(relative)?;
+                cache_file_hash(
+                    bytes.clone(),
+                    profile_path,
+                    &project_path,
+                    None,
+                    &state.pool,
+                )
+                .await?;
+                write(&profile_dir.join(&project_path), &bytes, &state.io_semaphore)
+                    .await?;
+
+                return Ok(());
+            }
+
+            let metafile: PackwizMetafile =
+                toml::from_str(&String::from_utf8_lossy(&bytes))?;
+            if metafile.side == PackwizSide::Server {
+                return Ok(());
+            }
+            let Some(url) = &metafile.download.url else {
+                emit_warning(&format!(
+                    "{} was skipped, as it can only be downloaded from CurseForge",
+                    metafile.name
+                ))
+                .await?;
+                return Ok(());
+            };
+
+            let mut project_path = safe_relative_path(&join_path(
+                &parent_path(relative),
+                &metafile.filename,
+            ))?;
+            if metafile
+                .option
+                .as_ref()
+                .is_some_and(|x| x.optional && !x.default)
+            {
+                project_path.push_str(".disabled");
+            }
+
+            let hash_format = &*metafile.download.hash_format;
+            let stored = if hash_format == "sha1" {
+                ContentRef::read_object(
+                    &state.directories,
+                    &metafile.download.hash,
+                    &state.io_semaphore,
+                )
+                .await?
+            } else {
+                None
+            };
+            let file = if let Some(file) = stored {
+                file
+            } else {
+                let file = fetch(
+                    url,
+                    (hash_format == "sha1")
+                        .then_some(&*metafile.download.hash),
+                    &state.fetch_semaphore,
+                    &state.pool,
+                )
+                .await?;
+                verify_hash(
+                    &file,
+                    hash_format,
+                    &metafile.download.hash,
+                    &metafile.filename,
+                )?;
+                file
+            };
+
+            let sha1 = sha1_async(file.clone()).await?;
+            cache_file_hash(
+                file.clone(),
+                profile_path,
+                &project_path,
+                Some(&sha1),
+                &state.pool,
+            )
+            .await?;
+            ContentRef::place(
+                profile_path,
+                &project_path,
+                &sha1,
+                &file,
+                profile_dir,
+                &state.directories,
+                &state.io_semaphore,
+                &state.pool,
+            )
+            .await?;
+
+            Ok(())
+        },
+    )
+    .await?;
+
+    if let Some(profile_val) = profile::get(profile_path).await? {
+        crate::launcher::install_minecraft(
+            &profile_val,
+            Some(loading_bar),
+            false,
+        )
+        .await?;
+    }
+
+    Ok(profile_path.to_string())
+}
+
+/// Exports a profile as a packwiz pack into a folder, such as a git checkout
+/// Projects from Modrinth are written as metafiles, and every other included
+/// file is copied. Disabled projects become optional files, off by default.
+/// Files of a previous export are overwritten
+#[tracing::instrument]
+pub async fn export_packwiz(
+    profile_path: &str,
+    export_path: PathBuf,
+    included_export_candidates: Vec<String>, // which folders/files to include in the export
+    version_id: Option<String>,
+    description: Option<String>,
+    author: Option<String>,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let profile = profile::get(profile_path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(profile_path.to_string())
+    })?;
+    let profile_base_path = profile::get_full_path(profile_path).await?;
+
+    let included = |path: &str| {
+        !path.ends_with(".DS_Store")
+            && included_export_candidates
+                .iter()
+                .any(|x| path == x || path.starts_with(&format!("{x}/")))
+    };
+
+    let projects = profile
+        .get_projects(None, &state.pool, &state.api_semaphore)
+        .await?
+        .into_iter()
+        .filter(|(path, _)| included(path))
+        .filter_map(|(path, file)| Some((path, file.hash, file.metadata?)))
+        .collect::<Vec<_>>();
+    let versions = CachedEntry::get_version_many(
+        &projects
+            .iter()
+            .map(|(_, _, x)| &*x.version_id)
+            .collect::<Vec<_>>(),
+        None,
+        &state.pool,
+        &state.api_semaphore,
+    )
+    .await?;
+    let project_infos = CachedEntry::get_project_many(
+        &projects
+            .iter()
+            .map(|(_, _, x)| &*x.project_id)
+            .collect::<Vec<_>>(),
+        None,
+        &state.pool,
+        &state.api_semaphore,
+    )
+    .await?;
+
+    let mut path_list = Vec::new();
+    add_all_recursive_folder_paths(&profile_base_path, &mut path_list).await?;
+
+    let loading_bar = init_loading(
+        LoadingBarType::ZipExtract {
+            profile_path: profile.path.clone(),
+            profile_name: profile.name.clone(),
+        },
+        (path_list.len() + projects.len()) as f64,
+        "Exporting profile to packwiz",
+    )
+    .await?;
+
+    let mut index = PackwizIndex {
+        hash_format: HASH_FORMAT.to_string(),
+        files: Vec::new(),
+    };
+    let mut exported = HashSet::new();
+
+    for (path, hash, metadata) in &projects {
+        emit_loading(&loading_bar, 1.0, None).await?;
+
+        let Some(version) =
+            versions.iter().find(|x| x.id == metadata.version_id)
+        else {
+            continue;
+        };
+        let Some(version_file) = version
+            .files
+            .iter()
+            .find(|x| x.hashes.get("sha1") == Some(hash))
+            .or_else(|| version.files.first())
+        else {
+            continue;
+        };
+        let (download_format, download_hash) =
+            if let Some(hash) = version_file.hashes.get("sha512") {
+                ("sha512", hash.clone())
+            } else {
+                ("sha1", hash.clone())
+            };
+
+        let project =
+            project_infos.iter().find(|x| x.id == metadata.project_id);
+        let slug = project
+            .and_then(|x| x.slug.clone())
+            .unwrap_or_else(|| metadata.project_id.clone());
+        let disabled = path.ends_with(".disabled");
+
+        let metafile = PackwizMetafile {
+            name: project
+                .map(|x| x.title.clone())
+                .unwrap_or_else(|| version_file.filename.clone()),
+            filename: version_file.filename.clone(),
+            side: PackwizSide::Both,
+            download: PackwizDownload {
+                url: Some(version_file.url.clone()),
+                hash_format: download_format.to_string(),
+                hash: download_hash,
+                mode: None,
+            },
+            option: disabled.then_some(PackwizOption {
+                optional: true,
+                default: false,
+                description: None,
+            }),
+            update: Some(PackwizUpdate {
+                modrinth: Some(PackwizModrinthUpdate {
+                    mod_id: metadata.project_id.clone(),
+                    version: metadata.version_id.clone(),
+                }),
+                curseforge: None,
+            }),
+        };
+
+        let metafile_path =
+            join_path(&parent_path(path), &format!("{slug}.pw.toml"));
+        let data = toml::to_string(&metafile)?;
+        write_export_file(&export_path, &metafile_path, data.as_bytes())
+            .await?;
+
+        index.files.push(PackwizIndexFile {
+            file: metafile_path,
+            hash: hash_bytes(data.as_bytes(), HASH_FORMAT)?,
+            hash_format: None,
+            alias: None,
+            metafile: true,
+            preserve: false,
+        });
+        exported.insert(path.clone());
+    }
+
+    for path in path_list {
+        emit_loading(&loading_bar, 1.0, None).await?;
+
+        let relative_path = path
+            .strip_prefix(&profile_base_path)?
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        if exported.contains(&relative_path) || !included(&relative_path) {
+            continue;
+        }
+
+        let data = io::read(&path).await?;
+        write_export_file(&export_path, &relative_path, &data).await?;
+
+        index.files.push(PackwizIndexFile {
+            file: relative_path,
+            hash: hash_bytes(&data, HASH_FORMAT)?,
+            hash_format: None,
+            alias: None,
+            metafile: false,
+            preserve: false,
+        });
+    }
+
+    // Sorted, so exports of the same profile diff cleanly
+    index.files.sort_by(|a, b| a.file.cmp(&b.file));
+    let index_data = toml::to_string(&index)?;
+    write_export_file(&export_path, "index.toml", index_data.as_bytes())
+        .await?;
+
+    let mut versions = BTreeMap::new();
+    versions.insert("minecraft".to_string(), profile.game_version.clone());
+    let loader_key = match profile.loader {
+        ModLoader::Vanilla => None,
+        ModLoader::Forge => Some("forge"),
+        ModLoader::NeoForge => Some("neoforge"),
+        ModLoader::Fabric => Some("fabric"),
+        ModLoader::Quilt => Some("quilt"),
+    };
+    if let (Some(key), Some(version)) = (loader_key, &profile.loader_version) {
+        versions.insert(key.to_string(), version.clone());
+    }
+
+    let pack = PackwizPack {
+        name: profile.name.clone(),
+        author,
+        version: Some(version_id.unwrap_or_else(|| "1.0.0".to_string())),
+        description,
+        pack_format: Some(PACK_FORMAT.to_string()),
+        index: PackwizIndexRef {
+            file: "index.toml".to_string(),
+            hash_format: HASH_FORMAT.to_string(),
+            hash: hash_bytes(index_data.as_bytes(), HASH_FORMAT)?,
+        },
+        versions,
+    };
+    write_export_file(
+        &export_path,
+        "pack.toml",
+        toml::to_string(&pack)?.as_bytes(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn write_export_file(
+    export_path: &Path,
+    relative_path: &str,
+    data: &[u8],
+) -> crate::Result<()> {
+    let path = export_path.join(relative_path);
+    if let Some(parent) = path.parent() {
+        io::create_dir_all(parent).await?;
+    }
+    io::write(&path, data).await?;
+
+    Ok(())
+}
+
+/// Hashes bytes with one of the hash formats packwiz declares. murmur2 is
+/// CurseForge's fingerprint, written as a decimal number
+fn hash_bytes(bytes: &[u8], format: &str) -> crate::Result<String> {
+    match format {
+        "sha1" => Ok(sha1_smol::Sha1::from(bytes).hexdigest()),
+        "sha256" => Ok(format!("{:x}", sha2::Sha256::digest(bytes))),
+        "sha512" => Ok(format!("{:x}", sha2::Sha512::digest(bytes))),
+        "md5" => Ok(format!("{:x}", md5::Md5::digest(bytes))),
+        "murmur2" => Ok(murmur2_fingerprint(bytes).to_string()),
+        _ => Err(crate::ErrorKind::InputError(format!(
+            "Unsupported packwiz hash format {format}"
+        ))
+        .as_error()),
+    }
+}
+
+fn verify_hash(
+    bytes: &[u8],
+    format: &str,
+    expected: &str,
+    path: &str,
+) -> crate::Result<()> {
+    if !hash_bytes(bytes, format)?.eq_ignore_ascii_case(expected) {
+        return Err(crate::ErrorKind::InputError(format!(
+            "{path} does not match its {format} hash"
+        ))
+        .as_error());
+    }
+
+    Ok(())
+}
+
+/// Checks that a path of the pack stays within its folder
+fn safe_relative_path(path: &str) -> crate::Result<String> {
+    if Path::new(path)
+        .components()
+        .any(|x| !matches!(x, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Invalid path in packwiz pack: {path}"
+        ))
+        .as_error());
+    }
+
+    Ok(path.to_string())
+}
+
+fn parent_path(path: &str) -> String {
+    path.rsplit_once('/')
+        .map(|(parent, _)| parent.to_string())
+        .unwrap_or_default()
+}
+
+fn join_path(parent: &str, path: &str) -> String {
+    if parent.is_empty() {
+        path.to_string()
+    } else {
+        format!("{parent}/{path}")
+    }
+}
@@ -0,0 +1,424 @@
+//! Dedicated server packs built from a profile: its server-side files, the
+//! vanilla server jar, the loader's server libraries and start scripts
+use crate::event::emit::{emit_loading, init_loading};
+use crate::event::{LoadingBarId, LoadingBarType};
+use crate::launcher::server::{server_java, ServerVersion};
+use crate::launcher::{parse_rules, run_processors};
+use crate::pack::install_from::{EnvType, PackFormat};
+use crate::profile::{self, add_all_recursive_folder_paths};
+use crate::state::{CachedEntry, Profile, Settings, SideType};
+use crate::util::fetch::{self, fetch};
+use crate::util::io::{self, IOError};
+use crate::State;
+use async_zip::base::read::seek::ZipFileReader;
+use async_zip::tokio::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use daedalus as d;
+use daedalus::modded::SidedDataEntry;
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use tokio::fs::File;
+
+/// Files and folders of a profile that only the client uses
+const CLIENT_ONLY_PATHS: &[&str] = &[
+    "resourcepacks",
+    "shaderpacks",
+    "screenshots",
+    "saves",
+    "logs",
+    "crash-reports",
+    "options.txt",
+    "optionsof.txt",
+    "optionsshaders.txt",
+    "servers.dat",
+    "servers.dat_old",
+    "realms_persistence.json",
+    "command_history.txt",
+    "icon.png",
+];
+
+/// Exports a profile as a ready-to-run dedicated server
+/// Included files are copied, except projects whose modpack or Modrinth
+/// project marks them as unsupported on servers and files only the client
+/// uses. The vanilla server jar and the loader's libraries are downloaded,
+/// Forge and NeoForge's processors are run for the server, and start scripts
+/// using the profile's memory settings are written
+/// The server is written into the `export_path` folder, or into a zip at
+/// `export_path`
+#[tracing::instrument]
+pub async fn export_server_pack(
+    profile_path: &str,
+    export_path: PathBuf,
+    included_export_candidates: Vec<String>, // which folders/files to include in the export
+    zip: bool,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let profile = profile::get(profile_path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(profile_path.to_string())
+    })?;
+    let profile_base_path = profile::get_full_path(profile_path).await?;
+
+    // Zips are built in a temporary folder first, as processors need one
+    let staging = if zip {
+        Some(tempfile::tempdir().map_err(IOError::from)?)
+    } else {
+        None
+    };
+    let server_dir = staging
+        .as_ref()
+        .map(|x| x.path().to_path_buf())
+        .unwrap_or_else(|| export_path.clone());
+    io::create_dir_all(&server_dir).await?;
+
+    let loading_bar = init_loading(
+        LoadingBarType::ZipExtract {
+            profile_path: profile.path.clone(),
+            profile_name: profile.name.clone(),
+        },
+        100.0,
+        "Building server pack",
+    )
+    .await?;
+
+    let included = |path: &str| {
+        !path.ends_with(".DS_Store")
+            && !path.ends_with(".disabled")
+            && included_export_candidates
+                .iter()
+                .any(|x| path == x || path.starts_with(&format!("{x}/")))
+            && !CLIENT_ONLY_PATHS
+                .iter()
+                .any(|x| path == *x || path.starts_with(&format!("{x}/")))
+    };
+    let client_only = client_only_projects(&profile).await?;
+
+    let mut path_list = Vec::new();
+    add_all_recursive_folder_paths(&profile_base_path, &mut path_list).await?;
+    let total_len = path_list.len();
+    for path in path_list {
+        emit_loading(&loading_bar, 20.0 / total_len as f64, None).await?;
+
+        let relative_path = path
+            .strip_prefix(&profile_base_path)?
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        if !included(&relative_path)
+            || client_only.contains(&relative_path)
+            || !path.is_file()
+        {
+            continue;
+        }
+
+        fetch::copy(
+            &path,
+            server_dir.join(&relative_path),
+            &state.io_semaphore,
+        )
+        .await?;
+    }
+
+    install_server(&profile, &server_dir, &loading_bar).await?;
+
+    if let Some(staging) = staging {
+        emit_loading(&loading_bar, 0.0, Some("Zipping server pack")).await?;
+
+        let mut file = File::create(&export_path)
+            .await
+            .map_err(|e| IOError::with_path(e, &export_path))?;
+        let mut writer = ZipFileWriter::with_tokio(&mut file);
+
+        let mut path_list = Vec::new();
+        add_all_recursive_folder_paths(staging.path(), &mut path_list).await?;
+        let total_len = path_list.len();
+        for path in path_list {
+            emit_loading(&loading_bar, 20.0 / total_len as f64, None).await?;
+            if !path.is_file() {
+                continue;
+            }
+
+            let relative_path = path
+                .strip_prefix(staging.path())?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            let mut builder = ZipEntryBuilder::new(
+                relative_path.clone().into(),
+                Compression::Deflate,
+            );
+            if relative_path.ends_with(".sh") {
+                builder = builder.unix_permissions(0o755);
+            }
+
+            let data = io::read(&path).await?;
+            writer.write_entry_whole(builder, &data).await?;
+        }
+
+        writer.close().await?;
+    }
+
+    Ok(())
+}
+
+/// Paths of the projects not supported on servers. Sides declared by the
+/// linked modpack's index take precedence over the Modrinth projects' own
+async fn client_only_projects(
+    profile: &Profile,
+) -> crate::Result<HashSet<String>> {
+    let state = State::get().await?;
+    let pack_sides = linked_pack_server_sides(profile).await?;
+
+    let projects = profile
+        .get_projects(None, &state.pool, &state.api_semaphore)
+        .await?
+        .into_iter()
+        .filter(|(path, _)| !pack_sides.contains_key(path))
+        .filter_map(|(path, file)| Some((path, file.metadata?.project_id)))
+        .collect::<Vec<_>>();
+    let project_infos = CachedEntry::get_project_many(
+        &projects.iter().map(|(_, x)| &**x).collect::<Vec<_>>(),
+        None,
+        &state.pool,
+        &state.api_semaphore,
+    )
+    .await?;
+
+    let mut client_only = pack_sides
+        .into_iter()
+        .filter(|(_, side)| *side == SideType::Unsupported)
+        .map(|(path, _)| path)
+        .collect::<HashSet<_>>();
+    for (path, project_id) in projects {
+        if project_infos.iter().any(|x| {
+            x.id == project_id && x.server_side == SideType::Unsupported
+        }) {
+            client_only.insert(path);
+        }
+    }
+
+    Ok(client_only)
+}
+
+/// Server sides of the files of the modpack a profile is linked to, from its
+/// `modrinth.index.json`
+async fn linked_pack_server_sides(
+    profile: &Profile,
+) -> crate::Result<HashMap<String, SideType>> {
+    let state = State::get().await?;
+    let Some(linked_data) = &profile.linked_data else {
+        return Ok(HashMap::new());
+    };
+    let Some(version) = CachedEntry::get_version(
+        &linked_data.version_id,
+        None,
+        &state.pool,
+        &state.api_semaphore,
+    )
+    .await?
+    else {
+        return Ok(HashMap::new());
+    };
+    let Some(file) = version
+        .files
+        .iter()
+        .find(|x| x.primary)
+        .or_else(|| version.files.first())
+    else {
+        return Ok(HashMap::new());
+    };
+
+    let bytes = fetch(
+        &file.url,
+        file.hashes.get("sha1").map(|x| &**x),
+        &state.fetch_semaphore,
+        &state.pool,
+    )
+    .await?;
+    let reader: Cursor<&bytes::Bytes> = Cursor::new(&bytes);
+    let mut zip_reader =
+        ZipFileReader::with_tokio(reader).await.map_err(|_| {
+            crate::Error::from(crate::ErrorKind::InputError(
+                "Failed to read linked modpack zip".to_string(),
+            ))
+        })?;
+    let Some(zip_index) = zip_reader.file().entries().iter().position(|f| {
+        f.filename().as_str().unwrap_or_default() == "modrinth.index.json"
+    }) else {
+        return Ok(HashMap::new());
+    };
+
+    let mut manifest = String::new();
+    let mut reader = zip_reader.reader_with_entry(zip_index).await?;
+    reader.read_to_string_checked(&mut manifest).await?;
+    let pack: PackFormat = serde_json::from_str(&manifest)?;
+
+    Ok(pack
+        .files
+        .into_iter()
+        .filter_map(|file| {
+            let mut env = file.env?;
+            Some((file.path, env.remove(&EnvType::Server)?))
+        })
+        .collect())
+}
+
+/// Downloads the server jar and the loader's libraries into a server folder,
+/// runs the loader's processors for the server and writes the start scripts
+async fn install_server(
+    profile: &Profile,
+    server_dir: &Path,
+    loading_bar: &LoadingBarId,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let settings = Settings::get(&state.pool).await?;
+
+    emit_loading(loading_bar, 0.0, Some("Downloading server")).await?;
+    let server = ServerVersion::get(profile).await?;
+    let java_version = server_java(profile, &server.version_info).await?;
+
+    // Libraries go through the shared libraries folder, so they are only
+    // downloaded once
+    server
+        .download(server_dir, &java_version.architecture, loading_bar, 30.0)
+        .await?;
+    let libraries_dir = server_dir.join("libraries");
+    for library in &server.loader_libraries {
+        if !library.downloadable {
+            continue;
+        }
+        if let Some(rules) = &library.rules {
+            if !parse_rules(
+                rules,
+                &java_version.architecture,
+                server.minecraft_updated,
+                None,
+            ) {
+                continue;
+            }
+        }
+
+        let artifact_path = d::get_path_from_artifact(&library.name)?;
+        let path = state.directories.libraries_dir().join(&artifact_path);
+        if !path.exists() {
+            return Err(crate::ErrorKind::LauncherError(format!(
+                "Library {} was not downloaded",
+                library.name
+            ))
+            .as_error());
+        }
+        fetch::copy(
+            &path,
+            libraries_dir.join(&artifact_path),
+            &state.io_semaphore,
+        )
+        .await?;
+    }
+
+    let bundled_libraries = server
+        .extract_bundled_libraries(server_dir, &libraries_dir)
+        .await?;
+
+    if let Some(processors) = &server.version_info.processors {
+        let mut data = server.version_info.data.clone().unwrap_or_default();
+        for (key, value) in [
+            ("SIDE", "server".to_string()),
+            (
+                "MINECRAFT_JAR",
+                server_dir
+                    .join(&server.server_jar)
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            ("MINECRAFT_VERSION", profile.game_version.clone()),
+            ("ROOT", server_dir.to_string_lossy().to_string()),
+            ("LIBRARY_DIR", libraries_dir.to_string_lossy().to_string()),
+        ] {
+            data.insert(
+                key.to_string(),
+                SidedDataEntry {
+                    client: String::new(),
+                    server: value,
+                },
+            );
+        }
+
+        emit_loading(loading_bar, 0.0, Some("Running forge processors"))
+            .await?;
+        run_processors(
+            processors,
+            &data,
+            "server",
+            &java_version,
+            &libraries_dir,
+            loading_bar,
+            30.0,
+        )
+        .await?;
+    } else {
+        emit_loading(loading_bar, 30.0, None).await?;
+    }
+
+    let arguments = server.arguments(
+        profile.loader,
+        "libraries",
+        &bundled_libraries,
+        &java_version.architecture,
+    )?;
+    let memory = profile.memory.unwrap_or(settings.memory).maximum;
+    let command = |separator: &str| {
+        let arguments = arguments
+            .iter()
+            .map(|x| {
+                let x = x.replace("${classpath_separator}", separator);
+                if x.contains(' ') {
+                    format!("\"{x}\"")
+                } else {
+                    x
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!("java -Xmx{memory}M {arguments}")
+    };
+
+    let start_sh = server_dir.join("start.sh");
+    io::write(
+        &start_sh,
+        format!(
+            "#!/usr/bin/env sh\n\
+             # Accept the Minecraft EULA in eula.txt before the first start\n\
+             cd \"$(dirname \"$0\")\"\n\
+             {} \"$@\"\n",
+            command(":")
+        ),
+    )
+    .await?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(
+            &start_sh,
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .await
+        .map_err(|e| IOError::with_path(e, &start_sh))?;
+    }
+    io::write(
+        server_dir.join("start.bat"),
+        format!(
+            "@echo off\r\n\
+             rem Accept the Minecraft EULA in eula.txt before the first start\r\n\
+             cd /d \"%~dp0\"\r\n\
+             {} %*\r\n\
+             pause\r\n",
+            command(";")
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -54,3 +54,19 @@ pub async fn wait_for(pid: i32) -> crate::Result<()> {
         Ok(())
     }
 }
+
+// Send a command to the console of a child process stored in the state, such as a server's
+#[tracing::instrument]
+pub async fn send_command(pid: i32, command: &str) -> crate::Result<()> {
+    let state = State::get().await?;
+    let process = Process::get(pid, &state.pool).await?;
+
+    if let Some(process) = process {
+        process.send_command(command).await
+    } else {
+        Err(crate::ErrorKind::LauncherError(format!(
+            "No running process with pid {pid}"
+        ))
+        .into())
+    }
+}
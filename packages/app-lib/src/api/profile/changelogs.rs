@@ -0,0 +1,138 @@
+//! Changelogs of the updates pending for a profile's projects
+use crate::profile::get;
+use crate::state::{
+    CachedEntry, ProjectType, ReleaseChannel, UpdateMode, UpdatePolicy,
+};
+use crate::State;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The versions between the installed and the update version of a project
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProjectChangelog {
+    pub project_path: String,
+    pub project_id: String,
+    pub installed_version_id: String,
+    pub update_version_id: String,
+    /// Versions after the installed one, up to and including the update
+    /// version, newest first
+    pub versions: Vec<ChangelogEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChangelogEntry {
+    pub version_id: String,
+    pub name: String,
+    pub version_number: String,
+    pub version_type: String,
+    pub date_published: DateTime<Utc>,
+    pub changelog: String,
+}
+
+/// Gets the changelogs of every project of a profile that has an update
+/// available, so they can be reviewed before updating
+/// Only versions the project could have been updated to are included, following
+/// its update policy
+#[tracing::instrument]
+pub async fn get_pending(
+    profile_path: &str,
+) -> crate::Result<Vec<ProjectChangelog>> {
+    let state = State::get().await?;
+    let profile = get(profile_path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(profile_path.to_string())
+    })?;
+
+    let mut updates = profile
+        .get_projects(None, &state.pool, &state.api_semaphore)
+        .await?
+        .into_iter()
+        .filter_map(|(path, file)| {
+            let metadata = file.metadata?;
+            Some((path, metadata, file.update_version_id?))
+        })
+        .collect::<Vec<_>>();
+    updates.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let project_versions = CachedEntry::get_project_versions_many(
+        &updates
+            .iter()
+            .map(|(_, metadata, _)| &*metadata.project_id)
+            .collect::<Vec<_>>(),
+        None,
+        &state.pool,
+        &state.api_semaphore,
+    )
+    .await?;
+    let policies =
+        UpdatePolicy::get_from_profile(profile_path, &state.pool).await?;
+
+    let mut changelogs = Vec::new();
+    for (project_path, metadata, update_version_id) in updates {
+        let Some(versions) = project_versions
+            .iter()
+            .find(|x| x.project_id == metadata.project_id)
+        else {
+            continue;
+        };
+        let (Some(installed), Some(update)) = (
+            versions
+                .versions
+                .iter()
+                .find(|x| x.id == metadata.version_id),
+            versions.versions.iter().find(|x| x.id == update_version_id),
+        ) else {
+            continue;
+        };
+
+        let policy = policies
+            .iter()
+            .find(|x| x.project_id == metadata.project_id)
+            .cloned()
+            .unwrap_or_else(|| {
+                UpdatePolicy::default_for(profile_path, &metadata.project_id)
+            });
+        let is_mod = matches!(
+            ProjectType::get_from_loaders(update.loaders.clone()),
+            Some(ProjectType::Mod)
+        );
+
+        let mut entries = versions
+            .versions
+            .iter()
+            .filter(|x| {
+                x.date_published > installed.date_published
+                    && x.date_published <= update.date_published
+            })
+            .filter(|x| {
+                ReleaseChannel::from_string(&x.version_type)
+                    .is_some_and(|x| policy.channels.contains(&x))
+            })
+            .filter(|x| {
+                policy.mode == UpdateMode::Follow
+                    || x.game_versions.contains(&profile.game_version)
+            })
+            .filter(|x| {
+                !is_mod || x.loaders.iter().any(|x| profile.loader.can_load(x))
+            })
+            .map(|x| ChangelogEntry {
+                version_id: x.id.clone(),
+                name: x.name.clone(),
+                version_number: x.version_number.clone(),
+                version_type: x.version_type.clone(),
+                date_published: x.date_published,
+                changelog: x.changelog.clone(),
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|x| std::cmp::Reverse(x.date_published));
+
+        changelogs.push(ProjectChangelog {
+            project_path,
+            project_id: metadata.project_id,
+            installed_version_id: metadata.version_id,
+            update_version_id,
+            versions: entries,
+        });
+    }
+
+    Ok(changelogs)
+}
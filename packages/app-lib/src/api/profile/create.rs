@@ -1,7 +1,10 @@
 //! Theseus profile management interface
 use crate::launcher::get_loader_version_from_profile;
 use crate::settings::Hooks;
-use crate::state::{LinkedData, ProfileInstallStage};
+use crate::state::{
+    ContentRef, LinkedData, ModSet, ProfileInstallStage, ProfileType,
+    UpdatePolicy,
+};
 use crate::util::io::{self, canonicalize};
 use crate::{
     event::{emit::emit_profile, ProfilePayloadType},
@@ -25,6 +28,7 @@ pub async fn profile_create(
     icon_path: Option<String>,      // the icon for the profile
     linked_data: Option<LinkedData>, // the linked project ID (mainly for modpacks)- used for updating
     skip_install_profile: Option<bool>,
+    profile_type: Option<ProfileType>, // whether the profile is a dedicated server. defaults to a client
 ) -> crate::Result<String> {
     trace!("Creating new profile. {}", name);
     let state = State::get().await?;
@@ -74,6 +78,7 @@ pub async fn profile_create(
     let mut profile = Profile {
         path: path.clone(),
         install_stage: ProfileInstallStage::NotInstalled,
+        profile_type: profile_type.unwrap_or_default(),
         name,
         icon_path: None,
         game_version,
@@ -97,6 +102,8 @@ pub async fn profile_create(
             wrapper: None,
             post_exit: None,
         },
+        default_options: None,
+        sync_options: false,
     };
 
     let result = async {
@@ -135,7 +142,7 @@ pub async fn profile_create(
     match result {
         Ok(profile) => Ok(profile),
         Err(err) => {
-            let _ = profile::remove(&path).await;
+            let _ = profile::delete(&path).await;
 
             Err(err)
         }
@@ -158,11 +165,74 @@ pub async fn profile_create_from_duplicate(
         profile.icon_path.clone(),
         profile.linked_data.clone(),
         Some(true),
+        Some(profile.profile_type),
     )
     .await?;
 
-    // Copy it over using the import system (essentially importing from the same profile)
     let state = State::get().await?;
+
+    // Link files shared through the content store first, so they aren't copied
+    let profile_dir = profile::get_full_path(&profile_path_id).await?;
+    let source_dir = profile::get_full_path(copy_from).await?;
+    for content in ContentRef::get_from_profile(copy_from, &state.pool).await? {
+        // Keep disabled projects disabled
+        let project_path = if source_dir.join(&content.project_path).is_file() {
+            content.project_path
+        } else if source_dir
+            .join(format!("{}.disabled", content.project_path))
+            .is_file()
+        {
+            format!("{}.disabled", content.project_path)
+        } else {
+            continue;
+        };
+
+        // Files changed outside of the launcher are copied as-is instead
+        let Some(object) = ContentRef::read_object(
+            &state.directories,
+            &content.hash,
+            &state.io_semaphore,
+        )
+        .await?
+        else {
+            continue;
+        };
+        if io::read(source_dir.join(&project_path)).await? != object {
+            continue;
+        }
+
+        ContentRef::link(
+            &profile_path_id,
+            &project_path,
+            &content.hash,
+            &profile_dir,
+            &state.directories,
+            &state.io_semaphore,
+            &state.pool,
+        )
+        .await?;
+    }
+
+    for policy in UpdatePolicy::get_from_profile(copy_from, &state.pool).await?
+    {
+        UpdatePolicy {
+            profile_path: profile_path_id.clone(),
+            ..policy
+        }
+        .upsert(&state.pool)
+        .await?;
+    }
+    for set in ModSet::get_from_profile(copy_from, &state.pool).await? {
+        ModSet {
+            id: uuid::Uuid::new_v4().to_string(),
+            profile_path: profile_path_id.clone(),
+            ..set
+        }
+        .upsert(&state.pool)
+        .await?;
+    }
+
+    // Copy the rest over using the import system (essentially importing from the same profile)
     let bar = pack::import::copy_dotminecraft(
         &profile_path_id,
         profile::get_full_path(copy_from).await?,
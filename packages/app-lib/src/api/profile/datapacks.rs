@@ -0,0 +1,159 @@
+//! Theseus per-world datapack (saves/<world>/datapacks/) management
+use crate::event::emit::emit_profile;
+use crate::event::ProfilePayloadType;
+use crate::profile::get_full_path;
+use crate::profile::worlds::get_world_path;
+use crate::state::fs_watcher::watch_world_datapacks;
+use crate::state::{Profile, ProfileFile, ProjectType};
+use crate::util::io;
+use crate::State;
+use dashmap::DashMap;
+use std::path::{Component, Path};
+
+/// Lists the datapacks in a world's datapacks/ folder, by their path
+/// relative to the profile
+#[tracing::instrument]
+pub async fn list(
+    profile_path: &str,
+    world: &str,
+) -> crate::Result<DashMap<String, ProfileFile>> {
+    get_world_path(profile_path, world).await?;
+
+    let projects = crate::profile::get_projects(profile_path).await?;
+    projects.retain(|_, x| x.world.as_deref() == Some(world));
+
+    Ok(projects)
+}
+
+/// Adds a datapack file to a world
+/// Returns the path to the datapack, relative to the profile
+#[tracing::instrument]
+pub async fn add_from_path(
+    profile_path: &str,
+    world: &str,
+    path: &Path,
+) -> crate::Result<String> {
+    let state = State::get().await?;
+    get_world_path(profile_path, world).await?;
+
+    let file = io::read(path).await?;
+    let file_name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    let project_path = Profile::add_project_bytes(
+        profile_path,
+        &file_name,
+        bytes::Bytes::from(file),
+        None,
+        Some(ProjectType::DataPack),
+        Some(world),
+        &state.io_semaphore,
+        &state.pool,
+    )
+    .await?;
+
+    watch_worlds(profile_path).await?;
+    emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(project_path)
+}
+
+/// Removes a datapack from a world, moving it to the trash
+#[tracing::instrument]
+pub async fn remove(
+    profile_path: &str,
+    world: &str,
+    file_name: &str,
+) -> crate::Result<()> {
+    let project_path =
+        get_datapack_path(profile_path, world, file_name).await?;
+
+    crate::profile::remove_project(profile_path, &project_path).await
+}
+
+/// Copies a datapack from a world into another, of the same profile or
+/// another one. Datapacks already in the destination world are replaced
+/// Returns the path to the copy, relative to the destination profile
+#[tracing::instrument]
+pub async fn copy(
+    from_profile_path: &str,
+    from_world: &str,
+    file_name: &str,
+    to_profile_path: &str,
+    to_world: &str,
+) -> crate::Result<String> {
+    let state = State::get().await?;
+    let project_path =
+        get_datapack_path(from_profile_path, from_world, file_name).await?;
+    get_world_path(to_profile_path, to_world).await?;
+
+    let file =
+        io::read(get_full_path(from_profile_path).await?.join(&project_path))
+            .await?;
+
+    let project_path = Profile::add_project_bytes(
+        to_profile_path,
+        file_name,
+        bytes::Bytes::from(file),
+        None,
+        Some(ProjectType::DataPack),
+        Some(to_world),
+        &state.io_semaphore,
+        &state.pool,
+    )
+    .await?;
+
+    watch_worlds(to_profile_path).await?;
+    emit_profile(to_profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(project_path)
+}
+
+/// Watches the datapacks/ folders of a profile's worlds, which may have just
+/// been created
+pub(crate) async fn watch_worlds(profile_path: &str) -> crate::Result<()> {
+    let state = State::get().await?;
+
+    watch_world_datapacks(
+        &get_full_path(profile_path).await?,
+        &state.file_watcher,
+    )
+    .await
+}
+
+// Path of an existing datapack file of a world, relative to the profile
+async fn get_datapack_path(
+    profile_path: &str,
+    world: &str,
+    file_name: &str,
+) -> crate::Result<String> {
+    let mut components = Path::new(file_name).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Invalid datapack name: {file_name}"
+        ))
+        .as_error());
+    }
+
+    let path = get_world_path(profile_path, world)
+        .await?
+        .join(ProjectType::DataPack.get_folder())
+        .join(file_name);
+    if !path.is_file() {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Datapack {file_name} does not exist in world {world}"
+        ))
+        .as_error());
+    }
+
+    Ok(format!(
+        "{}/{file_name}",
+        ProjectType::DataPack.get_world_folder(Some(world))
+    ))
+}
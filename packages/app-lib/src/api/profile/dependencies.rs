@@ -0,0 +1,398 @@
+//! Resolution of required dependencies when adding projects to a profile
+use crate::event::emit::emit_profile;
+use crate::event::ProfilePayloadType;
+use crate::profile::datapacks::watch_worlds;
+use crate::profile::get;
+use crate::profile::worlds::get_world_path;
+use crate::state::{
+    CachedEntry, DependencyType, ModLoader, Profile, ProjectType, Version,
+};
+use crate::State;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// The versions that adding a version to a profile would install
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DependencyPlan {
+    /// Versions to install, starting with the requested one
+    pub install: Vec<PlannedVersion>,
+    /// Ids of required projects that are already in the profile
+    pub already_installed: Vec<String>,
+    /// Dependencies that could not be satisfied. Nothing is installed unless
+    /// this is empty
+    pub unresolved: Vec<UnresolvedDependency>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlannedVersion {
+    pub project_id: String,
+    pub version_id: String,
+    pub name: String,
+    pub version_number: String,
+    /// Id of the version that required this one, if it is a dependency
+    pub required_by: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UnresolvedDependency {
+    pub project_id: Option<String>,
+    pub version_id: Option<String>,
+    /// Id of the version that declared this dependency
+    pub required_by: String,
+    pub reason: UnresolvedReason,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnresolvedReason {
+    /// The project or version does not exist
+    NotFound,
+    /// No version matches the profile's loader and game version
+    NoCompatibleVersion,
+    /// The project is marked as incompatible, but is installed or required
+    Incompatible,
+}
+
+impl UnresolvedReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotFound => "not found",
+            Self::NoCompatibleVersion => "no compatible version",
+            Self::Incompatible => "incompatible",
+        }
+    }
+}
+
+/// Works out which versions adding a version to a profile would install,
+/// following required dependencies, without installing anything
+#[tracing::instrument]
+pub async fn resolve(
+    profile_path: &str,
+    version_id: &str,
+) -> crate::Result<DependencyPlan> {
+    let state = State::get().await?;
+    let profile = get(profile_path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(profile_path.to_string())
+    })?;
+
+    let version = CachedEntry::get_version(
+        version_id,
+        None,
+        &state.pool,
+        &state.api_semaphore,
+    )
+    .await?
+    .ok_or_else(|| {
+        crate::ErrorKind::InputError(format!(
+            "Unable to install version id {version_id}. Not found."
+        ))
+    })?;
+
+    let installed = profile
+        .get_projects(None, &state.pool, &state.api_semaphore)
+        .await?
+        .into_iter()
+        .filter_map(|(_, file)| file.metadata.map(|x| x.project_id))
+        .collect::<HashSet<_>>();
+
+    let mut plan = DependencyPlan::default();
+    let mut seen = installed.clone();
+    seen.insert(version.project_id.clone());
+    plan.install.push(PlannedVersion::new(&version, None));
+
+    let mut planned = vec![version.clone()];
+    let mut queue = vec![version];
+
+    while !queue.is_empty() {
+        let current = std::mem::take(&mut queue);
+
+        let mut pinned = Vec::new();
+        let mut unpinned = Vec::new();
+        for version in &current {
+            for dependency in &version.dependencies {
+                if !matches!(
+                    dependency.dependency_type,
+                    DependencyType::Required
+                ) {
+                    continue;
+                }
+
+                match (&dependency.version_id, &dependency.project_id) {
+                    (Some(version_id), _) => {
+                        pinned.push((version_id.clone(), version.id.clone()))
+                    }
+                    (None, Some(project_id)) => {
+                        unpinned.push((project_id.clone(), version.id.clone()))
+                    }
+                    // Dependencies on files outside of Modrinth can't be resolved
+                    (None, None) => {}
+                }
+            }
+        }
+
+        // Dependencies on a specific version are installed as-is when they are
+        // compatible, and resolved like any other project otherwise
+        let pinned_versions = CachedEntry::get_version_many(
+            &pinned.iter().map(|x| &*x.0).collect::<Vec<_>>(),
+            None,
+            &state.pool,
+            &state.api_semaphore,
+        )
+        .await?;
+        for (version_id, required_by) in pinned {
+            match pinned_versions.iter().find(|x| x.id == version_id) {
+                Some(version) if seen.contains(&version.project_id) => {
+                    if installed.contains(&version.project_id) {
+                        plan.already_installed.push(version.project_id.clone());
+                    }
+                }
+                Some(version)
+                    if is_compatible(
+                        version,
+                        &profile.game_version,
+                        profile.loader,
+                    ) =>
+                {
+                    seen.insert(version.project_id.clone());
+                    plan.install
+                        .push(PlannedVersion::new(version, Some(required_by)));
+                    planned.push(version.clone());
+                    queue.push(version.clone());
+                }
+                Some(version) => {
+                    unpinned.push((version.project_id.clone(), required_by))
+                }
+                None => plan.unresolved.push(UnresolvedDependency {
+                    project_id: None,
+                    version_id: Some(version_id),
+                    required_by,
+                    reason: UnresolvedReason::NotFound,
+                }),
+            }
+        }
+
+        let mut dependencies = Vec::new();
+        for (project_id, required_by) in unpinned {
+            if seen.contains(&project_id) {
+                if installed.contains(&project_id) {
+                    plan.already_installed.push(project_id);
+                }
+            } else if !dependencies.iter().any(|(x, _)| x == &project_id) {
+                dependencies.push((project_id, required_by));
+            }
+        }
+
+        let projects = CachedEntry::get_project_many(
+            &dependencies.iter().map(|x| &*x.0).collect::<Vec<_>>(),
+            None,
+            &state.pool,
+            &state.api_semaphore,
+        )
+        .await?;
+        for (project_id, required_by) in dependencies {
+            // Projects may be referred to by slug
+            let Some(project) = projects.iter().find(|x| {
+                x.id == project_id || x.slug.as_ref() == Some(&project_id)
+            }) else {
+                plan.unresolved.push(UnresolvedDependency {
+                    project_id: Some(project_id),
+                    version_id: None,
+                    required_by,
+                    reason: UnresolvedReason::NotFound,
+                });
+                continue;
+            };
+
+            if seen.contains(&project.id) {
+                if installed.contains(&project.id) {
+                    plan.already_installed.push(project.id.clone());
+                }
+                continue;
+            }
+
+            let versions = CachedEntry::get_version_many(
+                &project.versions.iter().map(|x| &**x).collect::<Vec<_>>(),
+                None,
+                &state.pool,
+                &state.api_semaphore,
+            )
+            .await?;
+
+            // Prefer releases, then the newest version
+            let version = versions
+                .into_iter()
+                .filter(|x| {
+                    is_compatible(x, &profile.game_version, profile.loader)
+                })
+                .max_by_key(|x| {
+                    (x.version_type == "release", x.date_published)
+                });
+
+            if let Some(version) = version {
+                seen.insert(version.project_id.clone());
+                plan.install
+                    .push(PlannedVersion::new(&version, Some(required_by)));
+                planned.push(version.clone());
+                queue.push(version);
+            } else {
+                plan.unresolved.push(UnresolvedDependency {
+                    project_id: Some(project.id.clone()),
+                    version_id: None,
+                    required_by,
+                    reason: UnresolvedReason::NoCompatibleVersion,
+                });
+            }
+        }
+    }
+
+    // Nothing that is planned may conflict with the profile or with each other
+    let planned_projects = planned
+        .iter()
+        .map(|x| x.project_id.clone())
+        .collect::<HashSet<_>>();
+    for version in &planned {
+        for dependency in &version.dependencies {
+            if !matches!(
+                dependency.dependency_type,
+                DependencyType::Incompatible
+            ) {
+                continue;
+            }
+
+            if let Some(project_id) = &dependency.project_id {
+                if installed.contains(project_id)
+                    || planned_projects.contains(project_id)
+                {
+                    plan.unresolved.push(UnresolvedDependency {
+                        project_id: Some(project_id.clone()),
+                        version_id: dependency.version_id.clone(),
+                        required_by: version.id.clone(),
+                        reason: UnresolvedReason::Incompatible,
+                    });
+                }
+            }
+        }
+    }
+
+    plan.already_installed.sort();
+    plan.already_installed.dedup();
+
+    Ok(plan)
+}
+
+/// Adds a version to a profile along with every required dependency that is
+/// not installed yet
+/// If `dry_run` is set, returns the plan without installing anything. Fails
+/// without installing anything if a dependency cannot be satisfied, or if
+/// one of the versions fails to install
+/// Datapacks, including datapack dependencies, are installed into `world`'s
+/// datapacks/ folder when it is given
+#[tracing::instrument]
+pub async fn add_project_with_dependencies(
+    profile_path: &str,
+    version_id: &str,
+    world: Option<&str>,
+    dry_run: bool,
+) -> crate::Result<DependencyPlan> {
+    let plan = resolve(profile_path, version_id).await?;
+    if dry_run {
+        return Ok(plan);
+    }
+
+    if !plan.unresolved.is_empty() {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Unable to resolve the dependencies of version {version_id}: {}",
+            plan.unresolved
+                .iter()
+                .map(|x| format!(
+                    "{} required by {} ({})",
+                    x.project_id
+                        .as_deref()
+                        .or(x.version_id.as_deref())
+                        .unwrap_or_default(),
+                    x.required_by,
+                    x.reason.as_str()
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+        .as_error());
+    }
+
+    let state = State::get().await?;
+    if let Some(world) = world {
+        get_world_path(profile_path, world).await?;
+    }
+
+    let mut installed = Vec::new();
+    for version in &plan.install {
+        match Profile::add_project_version(
+            profile_path,
+            &version.version_id,
+            world,
+            &state.pool,
+            &state.fetch_semaphore,
+            &state.io_semaphore,
+        )
+        .await
+        {
+            Ok(path) => installed.push(path),
+            Err(err) => {
+                // Don't leave a partial install behind
+                for path in installed {
+                    Profile::remove_project(
+                        profile_path,
+                        &path,
+                        &state.directories,
+                        &state.pool,
+                    )
+                    .await?;
+                }
+                emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+                return Err(err);
+            }
+        }
+    }
+
+    if world.is_some() {
+        watch_worlds(profile_path).await?;
+    }
+    emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(plan)
+}
+
+impl PlannedVersion {
+    fn new(version: &Version, required_by: Option<String>) -> Self {
+        Self {
+            project_id: version.project_id.clone(),
+            version_id: version.id.clone(),
+            name: version.name.clone(),
+            version_number: version.version_number.clone(),
+            required_by,
+        }
+    }
+}
+
+// Whether a version can be installed into a profile of the given game version
+// and loader
+pub(crate) fn is_compatible(
+    version: &Version,
+    game_version: &str,
+    loader: ModLoader,
+) -> bool {
+    if !version.game_versions.iter().any(|x| x == game_version) {
+        return false;
+    }
+
+    // Only mods depend on the profile's loader
+    if !matches!(
+        ProjectType::get_from_loaders(version.loaders.clone()),
+        Some(ProjectType::Mod)
+    ) {
+        return true;
+    }
+
+    version.loaders.iter().any(|x| loader.can_load(x))
+}
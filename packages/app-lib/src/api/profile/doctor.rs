@@ -0,0 +1,275 @@
+//! Theseus profile doctor: detects problems with a profile's installed projects
+//! before they crash the game
+use crate::profile::get;
+use crate::state::{
+    CachedEntry, DependencyType, ModLoader, ProfileFile, ProjectType,
+};
+use crate::State;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Modrinth project ids of shader loaders (Iris, Oculus)
+const SHADER_LOADER_PROJECTS: [&str; 2] = ["YL57xq9U", "GchcoXML"];
+/// File name fragments of shader loaders, for those not installed from Modrinth
+const SHADER_LOADER_FILE_NAMES: [&str; 3] = ["iris", "oculus", "optifine"];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    #[serde(flatten)]
+    pub kind: DiagnosticKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The game is likely to crash or misbehave
+    Error,
+    /// Something likely does not work as intended
+    Warning,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    /// A project declares another installed project as incompatible
+    Incompatible {
+        project_path: String,
+        project_id: String,
+        incompatible_path: String,
+        incompatible_project_id: String,
+    },
+    /// The same project is installed more than once
+    Duplicate {
+        project_id: String,
+        project_paths: Vec<String>,
+        version_ids: Vec<String>,
+    },
+    /// A project requires a project that is not installed or is disabled
+    MissingDependency {
+        project_path: String,
+        dependency_project_id: Option<String>,
+        dependency_version_id: Option<String>,
+    },
+    /// A mod was built for loaders other than the profile's
+    WrongLoader {
+        project_path: String,
+        loaders: Vec<String>,
+        profile_loader: ModLoader,
+    },
+    /// Shader packs are installed, but no shader loader is
+    MissingShaderLoader { project_paths: Vec<String> },
+}
+
+/// Checks a profile's enabled projects for problems
+/// Only projects known to Modrinth can be checked for incompatibilities,
+/// dependencies and loaders
+#[tracing::instrument]
+pub async fn diagnose(profile_path: &str) -> crate::Result<Vec<Diagnostic>> {
+    let state = State::get().await?;
+    let profile = get(profile_path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(profile_path.to_string())
+    })?;
+
+    // Disabled projects aren't loaded by the game
+    let mut projects = profile
+        .get_projects(None, &state.pool, &state.api_semaphore)
+        .await?
+        .into_iter()
+        .filter(|(path, _)| !path.ends_with(".disabled"))
+        .collect::<Vec<(String, ProfileFile)>>();
+    projects.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let versions = CachedEntry::get_version_many(
+        &projects
+            .iter()
+            .filter_map(|(_, file)| file.metadata.as_ref())
+            .map(|x| &*x.version_id)
+            .collect::<Vec<_>>(),
+        None,
+        &state.pool,
+        &state.api_semaphore,
+    )
+    .await?;
+
+    // Installed project id -> path of the first file of that project
+    let mut installed = HashMap::new();
+    let mut installed_versions = HashSet::new();
+    let mut by_project: HashMap<&str, Vec<(&str, &str)>> = HashMap::new();
+    for (path, file) in &projects {
+        if let Some(metadata) = &file.metadata {
+            installed
+                .entry(metadata.project_id.as_str())
+                .or_insert(path.as_str());
+            installed_versions.insert(metadata.version_id.as_str());
+            by_project
+                .entry(&metadata.project_id)
+                .or_default()
+                .push((path, &metadata.version_id));
+        }
+    }
+
+    // Dependencies pinned to a version need that version's project
+    let dependency_versions = CachedEntry::get_version_many(
+        &versions
+            .iter()
+            .flat_map(|x| &x.dependencies)
+            .filter(|x| x.project_id.is_none())
+            .filter_map(|x| x.version_id.as_deref())
+            .collect::<Vec<_>>(),
+        None,
+        &state.pool,
+        &state.api_semaphore,
+    )
+    .await?;
+    let dependency_project =
+        |project_id: &Option<String>, version_id: &Option<String>| {
+            project_id.clone().or_else(|| {
+                let version_id = version_id.as_ref()?;
+                dependency_versions
+                    .iter()
+                    .find(|x| &x.id == version_id)
+                    .map(|x| x.project_id.clone())
+            })
+        };
+
+    let mut diagnostics = Vec::new();
+    let mut reported_incompatible = HashSet::new();
+
+    for (path, file) in &projects {
+        let Some(version) = file.metadata.as_ref().and_then(|metadata| {
+            versions.iter().find(|x| x.id == metadata.version_id)
+        }) else {
+            continue;
+        };
+
+        for dependency in &version.dependencies {
+            let project_id = dependency_project(
+                &dependency.project_id,
+                &dependency.version_id,
+            );
+
+            match dependency.dependency_type {
+                DependencyType::Incompatible => {
+                    let Some(project_id) = project_id else {
+                        continue;
+                    };
+                    let Some(incompatible_path) =
+                        installed.get(project_id.as_str())
+                    else {
+                        continue;
+                    };
+
+                    // A pair is only reported once, whichever side declares it
+                    let mut pair =
+                        [version.project_id.clone(), project_id.clone()];
+                    pair.sort();
+                    if project_id == version.project_id
+                        || !reported_incompatible.insert(pair)
+                    {
+                        continue;
+                    }
+
+                    diagnostics.push(Diagnostic {
+                        severity: Severity::Error,
+                        kind: DiagnosticKind::Incompatible {
+                            project_path: path.clone(),
+                            project_id: version.project_id.clone(),
+                            incompatible_path: incompatible_path.to_string(),
+                            incompatible_project_id: project_id,
+                        },
+                    });
+                }
+                DependencyType::Required => {
+                    let satisfied = match &project_id {
+                        Some(project_id) => {
+                            installed.contains_key(project_id.as_str())
+                        }
+                        None => match &dependency.version_id {
+                            Some(version_id) => {
+                                installed_versions.contains(version_id.as_str())
+                            }
+                            None => true,
+                        },
+                    };
+
+                    if !satisfied {
+                        diagnostics.push(Diagnostic {
+                            severity: Severity::Error,
+                            kind: DiagnosticKind::MissingDependency {
+                                project_path: path.clone(),
+                                dependency_project_id: project_id,
+                                dependency_version_id: dependency
+                                    .version_id
+                                    .clone(),
+                            },
+                        });
+                    }
+                }
+                DependencyType::Optional | DependencyType::Embedded => {}
+            }
+        }
+
+        if matches!(file.project_type, ProjectType::Mod)
+            && matches!(
+                ProjectType::get_from_loaders(version.loaders.clone()),
+                Some(ProjectType::Mod)
+            )
+            && !version.loaders.iter().any(|x| profile.loader.can_load(x))
+        {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                kind: DiagnosticKind::WrongLoader {
+                    project_path: path.clone(),
+                    loaders: version.loaders.clone(),
+                    profile_loader: profile.loader,
+                },
+            });
+        }
+    }
+
+    let mut duplicates = by_project
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .collect::<Vec<_>>();
+    duplicates.sort_by_key(|(project_id, _)| *project_id);
+    for (project_id, files) in duplicates {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            kind: DiagnosticKind::Duplicate {
+                project_id: project_id.to_string(),
+                project_paths: files.iter().map(|x| x.0.to_string()).collect(),
+                version_ids: files.iter().map(|x| x.1.to_string()).collect(),
+            },
+        });
+    }
+
+    let shader_packs = projects
+        .iter()
+        .filter(|(_, file)| {
+            matches!(file.project_type, ProjectType::ShaderPack)
+        })
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    let has_shader_loader = projects.iter().any(|(_, file)| {
+        matches!(file.project_type, ProjectType::Mod)
+            && (file.metadata.as_ref().is_some_and(|x| {
+                SHADER_LOADER_PROJECTS.contains(&x.project_id.as_str())
+            }) || {
+                let file_name = file.file_name.to_lowercase();
+                SHADER_LOADER_FILE_NAMES
+                    .iter()
+                    .any(|x| file_name.contains(x))
+            })
+    });
+    if !shader_packs.is_empty() && !has_shader_loader {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            kind: DiagnosticKind::MissingShaderLoader {
+                project_paths: shader_packs,
+            },
+        });
+    }
+
+    Ok(diagnostics)
+}
@@ -0,0 +1,318 @@
+//! Migration of a profile and its projects to another game version or loader
+use crate::event::emit::emit_profile;
+use crate::event::ProfilePayloadType;
+use crate::launcher::get_loader_version_from_profile;
+use crate::profile::dependencies::is_compatible;
+use crate::profile::{get, snapshot};
+use crate::state::{
+    CachedEntry, ModLoader, Profile, ProjectType, ReleaseChannel, UpdateMode,
+    UpdatePolicy,
+};
+use crate::State;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// What migrating a profile changes
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MigrationPlan {
+    pub game_version: String,
+    pub loader: ModLoader,
+    pub loader_version: Option<String>,
+    /// Projects that are replaced by a compatible version
+    pub changes: Vec<ProjectMigration>,
+    /// Projects without a compatible version. They are disabled by the
+    /// migration
+    pub unavailable: Vec<UnavailableProject>,
+    /// Files not on Modrinth, which are left as they are
+    pub unknown: Vec<String>,
+    /// Pinned projects that don't work with the target. The profile can't be
+    /// migrated until they are unpinned or removed
+    pub pinned: Vec<UnavailableProject>,
+    /// Snapshot taken before the migration was applied. Restoring it rolls the
+    /// migration back
+    pub snapshot_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProjectMigration {
+    pub project_path: String,
+    pub project_id: String,
+    pub from_version_id: String,
+    pub to_version_id: String,
+    pub to_version_number: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UnavailableProject {
+    pub project_path: String,
+    pub project_id: String,
+    pub version_id: String,
+}
+
+/// Works out what migrating a profile to another game version and/or loader
+/// would change, without changing anything
+/// `loader_version` is "latest", "stable" or a loader version id. It defaults
+/// to the profile's loader version when that is available for the target game
+/// version, and to latest otherwise
+#[tracing::instrument]
+pub async fn plan(
+    profile_path: &str,
+    game_version: Option<String>,
+    loader: Option<ModLoader>,
+    loader_version: Option<String>,
+) -> crate::Result<MigrationPlan> {
+    let state = State::get().await?;
+    let profile = get(profile_path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(profile_path.to_string())
+    })?;
+
+    let game_version = game_version.unwrap_or(profile.game_version.clone());
+    let loader = loader.unwrap_or(profile.loader);
+    let loader_version = if loader == ModLoader::Vanilla {
+        None
+    } else {
+        // The profile's loader version is kept when the target game version
+        // has it
+        let current = match &profile.loader_version {
+            Some(current)
+                if loader_version.is_none() && loader == profile.loader =>
+            {
+                get_loader_version_from_profile(
+                    &game_version,
+                    loader,
+                    Some(current),
+                )
+                .await?
+            }
+            _ => None,
+        };
+        let version = match current {
+            Some(current) => current,
+            None => get_loader_version_from_profile(
+                &game_version,
+                loader,
+                loader_version.as_deref(),
+            )
+            .await?
+            .ok_or_else(|| {
+                crate::ErrorKind::InputError(format!(
+                    "No {} version is available for {game_version}",
+                    loader.as_str()
+                ))
+            })?,
+        };
+
+        Some(version.id)
+    };
+
+    let mut projects = profile
+        .get_projects(None, &state.pool, &state.api_semaphore)
+        .await?
+        .into_iter()
+        .collect::<Vec<_>>();
+    projects.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let project_versions = CachedEntry::get_project_versions_many(
+        &projects
+            .iter()
+            .filter_map(|(_, file)| file.metadata.as_ref())
+            .map(|x| &*x.project_id)
+            .collect::<Vec<_>>(),
+        None,
+        &state.pool,
+        &state.api_semaphore,
+    )
+    .await?;
+    let policies =
+        UpdatePolicy::get_from_profile(profile_path, &state.pool).await?;
+
+    let mut plan = MigrationPlan {
+        game_version,
+        loader,
+        loader_version,
+        changes: Vec::new(),
+        unavailable: Vec::new(),
+        unknown: Vec::new(),
+        pinned: Vec::new(),
+        snapshot_id: None,
+    };
+
+    for (project_path, file) in projects {
+        let Some(metadata) = file.metadata else {
+            plan.unknown.push(project_path);
+            continue;
+        };
+        let versions = project_versions
+            .iter()
+            .find(|x| x.project_id == metadata.project_id)
+            .map(|x| &*x.versions)
+            .unwrap_or_default();
+
+        // Projects that already work with the target are left alone
+        if versions.iter().any(|x| {
+            x.id == metadata.version_id
+                && is_compatible(x, &plan.game_version, plan.loader)
+        }) {
+            continue;
+        }
+
+        let policy = policies
+            .iter()
+            .find(|x| x.project_id == metadata.project_id);
+        if policy.is_some_and(|x| x.mode == UpdateMode::Pin) {
+            plan.pinned.push(UnavailableProject {
+                project_path,
+                project_id: metadata.project_id,
+                version_id: metadata.version_id,
+            });
+            continue;
+        }
+
+        // Projects keep their release channels, preferring releases and then
+        // the newest version
+        let channels = policy
+            .map(|x| x.channels.clone())
+            .unwrap_or_else(|| ReleaseChannel::iterator().collect());
+        let version = versions
+            .iter()
+            .filter(|x| is_compatible(x, &plan.game_version, plan.loader))
+            .filter(|x| {
+                ReleaseChannel::from_string(&x.version_type)
+                    .is_some_and(|x| channels.contains(&x))
+            })
+            .max_by_key(|x| (x.version_type == "release", x.date_published));
+
+        if let Some(version) = version {
+            plan.changes.push(ProjectMigration {
+                project_path,
+                project_id: metadata.project_id,
+                from_version_id: metadata.version_id,
+                to_version_id: version.id.clone(),
+                to_version_number: version.version_number.clone(),
+            });
+        } else {
+            plan.unavailable.push(UnavailableProject {
+                project_path,
+                project_id: metadata.project_id,
+                version_id: metadata.version_id,
+            });
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Migrates a profile to another game version and/or loader, replacing every
+/// project with a compatible version and disabling those without one
+/// Fails without changing anything if pinned projects don't work with the
+/// target
+/// If `dry_run` is set, returns the plan without changing anything. Otherwise
+/// the profile is snapshotted first, and restored if any step fails. The
+/// returned plan holds the snapshot's id, so the migration can be rolled back
+/// with `snapshot::restore`
+/// The game still needs to be installed for the new version, as with `install`
+#[tracing::instrument]
+pub async fn migrate(
+    profile_path: &str,
+    game_version: Option<String>,
+    loader: Option<ModLoader>,
+    loader_version: Option<String>,
+    dry_run: bool,
+) -> crate::Result<MigrationPlan> {
+    let mut plan =
+        plan(profile_path, game_version, loader, loader_version).await?;
+    if dry_run {
+        return Ok(plan);
+    }
+
+    if !plan.pinned.is_empty() {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Pinned projects don't work with {} {}: {}",
+            plan.loader.as_str(),
+            plan.game_version,
+            plan.pinned
+                .iter()
+                .map(|x| &*x.project_path)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+        .as_error());
+    }
+
+    let snapshot = snapshot::create(
+        profile_path,
+        Some(format!(
+            "Before migrating to {} {}",
+            plan.loader.as_str(),
+            plan.game_version
+        )),
+    )
+    .await?;
+    plan.snapshot_id = Some(snapshot.id.clone());
+
+    if let Err(err) = apply(profile_path, &plan).await {
+        if let Err(err) = snapshot::restore(&snapshot.id).await {
+            tracing::error!(
+                "Unable to restore {profile_path} after a failed migration: {err}"
+            );
+        }
+
+        return Err(err);
+    }
+
+    emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(plan)
+}
+
+async fn apply(profile_path: &str, plan: &MigrationPlan) -> crate::Result<()> {
+    let state = State::get().await?;
+
+    for change in &plan.changes {
+        let path = Profile::add_project_version(
+            profile_path,
+            &change.to_version_id,
+            ProjectType::get_world(&change.project_path),
+            &state.pool,
+            &state.fetch_semaphore,
+            &state.io_semaphore,
+        )
+        .await?;
+
+        if path != change.project_path {
+            Profile::remove_project(
+                profile_path,
+                &change.project_path,
+                &state.directories,
+                &state.pool,
+            )
+            .await?;
+        }
+
+        // Disabled projects stay disabled
+        if change.project_path.ends_with(".disabled") {
+            Profile::toggle_disable_project(profile_path, &path).await?;
+        }
+    }
+
+    for project in &plan.unavailable {
+        if !project.project_path.ends_with(".disabled") {
+            Profile::toggle_disable_project(
+                profile_path,
+                &project.project_path,
+            )
+            .await?;
+        }
+    }
+
+    let mut profile = get(profile_path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(profile_path.to_string())
+    })?;
+    profile.game_version = plan.game_version.clone();
+    profile.loader = plan.loader;
+    profile.loader_version = plan.loader_version.clone();
+    profile.modified = Utc::now();
+    profile.upsert(&state.pool).await?;
+
+    Ok(())
+}
@@ -4,12 +4,15 @@ use crate::event::emit::{
     emit_loading, init_loading, loading_try_for_each_concurrent,
 };
 use crate::event::LoadingBarType;
+use crate::launcher::QuickPlayType;
 use crate::pack::install_from::{
     EnvType, PackDependency, PackFile, PackFileHash, PackFormat,
 };
 use crate::state::{
-    CacheBehaviour, CachedEntry, Credentials, JavaVersion, Process,
-    ProfileFile, ProjectType, SideType,
+    CacheBehaviour, CachedEntry, ContentRef, Credentials, DirectoryInfo,
+    JavaVersion, ModSet, Process, ProfileFile, ProfileSnapshot, ProfileType,
+    ProjectType, ProjectUpdate, ReleaseChannel, SharedOption, SideType,
+    UpdateMode, UpdatePolicy,
 };
 
 use crate::event::{emit::emit_profile, ProfilePayloadType};
@@ -18,6 +21,7 @@ use crate::util::io::{self, IOError};
 pub use crate::{state::Profile, State};
 use async_zip::tokio::write::ZipFileWriter;
 use async_zip::{Compression, ZipEntryBuilder};
+use chrono::Utc;
 use serde_json::json;
 
 use std::collections::{HashMap, HashSet};
@@ -33,22 +37,115 @@ use std::{
 use tokio::io::AsyncReadExt;
 use tokio::{fs::File, process::Command, sync::RwLock};
 
+pub mod changelogs;
 pub mod create;
+pub mod datapacks;
+pub mod dependencies;
+pub mod doctor;
+pub mod migrate;
+pub mod mod_sets;
+pub mod options;
+pub mod resource_packs;
+pub mod server_config;
+pub mod servers;
+pub mod snapshot;
 pub mod update;
+pub mod worlds;
 
-/// Remove a profile
+/// Remove a profile, moving it to the trash unless the trash is disabled
+/// Its snapshots, update history, update policies and mod sets are kept
+/// along with it, until it is restored or deleted from the trash
 #[tracing::instrument]
 pub async fn remove(path: &str) -> crate::Result<()> {
     let state = State::get().await?;
 
+    // The folder is moved to the trash first, so it is not deleted below
+    let trash_item = match get(path).await? {
+        Some(profile) => crate::trash::trash_profile(&profile).await?,
+        None => None,
+    };
+    let Some(trash_item) = trash_item else {
+        return delete(path).await;
+    };
+
+    let mut transaction = state.pool.begin().await?;
+
+    Profile::remove(path, &mut transaction).await?;
+    move_profile_data(
+        path,
+        &trash_item.data_key(),
+        &state.directories,
+        &mut transaction,
+    )
+    .await?;
+
+    emit_profile(path, ProfilePayloadType::Removed).await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// Permanently deletes a profile, without going through the trash
+#[tracing::instrument]
+pub(crate) async fn delete(path: &str) -> crate::Result<()> {
+    let state = State::get().await?;
     let mut transaction = state.pool.begin().await?;
 
     Profile::remove(path, &mut transaction).await?;
+    let hashes =
+        delete_profile_data(path, &state.directories, &mut transaction).await?;
 
     emit_profile(path, ProfilePayloadType::Removed).await?;
 
     transaction.commit().await?;
 
+    ContentRef::collect_garbage(&hashes, &state.directories, &state.pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Deletes the snapshots, file references, update history, update policies,
+/// mod sets and applied shared options stored under a profile path
+/// Returns the hashes of the files in the content store they pointed to
+pub(crate) async fn delete_profile_data(
+    path: &str,
+    dirs: &DirectoryInfo,
+    transaction: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+) -> crate::Result<Vec<String>> {
+    ProfileSnapshot::remove_from_profile(path, dirs, &mut **transaction)
+        .await?;
+    let mut hashes =
+        ContentRef::remove_from_profile(path, &mut **transaction).await?;
+    hashes.extend(
+        ProjectUpdate::remove_from_profile(path, &mut **transaction).await?,
+    );
+    UpdatePolicy::remove_from_profile(path, &mut **transaction).await?;
+    ModSet::remove_from_profile(path, &mut **transaction).await?;
+    SharedOption::remove_applied(path, &mut **transaction).await?;
+
+    Ok(hashes)
+}
+
+/// Moves the snapshots, file references, update history, update policies
+/// and mod sets of a profile to another profile path. The shared options
+/// applied at its last launch are dropped, as they only matter until the
+/// game exits
+pub(crate) async fn move_profile_data(
+    from_path: &str,
+    to_path: &str,
+    dirs: &DirectoryInfo,
+    transaction: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+) -> crate::Result<()> {
+    ProfileSnapshot::move_profile(from_path, to_path, dirs, &mut **transaction)
+        .await?;
+    ContentRef::move_profile(from_path, to_path, &mut **transaction).await?;
+    ProjectUpdate::move_profile(from_path, to_path, &mut **transaction).await?;
+    UpdatePolicy::move_profile(from_path, to_path, &mut **transaction).await?;
+    ModSet::move_profile(from_path, to_path, &mut **transaction).await?;
+    SharedOption::remove_applied(from_path, &mut **transaction).await?;
+
     Ok(())
 }
 
@@ -237,6 +334,9 @@ pub async fn install(path: &str, force: bool) -> crate::Result<()> {
     Ok(())
 }
 
+/// Updates every project of a profile that has an update available
+/// The replaced files are kept as a single update, which can be rolled back
+/// with `rollback_update`
 #[tracing::instrument]
 pub async fn update_all_projects(
     profile_path: &str,
@@ -267,9 +367,10 @@ pub async fn update_all_projects(
         let len = keys.len();
 
         let map = Arc::new(RwLock::new(HashMap::new()));
+        let generation = uuid::Uuid::new_v4().to_string();
 
         use futures::StreamExt;
-        loading_try_for_each_concurrent(
+        let res = loading_try_for_each_concurrent(
             futures::stream::iter(keys).map(Ok::<String, crate::Error>),
             None,
             Some(&loading_bar),
@@ -278,11 +379,15 @@ pub async fn update_all_projects(
             None,
             |project| async {
                 let map = map.clone();
+                let generation = &generation;
 
                 async move {
-                    let new_path =
-                        update_project(profile_path, &project, Some(true))
-                            .await?;
+                    let new_path = update_project_in_generation(
+                        profile_path,
+                        &project,
+                        generation,
+                    )
+                    .await?;
 
                     map.write().await.insert(project, new_path);
 
@@ -291,7 +396,11 @@ pub async fn update_all_projects(
                 .await
             },
         )
-        .await?;
+        .await;
+
+        // Projects updated before a failure can still be rolled back
+        prune_update_history(profile_path).await?;
+        res?;
 
         emit_profile(profile_path, ProfilePayloadType::Edited).await?;
 
@@ -306,75 +415,347 @@ pub async fn update_all_projects(
 
 /// Updates a project to the latest version
 /// Uses and returns the relative path to the project
+/// The replaced file is kept, and the update can be rolled back with
+/// `rollback_project`
 #[tracing::instrument]
 pub async fn update_project(
     profile_path: &str,
     project_path: &str,
     skip_send_event: Option<bool>,
 ) -> crate::Result<String> {
-    if let Some(profile) = get(profile_path).await? {
-        let state = State::get().await?;
-        if let Some((_, file)) = profile
-            .get_projects(
-                Some(CacheBehaviour::MustRevalidate),
+    let generation = uuid::Uuid::new_v4().to_string();
+    let path =
+        update_project_in_generation(profile_path, project_path, &generation)
+            .await?;
+    prune_update_history(profile_path).await?;
+
+    if !skip_send_event.unwrap_or(false) {
+        emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+    }
+
+    Ok(path)
+}
+
+// Updates a project, recording the replaced file under the given update
+async fn update_project_in_generation(
+    profile_path: &str,
+    project_path: &str,
+    generation: &str,
+) -> crate::Result<String> {
+    let profile = get(profile_path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(profile_path.to_string())
+    })?;
+
+    let state = State::get().await?;
+    let Some((_, file)) = profile
+        .get_projects(
+            Some(CacheBehaviour::MustRevalidate),
+            &state.pool,
+            &state.api_semaphore,
+        )
+        .await?
+        .remove(project_path)
+    else {
+        return Err(crate::ErrorKind::InputError(
+            "This project cannot be updated!".to_string(),
+        )
+        .as_error());
+    };
+    let Some(update_version) = &file.update_version_id else {
+        return Err(crate::ErrorKind::InputError(
+            "This project cannot be updated!".to_string(),
+        )
+        .as_error());
+    };
+
+    // The new version may be written over the old file, so it is kept first,
+    // along with the history entry that keeps it in the store
+    let full_path = get_full_path(profile_path).await?;
+    ContentRef::store_file(
+        &file.hash,
+        &full_path.join(project_path),
+        &state.directories,
+        &state.io_semaphore,
+    )
+    .await?;
+
+    let mut update = ProjectUpdate {
+        generation: generation.to_string(),
+        profile_path: profile_path.to_string(),
+        created: Utc::now(),
+        project_path: project_path.to_string(),
+        new_project_path: project_path.to_string(),
+        hash: file.hash.clone(),
+        old_version_id: file.metadata.map(|x| x.version_id),
+        new_version_id: update_version.clone(),
+    };
+    update.upsert(&state.pool).await?;
+
+    let path = match Profile::add_project_version(
+        profile_path,
+        update_version,
+        ProjectType::get_world(project_path),
+        &state.pool,
+        &state.fetch_semaphore,
+        &state.io_semaphore,
+    )
+    .await
+    {
+        Ok(path) => path,
+        Err(err) => {
+            ProjectUpdate::remove(generation, project_path, &state.pool)
+                .await?;
+            ContentRef::collect_garbage(
+                &[file.hash],
+                &state.directories,
                 &state.pool,
-                &state.api_semaphore,
             )
-            .await?
-            .remove(project_path)
-        {
-            if let Some(update_version) = &file.update_version_id {
-                let path = Profile::add_project_version(
-                    profile_path,
-                    update_version,
-                    &state.pool,
-                    &state.fetch_semaphore,
-                    &state.io_semaphore,
-                )
-                .await?;
+            .await?;
+            return Err(err);
+        }
+    };
 
-                if path != project_path {
-                    Profile::remove_project(profile_path, project_path).await?;
-                }
+    if path != project_path {
+        update.new_project_path = path.clone();
+        update.upsert(&state.pool).await?;
 
-                if !skip_send_event.unwrap_or(false) {
-                    emit_profile(profile_path, ProfilePayloadType::Edited)
-                        .await?;
-                }
+        Profile::remove_project(
+            profile_path,
+            project_path,
+            &state.directories,
+            &state.pool,
+        )
+        .await?;
+    }
 
-                return Ok(path);
-            }
-        }
+    Ok(path)
+}
 
-        Err(crate::ErrorKind::InputError(
-            "This project cannot be updated!".to_string(),
+// Drops the oldest updates of a profile past the configured history length
+async fn prune_update_history(profile_path: &str) -> crate::Result<()> {
+    let state = State::get().await?;
+    let settings = Settings::get(&state.pool).await?;
+
+    let hashes = ProjectUpdate::prune(
+        profile_path,
+        settings.update_history_generations,
+        &state.pool,
+    )
+    .await?;
+    ContentRef::collect_garbage(&hashes, &state.directories, &state.pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Gets how a project of a profile is updated
+#[tracing::instrument]
+pub async fn get_update_policy(
+    profile_path: &str,
+    project_id: &str,
+) -> crate::Result<UpdatePolicy> {
+    let state = State::get().await?;
+    let policy = UpdatePolicy::get_from_profile(profile_path, &state.pool)
+        .await?
+        .into_iter()
+        .find(|x| x.project_id == project_id)
+        .unwrap_or_else(|| UpdatePolicy::default_for(profile_path, project_id));
+    Ok(policy)
+}
+
+/// Sets how a project of a profile is updated: which release channels updates
+/// may come from, and whether the project is pinned or follows the newest
+/// version regardless of game version
+#[tracing::instrument]
+pub async fn set_update_policy(
+    profile_path: &str,
+    project_id: &str,
+    channels: Vec<ReleaseChannel>,
+    mode: UpdateMode,
+) -> crate::Result<()> {
+    if channels.is_empty() {
+        return Err(crate::ErrorKind::InputError(
+            "At least one release channel must be allowed".to_string(),
         )
-        .as_error())
+        .as_error());
+    }
+
+    let state = State::get().await?;
+    let policy = UpdatePolicy {
+        profile_path: profile_path.to_string(),
+        project_id: project_id.to_string(),
+        channels,
+        mode,
+    };
+
+    // Only policies that differ from the default are stored
+    if policy.is_default() {
+        UpdatePolicy::remove(profile_path, project_id, &state.pool).await?;
     } else {
-        Err(
-            crate::ErrorKind::UnmanagedProfileError(profile_path.to_string())
-                .as_error(),
+        policy.upsert(&state.pool).await?;
+    }
+
+    emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(())
+}
+
+/// Gets the updates of a profile that can be rolled back, newest first
+/// Every project replaced by the same update shares a generation
+#[tracing::instrument]
+pub async fn get_update_history(
+    profile_path: &str,
+) -> crate::Result<Vec<ProjectUpdate>> {
+    let state = State::get().await?;
+    let history =
+        ProjectUpdate::get_from_profile(profile_path, &state.pool).await?;
+    Ok(history)
+}
+
+/// Rolls back the latest update of a project, restoring the file it replaced
+/// Takes the current relative path of the project, and returns the relative
+/// path of the restored file
+#[tracing::instrument]
+pub async fn rollback_project(
+    profile_path: &str,
+    project_path: &str,
+) -> crate::Result<String> {
+    let state = State::get().await?;
+    ensure_not_running(profile_path, "roll back updates").await?;
+
+    let update = ProjectUpdate::get_from_profile(profile_path, &state.pool)
+        .await?
+        .into_iter()
+        .find(|x| x.new_project_path == project_path)
+        .ok_or_else(|| {
+            crate::ErrorKind::InputError(format!(
+                "No update of {project_path} to roll back"
+            ))
+        })?;
+
+    let path = restore_update(&update).await?;
+
+    emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(path)
+}
+
+/// Rolls back every project replaced by an update, such as a call to
+/// `update_all_projects`
+/// Returns a map of the rolled back relative paths to the restored ones
+#[tracing::instrument]
+pub async fn rollback_update(
+    profile_path: &str,
+    generation: &str,
+) -> crate::Result<HashMap<String, String>> {
+    let state = State::get().await?;
+    ensure_not_running(profile_path, "roll back updates").await?;
+
+    let updates =
+        ProjectUpdate::get_generation(profile_path, generation, &state.pool)
+            .await?;
+    if updates.is_empty() {
+        return Err(crate::ErrorKind::InputError(format!(
+            "No update {generation} to roll back"
+        ))
+        .as_error());
+    }
+
+    let mut map = HashMap::new();
+    for update in updates {
+        let path = restore_update(&update).await?;
+        map.insert(update.new_project_path, path);
+    }
+
+    emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(map)
+}
+
+/// Fails if a profile is running, as the game holds onto its files and
+/// rewrites some of them when it exits. `action` describes what was refused
+pub(crate) async fn ensure_not_running(
+    profile_path: &str,
+    action: &str,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+
+    if !Process::get_from_profile(profile_path, &state.pool)
+        .await?
+        .is_empty()
+    {
+        return Err(crate::ErrorKind::LauncherError(format!(
+            "Cannot {action} of {profile_path} while it is running"
+        ))
+        .as_error());
+    }
+
+    Ok(())
+}
+
+// Puts the file replaced by an update back in place of the one replacing it
+async fn restore_update(update: &ProjectUpdate) -> crate::Result<String> {
+    let state = State::get().await?;
+    let full_path = get_full_path(&update.profile_path).await?;
+
+    if full_path.join(&update.new_project_path).is_file() {
+        Profile::remove_project(
+            &update.profile_path,
+            &update.new_project_path,
+            &state.directories,
+            &state.pool,
         )
+        .await?;
     }
+
+    ContentRef::link(
+        &update.profile_path,
+        &update.project_path,
+        &update.hash,
+        &full_path,
+        &state.directories,
+        &state.io_semaphore,
+        &state.pool,
+    )
+    .await?;
+
+    ProjectUpdate::remove(
+        &update.generation,
+        &update.project_path,
+        &state.pool,
+    )
+    .await?;
+
+    Ok(update.project_path.clone())
 }
 
 /// Add a project from a version
+/// Datapacks are installed into `world`'s datapacks/ folder when it is given
 /// Returns the relative path to the project as a ProjectPathId
 #[tracing::instrument]
 pub async fn add_project_from_version(
     profile_path: &str,
     version_id: &str,
+    world: Option<&str>,
 ) -> crate::Result<String> {
     let state = State::get().await?;
+    if let Some(world) = world {
+        worlds::get_world_path(profile_path, world).await?;
+    }
+
     let project_path = Profile::add_project_version(
         profile_path,
         version_id,
+        world,
         &state.pool,
         &state.fetch_semaphore,
         &state.io_semaphore,
     )
     .await?;
 
+    if world.is_some() {
+        datapacks::watch_worlds(profile_path).await?;
+    }
     emit_profile(profile_path, ProfilePayloadType::Edited).await?;
 
     Ok(project_path)
@@ -403,6 +784,7 @@ pub async fn add_project_from_path(
         bytes::Bytes::from(file),
         None,
         project_type,
+        None,
         &state.io_semaphore,
         &state.pool,
     )
@@ -433,7 +815,7 @@ pub async fn remove_project(
     profile_path: &str,
     project: &str,
 ) -> crate::Result<()> {
-    Profile::remove_project(profile_path, project).await?;
+    crate::trash::trash_project(profile_path, project).await?;
 
     emit_profile(profile_path, ProfilePayloadType::Edited).await?;
 
@@ -613,15 +995,27 @@ fn pack_get_relative_path(
 
 /// Run Minecraft using a profile and the default credentials, logged in credentials,
 /// failing with an error if no credentials are available
+/// If a quick play target is given, the game starts straight into it
 #[tracing::instrument]
-pub async fn run(path: &str) -> crate::Result<Process> {
+pub async fn run(
+    path: &str,
+    quick_play: Option<QuickPlayType>,
+) -> crate::Result<Process> {
     let state = State::get().await?;
 
+    // Dedicated servers are started without an account
+    if get(path)
+        .await?
+        .is_some_and(|x| x.profile_type == ProfileType::Server)
+    {
+        return launch(path, None, None).await;
+    }
+
     let default_account = Credentials::get_active(&state.pool)
         .await?
         .ok_or_else(|| crate::ErrorKind::NoCredentialsError.as_error())?;
 
-    run_credentials(path, &default_account).await
+    run_credentials(path, &default_account, quick_play).await
 }
 
 /// Run Minecraft using a profile, and credentials for authentication
@@ -631,6 +1025,18 @@ pub async fn run(path: &str) -> crate::Result<Process> {
 pub async fn run_credentials(
     path: &str,
     credentials: &Credentials,
+    quick_play: Option<QuickPlayType>,
+) -> crate::Result<Process> {
+    launch(path, Some(credentials), quick_play).await
+}
+
+/// Runs a profile's game, or its dedicated server for server profiles, which
+/// ignore the credentials and quick play target
+#[tracing::instrument(skip(credentials))]
+async fn launch(
+    path: &str,
+    credentials: Option<&Credentials>,
+    quick_play: Option<QuickPlayType>,
 ) -> crate::Result<Process> {
     let state = State::get().await?;
     let settings = Settings::get(&state.pool).await?;
@@ -700,6 +1106,21 @@ pub async fn run_credentials(
         mc_set_options.push(("fullscreen".to_string(), "true".to_string()));
     }
 
+    if profile.profile_type == ProfileType::Server {
+        return crate::launcher::launch_server(
+            &java_args,
+            &env_args,
+            &wrapper,
+            &memory,
+            post_exit_hook,
+            &profile,
+        )
+        .await;
+    }
+
+    let credentials = credentials
+        .ok_or_else(|| crate::ErrorKind::NoCredentialsError.as_error())?;
+
     crate::launcher::launch_minecraft(
         &java_args,
         &env_args,
@@ -708,6 +1129,7 @@ pub async fn run_credentials(
         &memory,
         &resolution,
         credentials,
+        quick_play.as_ref(),
         post_exit_hook,
         &profile,
     )
@@ -0,0 +1,171 @@
+//! Theseus mod sets: named groups of projects that are enabled or disabled
+//! together
+use crate::event::emit::emit_profile;
+use crate::event::ProfilePayloadType;
+use crate::profile::get;
+use crate::state::{ModSet, ModSetEntry, Profile, ProjectType};
+use crate::State;
+use chrono::Utc;
+use std::collections::HashMap;
+
+/// Lists all mod sets of a profile, oldest first
+#[tracing::instrument]
+pub async fn list(profile_path: &str) -> crate::Result<Vec<ModSet>> {
+    let state = State::get().await?;
+    let sets = ModSet::get_from_profile(profile_path, &state.pool).await?;
+    Ok(sets)
+}
+
+/// Get a mod set by its id
+#[tracing::instrument]
+pub async fn get_mod_set(id: &str) -> crate::Result<Option<ModSet>> {
+    let state = State::get().await?;
+    let set = ModSet::get(id, &state.pool).await?;
+    Ok(set)
+}
+
+/// Creates a mod set for a profile
+#[tracing::instrument]
+pub async fn create(
+    profile_path: &str,
+    name: String,
+    exclusive: bool,
+    entries: Vec<ModSetEntry>,
+) -> crate::Result<ModSet> {
+    let state = State::get().await?;
+    get(profile_path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(profile_path.to_string())
+    })?;
+
+    let set = ModSet {
+        id: uuid::Uuid::new_v4().to_string(),
+        profile_path: profile_path.to_string(),
+        name,
+        created: Utc::now(),
+        exclusive,
+        entries: validate_entries(entries)?,
+    };
+    set.upsert(&state.pool).await?;
+
+    Ok(set)
+}
+
+/// Edits the name, exclusivity or projects of a mod set
+#[tracing::instrument]
+pub async fn edit(
+    id: &str,
+    name: Option<String>,
+    exclusive: Option<bool>,
+    entries: Option<Vec<ModSetEntry>>,
+) -> crate::Result<ModSet> {
+    let state = State::get().await?;
+    let mut set = ModSet::get(id, &state.pool).await?.ok_or_else(|| {
+        crate::ErrorKind::InputError(format!("Mod set {id} does not exist"))
+    })?;
+
+    if let Some(name) = name {
+        set.name = name;
+    }
+    if let Some(exclusive) = exclusive {
+        set.exclusive = exclusive;
+    }
+    if let Some(entries) = entries {
+        set.entries = validate_entries(entries)?;
+    }
+    set.upsert(&state.pool).await?;
+
+    Ok(set)
+}
+
+/// Removes a mod set. The profile's projects are left as they are
+#[tracing::instrument]
+pub async fn remove(id: &str) -> crate::Result<()> {
+    let state = State::get().await?;
+    ModSet::remove(id, &state.pool).await?;
+    Ok(())
+}
+
+/// Activates a mod set, enabling and disabling the profile's projects as the
+/// set describes
+/// Either every project is switched or none is. Returns a map of the switched
+/// projects' previous relative paths to their new ones
+#[tracing::instrument]
+pub async fn activate(id: &str) -> crate::Result<HashMap<String, String>> {
+    let state = State::get().await?;
+    let set = ModSet::get(id, &state.pool).await?.ok_or_else(|| {
+        crate::ErrorKind::InputError(format!("Mod set {id} does not exist"))
+    })?;
+    let profile = get(&set.profile_path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(set.profile_path.clone())
+    })?;
+
+    let mut projects = profile
+        .get_projects(None, &state.pool, &state.api_semaphore)
+        .await?
+        .into_iter()
+        .collect::<Vec<_>>();
+    projects.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let toggles = projects
+        .iter()
+        .filter(|(path, file)| {
+            let enabled = match set.entries.iter().find(|x| x.matches(file)) {
+                Some(entry) => entry.enabled,
+                None if set.exclusive
+                    && matches!(file.project_type, ProjectType::Mod) =>
+                {
+                    false
+                }
+                None => return false,
+            };
+
+            path.ends_with(".disabled") == enabled
+        })
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+
+    let mut switched = HashMap::new();
+    for path in toggles {
+        match Profile::toggle_disable_project(&profile.path, &path).await {
+            Ok(new_path) => {
+                switched.insert(path, new_path);
+            }
+            Err(err) => {
+                // Put back what was already switched
+                for new_path in switched.values() {
+                    if let Err(err) =
+                        Profile::toggle_disable_project(&profile.path, new_path)
+                            .await
+                    {
+                        tracing::error!(
+                            "Unable to restore {new_path} in {}: {err}",
+                            profile.path
+                        );
+                    }
+                }
+
+                return Err(err);
+            }
+        }
+    }
+
+    emit_profile(&profile.path, ProfilePayloadType::Edited).await?;
+
+    Ok(switched)
+}
+
+fn validate_entries(
+    entries: Vec<ModSetEntry>,
+) -> crate::Result<Vec<ModSetEntry>> {
+    if entries
+        .iter()
+        .any(|x| x.project_id.is_none() && x.hash.is_none())
+    {
+        return Err(crate::ErrorKind::InputError(
+            "Mod set entries need a project id or a hash".to_string(),
+        )
+        .as_error());
+    }
+
+    Ok(entries)
+}
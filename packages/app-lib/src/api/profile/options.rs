@@ -0,0 +1,444 @@
+//! Theseus profile game options (options.txt, optionsof.txt and
+//! optionsshaders.txt) management
+use crate::event::emit::emit_profile;
+use crate::event::ProfilePayloadType;
+use crate::profile::{ensure_not_running, get_full_path};
+use crate::util::io;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Prefix of the keys of keybinds in options.txt
+const KEYBIND_PREFIX: &str = "key_";
+
+/// A game options file in a profile's folder
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionsFile {
+    /// The game's own options
+    Vanilla,
+    /// OptiFine's options
+    #[serde(rename = "optifine")]
+    OptiFine,
+    /// OptiFine's shader options
+    Shaders,
+}
+
+impl OptionsFile {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            OptionsFile::Vanilla => "options.txt",
+            OptionsFile::OptiFine => "optionsof.txt",
+            OptionsFile::Shaders => "optionsshaders.txt",
+        }
+    }
+
+    fn separator(&self) -> char {
+        match self {
+            OptionsFile::Vanilla | OptionsFile::OptiFine => ':',
+            OptionsFile::Shaders => '=',
+        }
+    }
+}
+
+/// The options most worth changing from outside of the game. Fields left
+/// unset are either missing from the file or left unchanged when editing
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CommonOptions {
+    /// 0 is automatic
+    pub gui_scale: Option<u32>,
+    /// Field of view, in degrees
+    pub fov: Option<u32>,
+    /// Language code, such as `en_us`
+    pub language: Option<String>,
+    pub fullscreen: Option<bool>,
+    /// Enabled resource packs, from the bottom of the list to the top, as
+    /// `vanilla` and `file/<file name>` for packs in resourcepacks/
+    pub resource_packs: Option<Vec<String>>,
+    /// Enabled resource packs made for other game versions
+    pub incompatible_resource_packs: Option<Vec<String>>,
+    /// Keys bound to each keybind, such as `key.jump` to
+    /// `key.keyboard.space`
+    #[serde(default)]
+    pub keybinds: HashMap<String, String>,
+}
+
+/// The contents of a game options file. Lines are kept in order, with the
+/// keys the launcher doesn't know about untouched
+#[derive(Clone, Debug)]
+pub struct GameOptions {
+    file: OptionsFile,
+    lines: Vec<OptionsLine>,
+}
+
+#[derive(Clone, Debug)]
+enum OptionsLine {
+    Option { key: String, value: String },
+    // Lines without a key, kept as they are
+    Other(String),
+}
+
+impl GameOptions {
+    /// Reads an options file from a profile's folder. Missing files have no
+    /// options
+    pub async fn read(
+        profile_dir: &Path,
+        file: OptionsFile,
+    ) -> crate::Result<Self> {
+        let path = profile_dir.join(file.file_name());
+        if !path.exists() {
+            return Ok(Self::parse("", file));
+        }
+
+        Ok(Self::parse(&io::read_to_string(&path).await?, file))
+    }
+
+    pub fn parse(contents: &str, file: OptionsFile) -> Self {
+        let lines = contents
+            .lines()
+            .map(|line| match line.split_once(file.separator()) {
+                Some((key, value)) if !key.is_empty() => OptionsLine::Option {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                },
+                _ => OptionsLine::Other(line.to_string()),
+            })
+            .collect();
+
+        Self { file, lines }
+    }
+
+    /// Writes the options back into a profile's folder
+    pub async fn write(&self, profile_dir: &Path) -> crate::Result<()> {
+        io::write(profile_dir.join(self.file.file_name()), self.serialize())
+            .await?;
+
+        Ok(())
+    }
+
+    pub fn serialize(&self) -> String {
+        let separator = self.file.separator();
+
+        self.lines
+            .iter()
+            .map(|x| match x {
+                OptionsLine::Option { key, value } => {
+                    format!("{key}{separator}{value}\n")
+                }
+                OptionsLine::Other(line) => format!("{line}\n"),
+            })
+            .collect()
+    }
+
+    /// All options, in the order of the file
+    pub fn entries(&self) -> Vec<(String, String)> {
+        self.lines
+            .iter()
+            .filter_map(|x| match x {
+                OptionsLine::Option { key, value } => {
+                    Some((key.clone(), value.clone()))
+                }
+                OptionsLine::Other(_) => None,
+            })
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines.iter().find_map(|x| match x {
+            OptionsLine::Option { key: k, value } if k == key => Some(&**value),
+            _ => None,
+        })
+    }
+
+    /// Replaces an option's value in place, or adds it to the end
+    pub fn set(&mut self, key: &str, new_value: impl Into<String>) {
+        let new_value = new_value.into();
+        for line in &mut self.lines {
+            if let OptionsLine::Option { key: k, value } = line {
+                if k == key {
+                    *value = new_value;
+                    return;
+                }
+            }
+        }
+
+        self.lines.push(OptionsLine::Option {
+            key: key.to_string(),
+            value: new_value,
+        });
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.lines.retain(
+            |x| !matches!(x, OptionsLine::Option { key: k, .. } if k == key),
+        );
+    }
+
+    pub fn gui_scale(&self) -> Option<u32> {
+        self.get("guiScale")?.parse().ok()
+    }
+
+    pub fn set_gui_scale(&mut self, gui_scale: u32) {
+        self.set("guiScale", gui_scale.to_string());
+    }
+
+    /// Field of view in degrees. The game stores it as an offset from 70
+    /// degrees, over 40
+    pub fn fov(&self) -> Option<u32> {
+        let fov = self.get("fov")?.parse::<f64>().ok()?;
+        Some((fov * 40.0 + 70.0).round().max(0.0) as u32)
+    }
+
+    pub fn set_fov(&mut self, fov: u32) {
+        self.set("fov", format!("{:?}", (fov as f64 - 70.0) / 40.0));
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.get("lang")
+    }
+
+    pub fn set_language(&mut self, language: &str) {
+        self.set("lang", language);
+    }
+
+    pub fn fullscreen(&self) -> Option<bool> {
+        self.get("fullscreen")?.parse().ok()
+    }
+
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.set("fullscreen", fullscreen.to_string());
+    }
+
+    pub fn resource_packs(&self) -> Option<Vec<String>> {
+        serde_json::from_str(self.get("resourcePacks")?).ok()
+    }
+
+    pub fn set_resource_packs(&mut self, packs: &[String]) {
+        self.set("resourcePacks", json_list(packs));
+    }
+
+    pub fn incompatible_resource_packs(&self) -> Option<Vec<String>> {
+        serde_json::from_str(self.get("incompatibleResourcePacks")?).ok()
+    }
+
+    pub fn set_incompatible_resource_packs(&mut self, packs: &[String]) {
+        self.set("incompatibleResourcePacks", json_list(packs));
+    }
+
+    /// Keys bound to each keybind, by the keybind's name without the `key_`
+    /// prefix of its option
+    pub fn keybinds(&self) -> HashMap<String, String> {
+        self.entries()
+            .into_iter()
+            .filter_map(|(key, value)| {
+                Some((key.strip_prefix(KEYBIND_PREFIX)?.to_string(), value))
+            })
+            .collect()
+    }
+
+    pub fn set_keybind(&mut self, keybind: &str, key: &str) {
+        self.set(&format!("{KEYBIND_PREFIX}{keybind}"), key);
+    }
+
+    pub fn common(&self) -> CommonOptions {
+        CommonOptions {
+            gui_scale: self.gui_scale(),
+            fov: self.fov(),
+            language: self.language().map(|x| x.to_string()),
+            fullscreen: self.fullscreen(),
+            resource_packs: self.resource_packs(),
+            incompatible_resource_packs: self.incompatible_resource_packs(),
+            keybinds: self.keybinds(),
+        }
+    }
+
+    /// Sets the options that are set in `options`, leaving the rest as they
+    /// are
+    pub fn apply_common(&mut self, options: &CommonOptions) {
+        if let Some(gui_scale) = options.gui_scale {
+            self.set_gui_scale(gui_scale);
+        }
+        if let Some(fov) = options.fov {
+            self.set_fov(fov);
+        }
+        if let Some(language) = &options.language {
+            self.set_language(language);
+        }
+        if let Some(fullscreen) = options.fullscreen {
+            self.set_fullscreen(fullscreen);
+        }
+        if let Some(packs) = &options.resource_packs {
+            self.set_resource_packs(packs);
+        }
+        if let Some(packs) = &options.incompatible_resource_packs {
+            self.set_incompatible_resource_packs(packs);
+        }
+        for (keybind, key) in &options.keybinds {
+            self.set_keybind(keybind, key);
+        }
+    }
+}
+
+// The game writes lists as compact JSON
+fn json_list(values: &[String]) -> String {
+    serde_json::to_string(values).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Gets the options in one of a profile's options files, in the order of
+/// the file
+#[tracing::instrument]
+pub async fn get(
+    profile_path: &str,
+    file: OptionsFile,
+) -> crate::Result<Vec<(String, String)>> {
+    let profile_dir = get_full_path(profile_path).await?;
+
+    Ok(GameOptions::read(&profile_dir, file).await?.entries())
+}
+
+/// Edits options in one of a profile's options files, keeping the rest of
+/// the file as it is. Options set to `None` are removed, so the game uses
+/// their defaults. Fails while the profile is running, as the game rewrites
+/// the file when it exits
+/// Returns the options after the edit
+#[tracing::instrument]
+pub async fn edit(
+    profile_path: &str,
+    file: OptionsFile,
+    options: HashMap<String, Option<String>>,
+) -> crate::Result<Vec<(String, String)>> {
+    ensure_not_running(profile_path, "edit the options").await?;
+
+    let profile_dir = get_full_path(profile_path).await?;
+    let mut game_options = GameOptions::read(&profile_dir, file).await?;
+
+    for (key, value) in options {
+        match value {
+            Some(value) => game_options.set(&key, value),
+            None => game_options.remove(&key),
+        }
+    }
+
+    game_options.write(&profile_dir).await?;
+    emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(game_options.entries())
+}
+
+/// Gets the common options of a profile's options.txt
+#[tracing::instrument]
+pub async fn get_common(profile_path: &str) -> crate::Result<CommonOptions> {
+    let profile_dir = get_full_path(profile_path).await?;
+
+    Ok(GameOptions::read(&profile_dir, OptionsFile::Vanilla)
+        .await?
+        .common())
+}
+
+/// Sets the common options that are set in `options` in a profile's
+/// options.txt. Fails while the profile is running, as the game rewrites the
+/// file when it exits
+/// Returns the common options after the edit
+#[tracing::instrument]
+pub async fn edit_common(
+    profile_path: &str,
+    options: CommonOptions,
+) -> crate::Result<CommonOptions> {
+    ensure_not_running(profile_path, "edit the options").await?;
+
+    let profile_dir = get_full_path(profile_path).await?;
+    let mut game_options =
+        GameOptions::read(&profile_dir, OptionsFile::Vanilla).await?;
+
+    game_options.apply_common(&options);
+
+    game_options.write(&profile_dir).await?;
+    emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(game_options.common())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTIONS: &str = "\
+version:3953
+autoJump:false
+fov:0.0
+guiScale:0
+lang:en_us
+fullscreen:false
+resourcePacks:[\"vanilla\",\"file/Faithful 32x.zip\"]
+incompatibleResourcePacks:[]
+lastServer:play.example.com:25565
+key_key.jump:key.keyboard.space
+key_key.sneak:key.keyboard.left.shift
+soundCategory_master:1.0
+";
+
+    #[test]
+    fn options_round_trip() {
+        let options = GameOptions::parse(OPTIONS, OptionsFile::Vanilla);
+        let common = options.common();
+
+        assert_eq!(common.gui_scale, Some(0));
+        assert_eq!(common.fov, Some(70));
+        assert_eq!(common.language.as_deref(), Some("en_us"));
+        assert_eq!(common.fullscreen, Some(false));
+        assert_eq!(
+            common.resource_packs,
+            Some(vec![
+                "vanilla".to_string(),
+                "file/Faithful 32x.zip".to_string()
+            ])
+        );
+        assert_eq!(common.incompatible_resource_packs, Some(Vec::new()));
+        assert_eq!(common.keybinds["key.jump"], "key.keyboard.space");
+        assert_eq!(options.get("lastServer"), Some("play.example.com:25565"));
+        assert_eq!(options.serialize(), OPTIONS);
+    }
+
+    #[test]
+    fn options_apply_common_in_place() {
+        let mut options = GameOptions::parse(OPTIONS, OptionsFile::Vanilla);
+        options.apply_common(&CommonOptions {
+            fov: Some(90),
+            fullscreen: Some(true),
+            resource_packs: Some(vec!["vanilla".to_string()]),
+            keybinds: HashMap::from([(
+                "key.sprint".to_string(),
+                "key.keyboard.left.control".to_string(),
+            )]),
+            ..Default::default()
+        });
+
+        assert_eq!(options.fov(), Some(90));
+        assert_eq!(
+            options.serialize(),
+            OPTIONS
+                .replace("fov:0.0", "fov:0.5")
+                .replace("fullscreen:false", "fullscreen:true")
+                .replace(
+                    "[\"vanilla\",\"file/Faithful 32x.zip\"]",
+                    "[\"vanilla\"]"
+                )
+                + "key_key.sprint:key.keyboard.left.control\n"
+        );
+    }
+
+    #[test]
+    fn shader_options_round_trip() {
+        let contents = "shaderPack=BSL_v8.2.09.zip\nantialiasingLevel=0\n";
+        let mut options = GameOptions::parse(contents, OptionsFile::Shaders);
+
+        assert_eq!(options.get("shaderPack"), Some("BSL_v8.2.09.zip"));
+        assert_eq!(options.serialize(), contents);
+
+        options.set("shaderPack", "OFF");
+        assert_eq!(
+            options.serialize(),
+            "shaderPack=OFF\nantialiasingLevel=0\n"
+        );
+    }
+}
@@ -0,0 +1,374 @@
+//! Theseus profile resource pack management: which packs the game uses, and
+//! in what order, as kept in options.txt
+use crate::event::emit::{emit_profile, emit_warning};
+use crate::event::ProfilePayloadType;
+use crate::profile::options::{GameOptions, OptionsFile};
+use crate::profile::{ensure_not_running, get_full_path};
+use crate::state::{FileManifest, ProjectType};
+use crate::util::io;
+use crate::util::manifest::parse_pack_mcmeta;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::PathBuf;
+
+/// Id of the pack of the game's own resources
+const VANILLA_PACK: &str = "vanilla";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ResourcePack {
+    /// Id of the pack in options.txt: `file/<file name>` for packs in
+    /// resourcepacks/ (the file name alone before 1.13), or the name of a
+    /// pack built into the game or a mod, such as `vanilla`
+    pub id: String,
+    /// File or folder name in resourcepacks/, for packs there
+    pub file_name: Option<String>,
+    pub enabled: bool,
+    /// Position among the enabled packs, from the bottom of the list (0).
+    /// Packs override the ones under them
+    pub position: Option<usize>,
+    pub pack_format: Option<u32>,
+    /// Set when the pack is made for other game versions than the profile's
+    pub warning: Option<String>,
+}
+
+// The profile's folder, game version and resource pack options
+struct ResourcePacks {
+    profile_dir: PathBuf,
+    game_version: String,
+    options: GameOptions,
+    /// Whether the game names packs by their file name alone, before 1.13
+    legacy_ids: bool,
+}
+
+impl ResourcePacks {
+    async fn read(profile_path: &str) -> crate::Result<Self> {
+        let profile =
+            crate::profile::get(profile_path).await?.ok_or_else(|| {
+                crate::ErrorKind::UnmanagedProfileError(
+                    profile_path.to_string(),
+                )
+            })?;
+        let profile_dir = get_full_path(profile_path).await?;
+        let options =
+            GameOptions::read(&profile_dir, OptionsFile::Vanilla).await?;
+
+        // Lists written by the game tell which ids it uses, otherwise the
+        // game version does
+        let legacy_ids = match options
+            .resource_packs()
+            .unwrap_or_default()
+            .iter()
+            .find(|x| *x != VANILLA_PACK)
+        {
+            Some(id) => !id.starts_with("file/"),
+            None => resource_pack_format(&profile.game_version)
+                .is_some_and(|x| x <= 3),
+        };
+
+        Ok(Self {
+            profile_dir,
+            game_version: profile.game_version,
+            options,
+            legacy_ids,
+        })
+    }
+
+    fn enabled(&self) -> Vec<String> {
+        self.options.resource_packs().unwrap_or_else(|| {
+            if self.legacy_ids {
+                Vec::new()
+            } else {
+                vec![VANILLA_PACK.to_string()]
+            }
+        })
+    }
+
+    fn id(&self, file_name: &str) -> String {
+        if self.legacy_ids {
+            file_name.to_string()
+        } else {
+            format!("file/{file_name}")
+        }
+    }
+
+    fn file_name<'a>(&self, id: &'a str) -> Option<&'a str> {
+        if self.legacy_ids {
+            Some(id).filter(|x| *x != VANILLA_PACK)
+        } else {
+            id.strip_prefix("file/")
+        }
+    }
+
+    // Names of the packs in resourcepacks/. Disabled files aren't seen by
+    // the game
+    fn file_names(&self) -> crate::Result<Vec<String>> {
+        let path = self
+            .profile_dir
+            .join(ProjectType::ResourcePack.get_folder());
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut file_names = Vec::new();
+        for entry in std::fs::read_dir(&path)
+            .map_err(|e| io::IOError::with_path(e, &path))?
+        {
+            let entry = entry.map_err(io::IOError::from)?.path();
+            let Some(file_name) = entry.file_name().and_then(|x| x.to_str())
+            else {
+                continue;
+            };
+
+            if entry.is_dir() || file_name.ends_with(".zip") {
+                file_names.push(file_name.to_string());
+            }
+        }
+        file_names.sort();
+
+        Ok(file_names)
+    }
+
+    // Why a pack in resourcepacks/ may not work with the profile's game
+    // version, if it may not
+    async fn warning(&self, file_name: &str) -> (Option<u32>, Option<String>) {
+        let manifest = read_pack_mcmeta(
+            self.profile_dir
+                .join(ProjectType::ResourcePack.get_folder())
+                .join(file_name),
+        )
+        .await;
+        let Some(manifest) = manifest else {
+            return (None, None);
+        };
+        let pack_format = manifest.pack_format;
+
+        let warning = resource_pack_format(&self.game_version).and_then(
+            |expected| {
+                let supported = manifest
+                    .supported_formats
+                    .is_some_and(|(min, max)| {
+                        (min..=max).contains(&expected)
+                    });
+                if supported || pack_format == Some(expected) {
+                    return None;
+                }
+
+                Some(match pack_format {
+                    Some(pack_format) => format!(
+                        "{file_name} is made for resource pack format {pack_format}, but Minecraft {} uses format {expected}",
+                        self.game_version
+                    ),
+                    None => format!(
+                        "{file_name} has no resource pack format, and may not work with Minecraft {}",
+                        self.game_version
+                    ),
+                })
+            },
+        );
+
+        (pack_format, warning)
+    }
+
+    async fn list(&self) -> crate::Result<Vec<ResourcePack>> {
+        let enabled = self.enabled();
+
+        let mut packs = Vec::new();
+        for (position, id) in enabled.iter().enumerate() {
+            let file_name = self.file_name(id);
+            let (pack_format, warning) = match file_name {
+                Some(file_name) => self.warning(file_name).await,
+                None => (None, None),
+            };
+
+            packs.push(ResourcePack {
+                id: id.clone(),
+                file_name: file_name.map(|x| x.to_string()),
+                enabled: true,
+                position: Some(position),
+                pack_format,
+                warning,
+            });
+        }
+
+        for file_name in self.file_names()? {
+            let id = self.id(&file_name);
+            if enabled.contains(&id) {
+                continue;
+            }
+
+            let (pack_format, warning) = self.warning(&file_name).await;
+            packs.push(ResourcePack {
+                id,
+                file_name: Some(file_name),
+                enabled: false,
+                position: None,
+                pack_format,
+                warning,
+            });
+        }
+
+        Ok(packs)
+    }
+
+    async fn write(&self, profile_path: &str) -> crate::Result<()> {
+        self.options.write(&self.profile_dir).await?;
+        emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+        Ok(())
+    }
+}
+
+// Reads the pack.mcmeta of a zipped or folder pack
+async fn read_pack_mcmeta(path: PathBuf) -> Option<FileManifest> {
+    let contents = if path.is_dir() {
+        io::read(path.join("pack.mcmeta")).await.ok()?
+    } else {
+        tokio::task::spawn_blocking(move || {
+            let file = std::fs::File::open(path).ok()?;
+            let mut archive = zip::ZipArchive::new(file).ok()?;
+            let mut entry = archive.by_name("pack.mcmeta").ok()?;
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents).ok()?;
+            Some(contents)
+        })
+        .await
+        .ok()??
+    };
+
+    Some(parse_pack_mcmeta(&serde_json::from_slice(&contents).ok()?))
+}
+
+// Resource pack format of a release of the game, since resource packs were
+// added in 1.6. Snapshots have their own formats, which aren't known here
+fn resource_pack_format(game_version: &str) -> Option<u32> {
+    let mut parts = game_version.strip_prefix("1.")?.split('.');
+    let minor: u32 = parts.next()?.parse().ok()?;
+    let patch: u32 = match parts.next() {
+        Some(patch) => patch.parse().ok()?,
+        None => 0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(match (minor, patch) {
+        (6..=8, _) => 1,
+        (9..=10, _) => 2,
+        (11..=12, _) => 3,
+        (13..=14, _) => 4,
+        (15, _) | (16, 0..=1) => 5,
+        (16, _) => 6,
+        (17, _) => 7,
+        (18, _) => 8,
+        (19, 0..=2) => 9,
+        (19, 3) => 12,
+        (19, _) => 13,
+        (20, 0..=1) => 15,
+        (20, 2) => 18,
+        (20, 3..=4) => 22,
+        (20, _) => 32,
+        (21, 0..=1) => 34,
+        _ => return None,
+    })
+}
+
+/// Lists a profile's resource packs: the enabled ones first, from the bottom
+/// of the game's list to the top, then the other packs in resourcepacks/
+#[tracing::instrument]
+pub async fn list(profile_path: &str) -> crate::Result<Vec<ResourcePack>> {
+    ResourcePacks::read(profile_path).await?.list().await
+}
+
+/// Enables or disables a resource pack by its id. Enabled packs are put at
+/// the top of the list, over the other packs. Packs made for other game
+/// versions are enabled with a warning, and marked as such so the game
+/// keeps them. The game's own resources can't be disabled
+/// Fails while the profile is running, as the game rewrites options.txt when
+/// it exits
+/// Returns the profile's resource packs after the change
+#[tracing::instrument]
+pub async fn set_enabled(
+    profile_path: &str,
+    id: &str,
+    enabled: bool,
+) -> crate::Result<Vec<ResourcePack>> {
+    ensure_not_running(profile_path, "change the resource packs").await?;
+    if !enabled && id == VANILLA_PACK {
+        return Err(crate::ErrorKind::InputError(
+            "The vanilla resource pack cannot be disabled".to_string(),
+        )
+        .into());
+    }
+
+    let mut packs = ResourcePacks::read(profile_path).await?;
+
+    let mut enabled_packs = packs.enabled();
+    let mut incompatible = packs
+        .options
+        .incompatible_resource_packs()
+        .unwrap_or_default();
+    enabled_packs.retain(|x| x != id);
+    incompatible.retain(|x| x != id);
+
+    if enabled {
+        let file_name = packs.file_name(id).ok_or_else(|| {
+            crate::ErrorKind::InputError(format!(
+                "{id} is not a pack in resourcepacks/"
+            ))
+        })?;
+        if !packs.file_names()?.iter().any(|x| x == file_name) {
+            return Err(crate::ErrorKind::InputError(format!(
+                "Resource pack {file_name} does not exist"
+            ))
+            .into());
+        }
+
+        if let (_, Some(warning)) = packs.warning(file_name).await {
+            emit_warning(&warning).await?;
+            if !packs.legacy_ids {
+                incompatible.push(id.to_string());
+            }
+        }
+        enabled_packs.push(id.to_string());
+    }
+
+    packs.options.set_resource_packs(&enabled_packs);
+    if !packs.legacy_ids {
+        packs.options.set_incompatible_resource_packs(&incompatible);
+    }
+    packs.write(profile_path).await?;
+
+    packs.list().await
+}
+
+/// Reorders a profile's enabled resource packs, given all of their ids from
+/// the bottom of the list to the top
+/// Fails while the profile is running, as the game rewrites options.txt when
+/// it exits
+/// Returns the profile's resource packs after the change
+#[tracing::instrument]
+pub async fn reorder(
+    profile_path: &str,
+    order: Vec<String>,
+) -> crate::Result<Vec<ResourcePack>> {
+    ensure_not_running(profile_path, "change the resource packs").await?;
+
+    let mut packs = ResourcePacks::read(profile_path).await?;
+
+    let mut enabled = packs.enabled();
+    let mut sorted_order = order.clone();
+    enabled.sort();
+    sorted_order.sort();
+    if enabled != sorted_order {
+        return Err(crate::ErrorKind::InputError(
+            "The new order must have every enabled resource pack exactly once"
+                .to_string(),
+        )
+        .into());
+    }
+
+    packs.options.set_resource_packs(&order);
+    packs.write(profile_path).await?;
+
+    packs.list().await
+}
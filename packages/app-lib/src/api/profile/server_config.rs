@@ -0,0 +1,386 @@
+//! Theseus dedicated server configuration (eula.txt and server.properties)
+//! management, for server profiles
+use crate::event::emit::emit_profile;
+use crate::event::ProfilePayloadType;
+use crate::profile::get_full_path;
+use crate::util::io;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const EULA_FILE_NAME: &str = "eula.txt";
+pub const PROPERTIES_FILE_NAME: &str = "server.properties";
+
+/// A Java properties file, keeping its comments and the order of its
+/// properties when written back
+#[derive(Default)]
+struct PropertiesFile {
+    lines: Vec<PropertiesLine>,
+}
+
+enum PropertiesLine {
+    Property { key: String, value: String },
+    // Comments and blank lines, kept as they are
+    Other(String),
+}
+
+impl PropertiesFile {
+    async fn read(path: &Path) -> crate::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Ok(Self::parse(&io::read_to_string(path).await?))
+    }
+
+    fn parse(contents: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut raw_lines = contents.lines();
+        while let Some(line) = raw_lines.next() {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty()
+                || trimmed.starts_with('#')
+                || trimmed.starts_with('!')
+            {
+                lines.push(PropertiesLine::Other(line.to_string()));
+                continue;
+            }
+
+            // Lines ending in an unescaped backslash continue on the next one
+            let mut logical = trimmed.to_string();
+            while logical.chars().rev().take_while(|x| *x == '\\').count() % 2
+                == 1
+            {
+                logical.pop();
+                match raw_lines.next() {
+                    Some(next) => logical.push_str(next.trim_start()),
+                    None => break,
+                }
+            }
+
+            let (key, value) = split_property(&logical);
+            lines.push(PropertiesLine::Property {
+                key: unescape(key),
+                value: unescape(value),
+            });
+        }
+
+        Self { lines }
+    }
+
+    fn properties(&self) -> HashMap<String, String> {
+        self.lines
+            .iter()
+            .filter_map(|x| match x {
+                PropertiesLine::Property { key, value } => {
+                    Some((key.clone(), value.clone()))
+                }
+                PropertiesLine::Other(_) => None,
+            })
+            .collect()
+    }
+
+    // Replaces a property's value in place, or adds it to the end
+    fn set(&mut self, key: &str, new_value: String) {
+        for line in &mut self.lines {
+            if let PropertiesLine::Property { key: k, value } = line {
+                if k == key {
+                    *value = new_value;
+                    return;
+                }
+            }
+        }
+
+        self.lines.push(PropertiesLine::Property {
+            key: key.to_string(),
+            value: new_value,
+        });
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.lines.retain(|x| {
+            !matches!(x, PropertiesLine::Property { key: k, .. } if k == key)
+        });
+    }
+
+    fn serialize(&self) -> String {
+        self.lines
+            .iter()
+            .map(|x| match x {
+                PropertiesLine::Property { key, value } => {
+                    format!("{}={}\n", escape(key, true), escape(value, false))
+                }
+                PropertiesLine::Other(line) => format!("{line}\n"),
+            })
+            .collect()
+    }
+}
+
+// Splits a property line at the first unescaped `=`, `:` or whitespace
+fn split_property(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+    let mut key_end = line.len();
+    for (index, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '=' || c == ':' || c.is_whitespace() {
+            key_end = index;
+            break;
+        }
+    }
+
+    let (key, rest) = line.split_at(key_end);
+    let rest = rest.trim_start();
+    let rest = rest
+        .strip_prefix('=')
+        .or_else(|| rest.strip_prefix(':'))
+        .unwrap_or(rest);
+
+    (key, rest.trim_start())
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('f') => result.push('\u{c}'),
+            Some('u') => {
+                let unit = unicode_unit(&mut chars);
+
+                // Characters outside of the BMP are written as surrogate pairs
+                if let Some(high @ 0xD800..=0xDBFF) = unit {
+                    let mut next = chars.clone();
+                    if next.next() == Some('\\') && next.next() == Some('u') {
+                        if let Some(low @ 0xDC00..=0xDFFF) =
+                            unicode_unit(&mut next)
+                        {
+                            chars = next;
+                            result.extend(
+                                char::decode_utf16([high, low]).flatten(),
+                            );
+                            continue;
+                        }
+                    }
+                }
+
+                if let Some(c) = unit.and_then(|x| char::from_u32(x.into())) {
+                    result.push(c);
+                }
+            }
+            Some(c) => result.push(c),
+            None => {}
+        }
+    }
+
+    result
+}
+
+// Reads the four hex digits of a `\u` escape
+fn unicode_unit(chars: &mut std::str::Chars) -> Option<u16> {
+    let code = chars.by_ref().take(4).collect::<String>();
+    u16::from_str_radix(&code, 16).ok()
+}
+
+// Escapes a key or value the way Java writes properties files, with
+// characters outside of ASCII as unicode escapes
+fn escape(value: &str, is_key: bool) -> String {
+    let mut result = String::with_capacity(value.len());
+    for (index, c) in value.chars().enumerate() {
+        match c {
+            ' ' if is_key || index == 0 => result.push_str("\\ "),
+            '\\' => result.push_str("\\\\"),
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\u{c}' => result.push_str("\\f"),
+            '=' | ':' | '#' | '!' => {
+                result.push('\\');
+                result.push(c);
+            }
+            c if (' '..='~').contains(&c) => result.push(c),
+            c => {
+                let mut buf = [0; 2];
+                for unit in c.encode_utf16(&mut buf) {
+                    result.push_str(&format!("\\u{unit:04X}"));
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Whether the Minecraft EULA is accepted in a server profile's eula.txt
+#[tracing::instrument]
+pub async fn get_eula(profile_path: &str) -> crate::Result<bool> {
+    let path = get_full_path(profile_path).await?.join(EULA_FILE_NAME);
+    let eula = PropertiesFile::read(&path).await?;
+
+    Ok(eula
+        .properties()
+        .get("eula")
+        .is_some_and(|x| x.eq_ignore_ascii_case("true")))
+}
+
+/// Accepts or declines the Minecraft EULA (https://aka.ms/MinecraftEULA) in
+/// a server profile's eula.txt. Servers only start once it is accepted
+#[tracing::instrument]
+pub async fn set_eula(profile_path: &str, accepted: bool) -> crate::Result<()> {
+    let path = get_full_path(profile_path).await?.join(EULA_FILE_NAME);
+
+    // The same file the server writes on its first start
+    io::write(
+        &path,
+        format!(
+            "#By changing the setting below to TRUE you are indicating your \
+             agreement to our EULA (https://aka.ms/MinecraftEULA).\n\
+             #{}\n\
+             eula={accepted}\n",
+            Utc::now().format("%a %b %d %H:%M:%S UTC %Y")
+        ),
+    )
+    .await?;
+
+    emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(())
+}
+
+/// Gets a server profile's server.properties. The server writes its defaults
+/// on its first start, so this is empty until then
+#[tracing::instrument]
+pub async fn get_properties(
+    profile_path: &str,
+) -> crate::Result<HashMap<String, String>> {
+    let path = get_full_path(profile_path)
+        .await?
+        .join(PROPERTIES_FILE_NAME);
+
+    Ok(PropertiesFile::read(&path).await?.properties())
+}
+
+/// Edits a server profile's server.properties, keeping its comments and the
+/// order of its properties. Properties set to `None` are removed, so the
+/// server uses their defaults
+/// Returns the properties after the edit
+#[tracing::instrument]
+pub async fn edit_properties(
+    profile_path: &str,
+    properties: HashMap<String, Option<String>>,
+) -> crate::Result<HashMap<String, String>> {
+    let path = get_full_path(profile_path)
+        .await?
+        .join(PROPERTIES_FILE_NAME);
+    let mut file = PropertiesFile::read(&path).await?;
+
+    for (key, value) in properties {
+        match value {
+            Some(value) => file.set(&key, value),
+            None => file.remove(&key),
+        }
+    }
+
+    io::write(&path, file.serialize()).await?;
+    emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(file.properties())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERVER_PROPERTIES: &str = "\
+#Minecraft server properties
+#Sat Aug 31 12:00:00 UTC 2024
+accepts-transfers=false
+allow-flight=false
+difficulty=easy
+enable-rcon=false
+gamemode=survival
+level-name=world
+level-seed=
+max-players=20
+motd=\\u00A7aA Minecraft Server \\u00E9t\\u00E9 \\uD83D\\uDE00
+resource-pack=https\\://example.com/pack.zip?a\\=b
+server-port=25565
+";
+
+    #[test]
+    fn properties_round_trip() {
+        let file = PropertiesFile::parse(SERVER_PROPERTIES);
+        let properties = file.properties();
+
+        assert_eq!(properties["difficulty"], "easy");
+        assert_eq!(properties["level-seed"], "");
+        assert_eq!(properties["motd"], "\u{a7}aA Minecraft Server été 😀");
+        assert_eq!(
+            properties["resource-pack"],
+            "https://example.com/pack.zip?a=b"
+        );
+        assert_eq!(file.serialize(), SERVER_PROPERTIES);
+    }
+
+    #[test]
+    fn properties_edit_in_place() {
+        let mut file = PropertiesFile::parse(SERVER_PROPERTIES);
+        file.set("difficulty", "hard".to_string());
+        file.set("white-list", "true".to_string());
+        file.remove("level-seed");
+
+        assert_eq!(
+            file.serialize(),
+            SERVER_PROPERTIES
+                .replace("difficulty=easy", "difficulty=hard")
+                .replace("level-seed=\n", "")
+                + "white-list=true\n"
+        );
+    }
+
+    #[test]
+    fn properties_continuation_lines() {
+        let file = PropertiesFile::parse(
+            "motd=Welcome to \\\n    the server\n\
+             key\\ with\\ spaces : value\n\
+             trailing=ends with a backslash\\\\\n\
+             next=value\n",
+        );
+        let properties = file.properties();
+
+        assert_eq!(properties["motd"], "Welcome to the server");
+        assert_eq!(properties["key with spaces"], "value");
+        assert_eq!(properties["trailing"], "ends with a backslash\\");
+        assert_eq!(properties["next"], "value");
+        assert_eq!(
+            file.serialize(),
+            "motd=Welcome to the server\n\
+             key\\ with\\ spaces=value\n\
+             trailing=ends with a backslash\\\\\n\
+             next=value\n"
+        );
+    }
+
+    #[test]
+    fn properties_escape() {
+        assert_eq!(escape("a b", true), "a\\ b");
+        assert_eq!(escape(" a b", false), "\\ a b");
+        assert_eq!(escape("a=b:c#d!e", false), "a\\=b\\:c\\#d\\!e");
+        assert_eq!(escape("tab\there\n", false), "tab\\there\\n");
+        assert_eq!(escape("\u{a7}😀", false), "\\u00A7\\uD83D\\uDE00");
+
+        let value = " \\ é😀\t=:";
+        assert_eq!(unescape(&escape(value, false)), value);
+        assert_eq!(unescape(&escape(value, true)), value);
+    }
+}
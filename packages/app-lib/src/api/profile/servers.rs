@@ -0,0 +1,281 @@
+//! Theseus profile multiplayer server list (servers.dat) management
+use crate::event::emit::emit_profile;
+use crate::event::ProfilePayloadType;
+use crate::profile::get_full_path;
+use crate::util::io;
+use crate::util::nbt::{self, NbtCompound, NbtTag};
+use serde::{Deserialize, Serialize};
+
+pub const SERVERS_FILE_NAME: &str = "servers.dat";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Server {
+    pub name: String,
+    pub address: String,
+    /// Base64 encoded PNG, as cached by the game
+    pub icon: Option<String>,
+    pub accept_textures: ResourcePackPolicy,
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+/// Whether the game should use a server's resource pack
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourcePackPolicy {
+    Enabled,
+    Disabled,
+    Prompt,
+}
+
+impl Server {
+    fn from_nbt(tag: &NbtCompound) -> Self {
+        Self {
+            name: tag.get_str("name").unwrap_or_default().to_string(),
+            address: tag.get_str("ip").unwrap_or_default().to_string(),
+            icon: tag.get_str("icon").map(|x| x.to_string()),
+            accept_textures: match tag.get_i64("acceptTextures") {
+                Some(1) => ResourcePackPolicy::Enabled,
+                Some(_) => ResourcePackPolicy::Disabled,
+                None => ResourcePackPolicy::Prompt,
+            },
+            hidden: tag.get_i64("hidden").unwrap_or(0) == 1,
+        }
+    }
+
+    // Writes this server into an entry, keeping any keys we don't know about
+    fn write_nbt(&self, tag: &mut NbtCompound) {
+        tag.insert("name", NbtTag::String(self.name.clone()));
+        tag.insert("ip", NbtTag::String(self.address.clone()));
+
+        if let Some(icon) = &self.icon {
+            tag.insert("icon", NbtTag::String(icon.clone()));
+        } else {
+            tag.remove("icon");
+        }
+
+        match self.accept_textures {
+            ResourcePackPolicy::Enabled => {
+                tag.insert("acceptTextures", NbtTag::Byte(1))
+            }
+            ResourcePackPolicy::Disabled => {
+                tag.insert("acceptTextures", NbtTag::Byte(0))
+            }
+            ResourcePackPolicy::Prompt => {
+                tag.remove("acceptTextures");
+            }
+        }
+
+        if self.hidden {
+            tag.insert("hidden", NbtTag::Byte(1));
+        } else {
+            tag.remove("hidden");
+        }
+    }
+}
+
+// The parsed servers.dat of a profile, keeping the raw entries around
+struct ServerList {
+    root_name: String,
+    root: NbtCompound,
+    entries: Vec<NbtCompound>,
+}
+
+impl ServerList {
+    async fn read(profile_path: &str) -> crate::Result<Self> {
+        let path = get_full_path(profile_path).await?.join(SERVERS_FILE_NAME);
+
+        if !path.exists() {
+            return Ok(Self {
+                root_name: String::new(),
+                root: NbtCompound::new(),
+                entries: Vec::new(),
+            });
+        }
+
+        let (root_name, root) = nbt::from_bytes(&io::read(&path).await?)?;
+        let entries = root
+            .get("servers")
+            .and_then(|x| x.as_list())
+            .map(|x| {
+                x.iter().filter_map(|x| x.as_compound().cloned()).collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            root_name,
+            root,
+            entries,
+        })
+    }
+
+    async fn write(mut self, profile_path: &str) -> crate::Result<()> {
+        let path = get_full_path(profile_path).await?.join(SERVERS_FILE_NAME);
+
+        self.root.insert(
+            "servers",
+            NbtTag::List(
+                self.entries.into_iter().map(NbtTag::Compound).collect(),
+            ),
+        );
+
+        // The game never compresses servers.dat
+        io::write(&path, nbt::to_bytes(&self.root_name, &self.root, false)?)
+            .await?;
+
+        emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+        Ok(())
+    }
+
+    fn servers(&self) -> Vec<Server> {
+        self.entries.iter().map(Server::from_nbt).collect()
+    }
+
+    fn entry_mut(&mut self, index: usize) -> crate::Result<&mut NbtCompound> {
+        let len = self.entries.len();
+        self.entries.get_mut(index).ok_or_else(|| {
+            crate::ErrorKind::InputError(format!(
+                "Server index {index} is out of bounds for a list of {len}"
+            ))
+            .as_error()
+        })
+    }
+}
+
+/// Lists a profile's multiplayer servers, in the order the game shows them
+#[tracing::instrument]
+pub async fn list(profile_path: &str) -> crate::Result<Vec<Server>> {
+    Ok(ServerList::read(profile_path).await?.servers())
+}
+
+/// Adds a server to a profile, at the given index or at the end of the list
+#[tracing::instrument]
+pub async fn add(
+    profile_path: &str,
+    server: Server,
+    index: Option<usize>,
+) -> crate::Result<Vec<Server>> {
+    let mut list = ServerList::read(profile_path).await?;
+
+    let mut entry = NbtCompound::new();
+    server.write_nbt(&mut entry);
+
+    let index = index.unwrap_or(list.entries.len()).min(list.entries.len());
+    list.entries.insert(index, entry);
+
+    let servers = list.servers();
+    list.write(profile_path).await?;
+
+    Ok(servers)
+}
+
+/// Replaces a server's details
+#[tracing::instrument]
+pub async fn edit(
+    profile_path: &str,
+    index: usize,
+    server: Server,
+) -> crate::Result<Vec<Server>> {
+    let mut list = ServerList::read(profile_path).await?;
+    server.write_nbt(list.entry_mut(index)?);
+
+    let servers = list.servers();
+    list.write(profile_path).await?;
+
+    Ok(servers)
+}
+
+/// Removes a server from a profile
+#[tracing::instrument]
+pub async fn remove(
+    profile_path: &str,
+    index: usize,
+) -> crate::Result<Vec<Server>> {
+    let mut list = ServerList::read(profile_path).await?;
+    list.entry_mut(index)?;
+    list.entries.remove(index);
+
+    let servers = list.servers();
+    list.write(profile_path).await?;
+
+    Ok(servers)
+}
+
+/// Moves a server to a new position in the list
+#[tracing::instrument]
+pub async fn reorder(
+    profile_path: &str,
+    from: usize,
+    to: usize,
+) -> crate::Result<Vec<Server>> {
+    let mut list = ServerList::read(profile_path).await?;
+    list.entry_mut(from)?;
+
+    let entry = list.entries.remove(from);
+    let to = to.min(list.entries.len());
+    list.entries.insert(to, entry);
+
+    let servers = list.servers();
+    list.write(profile_path).await?;
+
+    Ok(servers)
+}
+
+/// Sets whether the game should use a server's resource pack
+#[tracing::instrument]
+pub async fn set_accept_textures(
+    profile_path: &str,
+    index: usize,
+    policy: ResourcePackPolicy,
+) -> crate::Result<Vec<Server>> {
+    let mut list = ServerList::read(profile_path).await?;
+
+    let entry = list.entry_mut(index)?;
+    let mut server = Server::from_nbt(entry);
+    server.accept_textures = policy;
+    server.write_nbt(entry);
+
+    let servers = list.servers();
+    list.write(profile_path).await?;
+
+    Ok(servers)
+}
+
+/// Imports a list of servers into several profiles at once
+/// Servers whose address is already in a profile's list are updated in place,
+/// the rest are added to the end. If `replace` is set, each profile's list is
+/// replaced by the given servers instead.
+#[tracing::instrument]
+pub async fn import(
+    profile_paths: &[&str],
+    servers: Vec<Server>,
+    replace: bool,
+) -> crate::Result<()> {
+    for profile_path in profile_paths {
+        let mut list = ServerList::read(profile_path).await?;
+
+        if replace {
+            list.entries.clear();
+        }
+
+        for server in &servers {
+            let existing = list
+                .entries
+                .iter_mut()
+                .find(|x| x.get_str("ip") == Some(server.address.as_str()));
+
+            if let Some(entry) = existing {
+                server.write_nbt(entry);
+            } else {
+                let mut entry = NbtCompound::new();
+                server.write_nbt(&mut entry);
+                list.entries.push(entry);
+            }
+        }
+
+        list.write(profile_path).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,381 @@
+//! Theseus profile snapshots: point-in-time backups of a whole profile
+use crate::event::emit::{emit_loading, emit_profile, init_loading};
+use crate::event::{LoadingBarId, LoadingBarType, ProfilePayloadType};
+use crate::profile::{
+    add_all_recursive_folder_paths, get, get_full_path, pack_get_relative_path,
+};
+use crate::state::{ContentRef, Process, Profile, ProfileSnapshot};
+use crate::util::fetch::sha1_async;
+use crate::util::io::{self, IOError};
+use crate::State;
+use async_zip::tokio::read::fs::ZipFileReader;
+use async_zip::tokio::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use chrono::Utc;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs::File;
+
+/// Folders that are not part of a profile's state, and are left out of snapshots
+const EXCLUDED_FOLDERS: [&str; 2] = ["logs", "crash-reports"];
+
+/// Lists all snapshots of a profile, newest first
+#[tracing::instrument]
+pub async fn list(profile_path: &str) -> crate::Result<Vec<ProfileSnapshot>> {
+    let state = State::get().await?;
+    let snapshots =
+        ProfileSnapshot::get_from_profile(profile_path, &state.pool).await?;
+    Ok(snapshots)
+}
+
+/// Get a snapshot by its id
+#[tracing::instrument]
+pub async fn get_snapshot(id: &str) -> crate::Result<Option<ProfileSnapshot>> {
+    let state = State::get().await?;
+    let snapshot = ProfileSnapshot::get(id, &state.pool).await?;
+    Ok(snapshot)
+}
+
+/// Snapshots a profile's folder (mods, config, saves, options.txt, etc.) and its
+/// database row into a compressed archive in the snapshots directory
+#[tracing::instrument]
+pub async fn create(
+    profile_path: &str,
+    name: Option<String>,
+) -> crate::Result<ProfileSnapshot> {
+    let state = State::get().await?;
+    let profile = get(profile_path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(profile_path.to_string())
+    })?;
+
+    let projects = profile
+        .get_projects(None, &state.pool, &state.api_semaphore)
+        .await?
+        .into_iter()
+        .collect();
+
+    let mut snapshot = ProfileSnapshot {
+        id: uuid::Uuid::new_v4().to_string(),
+        profile_path: profile.path.clone(),
+        name,
+        created: Utc::now(),
+        game_version: profile.game_version.clone(),
+        loader: profile.loader,
+        loader_version: profile.loader_version.clone(),
+        profile: profile.clone(),
+        projects,
+        size: 0,
+    };
+
+    let profile_base_path = get_full_path(profile_path).await?;
+    let mut path_list = Vec::new();
+    add_all_recursive_folder_paths(&profile_base_path, &mut path_list).await?;
+
+    let archive_path = snapshot.archive_path(&state.directories);
+    if let Some(parent) = archive_path.parent() {
+        io::create_dir_all(parent).await?;
+    }
+
+    let loading_bar = init_loading(
+        LoadingBarType::ZipExtract {
+            profile_path: profile.path.clone(),
+            profile_name: profile.name.clone(),
+        },
+        path_list.len() as f64,
+        "Creating profile snapshot",
+    )
+    .await?;
+
+    let result = async {
+        let _permit = state.io_semaphore.0.acquire().await?;
+
+        let mut file = File::create(&archive_path)
+            .await
+            .map_err(|e| IOError::with_path(e, &archive_path))?;
+        let mut writer = ZipFileWriter::with_tokio(&mut file);
+
+        for path in path_list {
+            emit_loading(&loading_bar, 1.0, None).await?;
+
+            let relative_path =
+                pack_get_relative_path(&profile_base_path, &path)?;
+            if EXCLUDED_FOLDERS
+                .iter()
+                .any(|x| relative_path.starts_with(&format!("{x}/")))
+            {
+                continue;
+            }
+
+            let data = io::read(&path).await?;
+            let builder = ZipEntryBuilder::new(
+                relative_path.into(),
+                Compression::Deflate,
+            );
+            writer.write_entry_whole(builder, &data).await?;
+        }
+
+        writer.close().await?;
+
+        Ok::<(), crate::Error>(())
+    }
+    .await;
+
+    if let Err(err) = result {
+        let _ = io::remove_file(&archive_path).await;
+        return Err(err);
+    }
+
+    snapshot.size = tokio::fs::metadata(&archive_path)
+        .await
+        .map_err(|e| IOError::with_path(e, &archive_path))?
+        .len();
+    snapshot.upsert(&state.pool).await?;
+
+    Ok(snapshot)
+}
+
+/// Restores a profile to the state of a snapshot
+/// The snapshot is extracted next to the profile first, and only swapped in once
+/// it is complete, so a failed restore leaves the profile untouched
+#[tracing::instrument]
+pub async fn restore(id: &str) -> crate::Result<()> {
+    let state = State::get().await?;
+    let snapshot =
+        ProfileSnapshot::get(id, &state.pool)
+            .await?
+            .ok_or_else(|| {
+                crate::ErrorKind::InputError(format!("Unknown snapshot {id}"))
+            })?;
+    let profile_path = snapshot.profile_path.clone();
+
+    let current = get(&profile_path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(profile_path.clone())
+    })?;
+
+    if !Process::get_from_profile(&profile_path, &state.pool)
+        .await?
+        .is_empty()
+    {
+        return Err(crate::ErrorKind::LauncherError(format!(
+            "Cannot restore a snapshot of {} while it is running",
+            current.name
+        ))
+        .as_error());
+    }
+
+    let profile_dir = state.directories.profiles_dir().join(&profile_path);
+    let snapshots_dir = state.directories.snapshots_dir().join(&profile_path);
+    let staging_dir = snapshots_dir.join(format!("{id}.restore"));
+    let backup_dir = snapshots_dir.join(format!("{id}.old"));
+
+    for dir in [&staging_dir, &backup_dir] {
+        if dir.exists() {
+            io::remove_dir_all(dir).await?;
+        }
+    }
+
+    let loading_bar = init_loading(
+        LoadingBarType::ZipExtract {
+            profile_path: profile_path.clone(),
+            profile_name: current.name.clone(),
+        },
+        100.0,
+        "Restoring profile snapshot",
+    )
+    .await?;
+
+    if let Err(err) = extract_snapshot(
+        &snapshot.archive_path(&state.directories),
+        &staging_dir,
+        &loading_bar,
+    )
+    .await
+    {
+        let _ = io::remove_dir_all(&staging_dir).await;
+        return Err(err);
+    }
+
+    // Swap the extracted snapshot in place of the current profile folder
+    if profile_dir.exists() {
+        io::rename(&profile_dir, &backup_dir).await?;
+    }
+    if let Err(err) = io::rename(&staging_dir, &profile_dir).await {
+        if backup_dir.exists() {
+            io::rename(&backup_dir, &profile_dir).await?;
+        }
+        let _ = io::remove_dir_all(&staging_dir).await;
+        return Err(err.into());
+    }
+
+    // Playtime is not part of the profile's state, so it is kept as-is
+    let mut profile = snapshot.profile.clone();
+    profile.path = current.path.clone();
+    profile.last_played = current.last_played;
+    profile.submitted_time_played = current.submitted_time_played;
+    profile.recent_time_played = current.recent_time_played;
+    profile.modified = Utc::now();
+
+    if let Err(err) = profile.upsert(&state.pool).await {
+        io::remove_dir_all(&profile_dir).await?;
+        if backup_dir.exists() {
+            io::rename(&backup_dir, &profile_dir).await?;
+        }
+        return Err(err);
+    }
+
+    if backup_dir.exists() {
+        // Logs and crash reports are not snapshotted, so carry them over
+        for folder in EXCLUDED_FOLDERS {
+            let old = backup_dir.join(folder);
+            if old.exists() {
+                let new = profile_dir.join(folder);
+                if new.exists() {
+                    io::remove_dir_all(&new).await?;
+                }
+                io::rename(&old, &new).await?;
+            }
+        }
+
+        io::remove_dir_all(&backup_dir).await?;
+    }
+
+    relink_content(&profile_path, &profile_dir).await?;
+
+    crate::state::fs_watcher::watch_profile(
+        &profile_path,
+        &state.file_watcher,
+        &state.directories,
+    )
+    .await?;
+
+    emit_profile(&profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(())
+}
+
+/// Removes a snapshot and its archive
+#[tracing::instrument]
+pub async fn remove(id: &str) -> crate::Result<()> {
+    let state = State::get().await?;
+
+    if let Some(snapshot) = ProfileSnapshot::get(id, &state.pool).await? {
+        ProfileSnapshot::remove(id, &state.pool).await?;
+
+        let archive_path = snapshot.archive_path(&state.directories);
+        if archive_path.exists() {
+            io::remove_file(&archive_path).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes all but the `keep` newest snapshots of a profile
+/// Returns the ids of the removed snapshots
+#[tracing::instrument]
+pub async fn prune(
+    profile_path: &str,
+    keep: usize,
+) -> crate::Result<Vec<String>> {
+    let mut removed = Vec::new();
+
+    for snapshot in list(profile_path).await?.into_iter().skip(keep) {
+        remove(&snapshot.id).await?;
+        removed.push(snapshot.id);
+    }
+
+    Ok(removed)
+}
+
+// Points a restored profile's content store references at its new files. The
+// replaced files' references are dropped, and restored files that are in the
+// store are referenced again
+async fn relink_content(
+    profile_path: &str,
+    profile_dir: &Path,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let hashes =
+        ContentRef::remove_from_profile(profile_path, &state.pool).await?;
+
+    for (project_type, world) in Profile::project_folders(profile_dir)? {
+        let folder = project_type.get_world_folder(world.as_deref());
+        let dir = profile_dir.join(&folder);
+        if !dir.exists() {
+            continue;
+        }
+
+        for entry in
+            std::fs::read_dir(&dir).map_err(|e| IOError::with_path(e, &dir))?
+        {
+            let path = entry.map_err(IOError::from)?.path();
+            let Some(file_name) = path.file_name().and_then(|x| x.to_str())
+            else {
+                continue;
+            };
+            if !path.is_file() {
+                continue;
+            }
+
+            let hash =
+                sha1_async(bytes::Bytes::from(io::read(&path).await?)).await?;
+            if ContentRef::object_path(&state.directories, &hash).exists() {
+                ContentRef::adopt(
+                    profile_path,
+                    &format!("{folder}/{file_name}"),
+                    &hash,
+                    &state.pool,
+                )
+                .await?;
+            }
+        }
+    }
+
+    ContentRef::collect_garbage(&hashes, &state.directories, &state.pool)
+        .await?;
+
+    Ok(())
+}
+
+async fn extract_snapshot(
+    archive_path: &Path,
+    dest: &Path,
+    loading_bar: &LoadingBarId,
+) -> crate::Result<()> {
+    let zip_reader = ZipFileReader::new(archive_path).await?;
+
+    io::create_dir_all(dest).await?;
+
+    let total = zip_reader.file().entries().len();
+    for index in 0..total {
+        let file = zip_reader.file().entries().get(index).ok_or_else(|| {
+            crate::ErrorKind::InputError(format!(
+                "Snapshot archive {} has no entry {index}",
+                archive_path.display()
+            ))
+        })?;
+        let filename = file.filename().as_str().unwrap_or_default();
+
+        let relative_path = PathBuf::from(filename);
+        if filename.ends_with('/')
+            || relative_path
+                .components()
+                .any(|x| !matches!(x, Component::Normal(_)))
+        {
+            continue;
+        }
+
+        let mut content = Vec::new();
+        let mut reader = zip_reader.reader_with_entry(index).await?;
+        reader.read_to_end_checked(&mut content).await?;
+
+        let path = dest.join(relative_path);
+        if let Some(parent) = path.parent() {
+            io::create_dir_all(parent).await?;
+        }
+        io::write(&path, &content).await?;
+
+        emit_loading(loading_bar, 100.0 / total as f64, None).await?;
+    }
+
+    Ok(())
+}
@@ -76,7 +76,13 @@ pub async fn repair_managed_modrinth(profile_path: &str) -> crate::Result<()> {
         .await?;
 
     for (file, _) in projects_map {
-        crate::state::Profile::remove_project(&profile.path, &file).await?;
+        crate::state::Profile::remove_project(
+            &profile.path,
+            &file,
+            &state.directories,
+            &state.pool,
+        )
+        .await?;
     }
 
     // Extract modrinth pack information, if appropriate
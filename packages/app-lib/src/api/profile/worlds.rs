@@ -0,0 +1,345 @@
+//! Theseus profile world (saves/) management
+use crate::event::emit::emit_profile;
+use crate::event::ProfilePayloadType;
+use crate::pack::import::get_all_subfiles;
+use crate::profile::{get, get_full_path, pack_get_relative_path};
+use crate::state::Process;
+use crate::util::fetch;
+use crate::util::io::{self, IOError};
+use crate::util::nbt::{self, NbtTag};
+use crate::State;
+use async_zip::tokio::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use tokio::fs::File;
+
+pub const SAVES_FOLDER_NAME: &str = "saves";
+pub const BACKUPS_FOLDER_NAME: &str = "backups";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct World {
+    /// Name of the world's folder in saves/, used to refer to the world
+    pub folder: String,
+    pub name: String,
+    pub game_mode: GameMode,
+    pub hardcore: bool,
+    pub last_played: Option<DateTime<Utc>>,
+    /// Name of the Minecraft version the world was last played in
+    pub version: Option<String>,
+    pub seed: Option<i64>,
+    /// Size of the world's folder on disk, in bytes
+    pub size: u64,
+    pub icon: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+    Unknown,
+}
+
+impl GameMode {
+    pub fn from_id(id: i64) -> Self {
+        match id {
+            0 => Self::Survival,
+            1 => Self::Creative,
+            2 => Self::Adventure,
+            3 => Self::Spectator,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Lists every world in a profile's saves/ folder, most recently played first
+#[tracing::instrument]
+pub async fn list(profile_path: &str) -> crate::Result<Vec<World>> {
+    let saves_dir = get_full_path(profile_path).await?.join(SAVES_FOLDER_NAME);
+    if !saves_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut worlds = Vec::new();
+    let mut read_dir = io::read_dir(&saves_dir).await?;
+    while let Some(entry) = read_dir
+        .next_entry()
+        .await
+        .map_err(|e| IOError::with_path(e, &saves_dir))?
+    {
+        let path = entry.path();
+        if !path.join("level.dat").exists() {
+            continue;
+        }
+
+        match read_world(&path).await {
+            Ok(world) => worlds.push(world),
+            Err(err) => {
+                tracing::warn!("Unable to read world at {:?}: {}", path, err)
+            }
+        }
+    }
+
+    worlds.sort_by_key(|x| std::cmp::Reverse(x.last_played));
+
+    Ok(worlds)
+}
+
+/// Get a single world of a profile by its folder name
+#[tracing::instrument]
+pub async fn get_world(
+    profile_path: &str,
+    world: &str,
+) -> crate::Result<World> {
+    let path = get_world_path(profile_path, world).await?;
+    read_world(&path).await
+}
+
+/// Backs up a world into the profile's backups/ folder, the same place the game
+/// puts its own world backups
+/// Returns the path to the created backup
+#[tracing::instrument]
+pub async fn backup(profile_path: &str, world: &str) -> crate::Result<PathBuf> {
+    let state = State::get().await?;
+    let world_path = get_world_path(profile_path, world).await?;
+
+    let backups_dir =
+        get_full_path(profile_path).await?.join(BACKUPS_FOLDER_NAME);
+    io::create_dir_all(&backups_dir).await?;
+
+    let backup_path = backups_dir.join(format!(
+        "{}_{}.zip",
+        Utc::now().format("%Y-%m-%d_%H-%M-%S"),
+        world
+    ));
+
+    let _permit = state.io_semaphore.0.acquire().await?;
+
+    let result = async {
+        let mut file = File::create(&backup_path)
+            .await
+            .map_err(|e| IOError::with_path(e, &backup_path))?;
+        let mut writer = ZipFileWriter::with_tokio(&mut file);
+
+        let saves_dir = world_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        for path in get_all_subfiles(&world_path).await? {
+            if path.ends_with("session.lock") {
+                continue;
+            }
+
+            let relative_path = pack_get_relative_path(&saves_dir, &path)?;
+            let data = io::read(&path).await?;
+            let builder = ZipEntryBuilder::new(
+                relative_path.into(),
+                Compression::Deflate,
+            );
+            writer.write_entry_whole(builder, &data).await?;
+        }
+
+        writer.close().await?;
+
+        Ok::<(), crate::Error>(())
+    }
+    .await;
+
+    if let Err(err) = result {
+        let _ = io::remove_file(&backup_path).await;
+        return Err(err);
+    }
+
+    Ok(backup_path)
+}
+
+/// Deletes a world from a profile
+#[tracing::instrument]
+pub async fn delete(profile_path: &str, world: &str) -> crate::Result<()> {
+    ensure_not_running(profile_path).await?;
+
+    let path = get_world_path(profile_path, world).await?;
+    io::remove_dir_all(&path).await?;
+
+    emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(())
+}
+
+/// Renames a world, as shown in the game's world list
+/// The world's folder is left as-is
+#[tracing::instrument]
+pub async fn rename(
+    profile_path: &str,
+    world: &str,
+    new_name: &str,
+) -> crate::Result<()> {
+    ensure_not_running(profile_path).await?;
+
+    let level_dat =
+        get_world_path(profile_path, world).await?.join("level.dat");
+    let (root_name, mut root) = nbt::from_bytes(&io::read(&level_dat).await?)?;
+
+    let data = root.get_compound_mut("Data").ok_or_else(|| {
+        crate::ErrorKind::NBTError(format!(
+            "{} has no Data compound",
+            level_dat.display()
+        ))
+    })?;
+    data.insert("LevelName", NbtTag::String(new_name.to_string()));
+
+    io::write(&level_dat, nbt::to_bytes(&root_name, &root, true)?).await?;
+
+    emit_profile(profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(())
+}
+
+/// Copies a world from one profile into another
+/// Returns the folder name of the copy in the destination profile
+#[tracing::instrument]
+pub async fn copy(
+    from_profile_path: &str,
+    world: &str,
+    to_profile_path: &str,
+) -> crate::Result<String> {
+    let state = State::get().await?;
+    let world_path = get_world_path(from_profile_path, world).await?;
+
+    if get(to_profile_path).await?.is_none() {
+        return Err(crate::ErrorKind::UnmanagedProfileError(
+            to_profile_path.to_string(),
+        )
+        .as_error());
+    }
+
+    let saves_dir = get_full_path(to_profile_path)
+        .await?
+        .join(SAVES_FOLDER_NAME);
+
+    // Never overwrite an existing world
+    let mut folder = world.to_string();
+    let mut i = 1;
+    while saves_dir.join(&folder).exists() {
+        folder = format!("{world} ({i})");
+        i += 1;
+    }
+    let dest = saves_dir.join(&folder);
+
+    for path in get_all_subfiles(&world_path).await? {
+        if path.ends_with("session.lock") {
+            continue;
+        }
+
+        let relative_path = path.strip_prefix(&world_path)?;
+        fetch::copy(&path, dest.join(relative_path), &state.io_semaphore)
+            .await?;
+    }
+
+    emit_profile(to_profile_path, ProfilePayloadType::Edited).await?;
+
+    Ok(folder)
+}
+
+pub(crate) async fn get_world_path(
+    profile_path: &str,
+    world: &str,
+) -> crate::Result<PathBuf> {
+    let mut components = Path::new(world).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Invalid world name: {world}"
+        ))
+        .as_error());
+    }
+
+    let path = get_full_path(profile_path)
+        .await?
+        .join(SAVES_FOLDER_NAME)
+        .join(world);
+
+    if !path.join("level.dat").exists() {
+        return Err(crate::ErrorKind::InputError(format!(
+            "World {world} does not exist in profile {profile_path}"
+        ))
+        .as_error());
+    }
+
+    Ok(path)
+}
+
+async fn ensure_not_running(profile_path: &str) -> crate::Result<()> {
+    let state = State::get().await?;
+
+    if !Process::get_from_profile(profile_path, &state.pool)
+        .await?
+        .is_empty()
+    {
+        return Err(crate::ErrorKind::LauncherError(format!(
+            "Cannot modify worlds of {profile_path} while it is running"
+        ))
+        .as_error());
+    }
+
+    Ok(())
+}
+
+async fn read_world(path: &Path) -> crate::Result<World> {
+    let level_dat = path.join("level.dat");
+    let (_, root) = nbt::from_bytes(&io::read(&level_dat).await?)?;
+
+    let data = root.get_compound("Data").ok_or_else(|| {
+        crate::ErrorKind::NBTError(format!(
+            "{} has no Data compound",
+            level_dat.display()
+        ))
+    })?;
+
+    let folder = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    // 1.16+ store the seed in WorldGenSettings, older versions in RandomSeed
+    let seed = data
+        .get_compound("WorldGenSettings")
+        .and_then(|x| x.get_i64("seed"))
+        .or_else(|| data.get_i64("RandomSeed"));
+
+    let mut size = 0;
+    for file in get_all_subfiles(path).await? {
+        if let Ok(metadata) = tokio::fs::metadata(&file).await {
+            size += metadata.len();
+        }
+    }
+
+    let icon = path.join("icon.png");
+
+    Ok(World {
+        name: data
+            .get_str("LevelName")
+            .map(|x| x.to_string())
+            .unwrap_or_else(|| folder.clone()),
+        folder,
+        game_mode: GameMode::from_id(data.get_i64("GameType").unwrap_or(0)),
+        hardcore: data.get_i64("hardcore").unwrap_or(0) == 1,
+        last_played: data
+            .get_i64("LastPlayed")
+            .and_then(|x| Utc.timestamp_millis_opt(x).single()),
+        version: data
+            .get_compound("Version")
+            .and_then(|x| x.get_str("Name"))
+            .map(|x| x.to_string()),
+        seed,
+        size,
+        icon: if icon.exists() { Some(icon) } else { None },
+    })
+}
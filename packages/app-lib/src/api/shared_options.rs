@@ -0,0 +1,132 @@
+//! Theseus shared game options: options.txt options, such as keybinds, that
+//! the profiles syncing options have in common
+//! They are set in a profile's options.txt when it launches, and taken back
+//! from it when the game exits
+use crate::profile::get_full_path;
+use crate::profile::options::{GameOptions, OptionsFile};
+use crate::state::{Settings, SharedOption};
+use crate::State;
+use chrono::Utc;
+use std::collections::HashMap;
+
+/// Lists the shared options, whether or not their category is shared
+#[tracing::instrument]
+pub async fn list() -> crate::Result<Vec<SharedOption>> {
+    let state = State::get().await?;
+    let options = SharedOption::get_all(&state.pool).await?;
+    Ok(options)
+}
+
+/// Edits shared options, by their options.txt key. Options set to `None` are
+/// removed, so profiles keep their own value
+/// Returns the shared options after the edit
+#[tracing::instrument]
+pub async fn edit(
+    options: HashMap<String, Option<String>>,
+) -> crate::Result<Vec<SharedOption>> {
+    let state = State::get().await?;
+
+    let mut transaction = state.pool.begin().await?;
+    for (key, value) in options {
+        match value {
+            Some(value) => {
+                SharedOption {
+                    key,
+                    value,
+                    modified: Utc::now(),
+                }
+                .upsert(&mut *transaction)
+                .await?
+            }
+            None => SharedOption::remove(&key, &mut *transaction).await?,
+        }
+    }
+    transaction.commit().await?;
+
+    list().await
+}
+
+/// Sets the shared options of the shared categories in a profile's options
+pub(crate) async fn apply(options: &mut GameOptions) -> crate::Result<()> {
+    let state = State::get().await?;
+    let settings = Settings::get(&state.pool).await?;
+
+    for option in SharedOption::get_all(&state.pool).await? {
+        if settings
+            .shared_option_categories
+            .iter()
+            .any(|x| x.contains(&option.key))
+        {
+            options.set(&option.key, option.value);
+        }
+    }
+
+    Ok(())
+}
+
+/// Records the values of the options of the shared categories in a
+/// profile's options as it launches, so only the ones the game changes are
+/// taken back when it exits
+pub(crate) async fn record_applied(
+    profile_path: &str,
+    options: &GameOptions,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let settings = Settings::get(&state.pool).await?;
+
+    let applied = options
+        .entries()
+        .into_iter()
+        .filter(|(key, _)| {
+            settings
+                .shared_option_categories
+                .iter()
+                .any(|x| x.contains(key))
+        })
+        .collect::<Vec<_>>();
+
+    let mut transaction = state.pool.begin().await?;
+    SharedOption::set_applied(profile_path, &applied, &mut transaction).await?;
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// Takes the options of the shared categories the game changed in a
+/// profile's options.txt since it launched into the shared options, so the
+/// other profiles syncing options get them on their next launch. Options it
+/// left alone are not taken, so they don't undo changes other profiles made
+/// meanwhile
+#[tracing::instrument]
+pub async fn pull(profile_path: &str) -> crate::Result<()> {
+    let state = State::get().await?;
+    let settings = Settings::get(&state.pool).await?;
+
+    let profile_dir = get_full_path(profile_path).await?;
+    let options = GameOptions::read(&profile_dir, OptionsFile::Vanilla).await?;
+    let applied = SharedOption::get_applied(profile_path, &state.pool).await?;
+
+    let mut transaction = state.pool.begin().await?;
+    for (key, value) in options.entries() {
+        if !settings
+            .shared_option_categories
+            .iter()
+            .any(|x| x.contains(&key))
+            || applied.get(&key) == Some(&value)
+        {
+            continue;
+        }
+
+        SharedOption {
+            key,
+            value,
+            modified: Utc::now(),
+        }
+        .upsert(&mut *transaction)
+        .await?;
+    }
+    SharedOption::remove_applied(profile_path, &mut *transaction).await?;
+    transaction.commit().await?;
+
+    Ok(())
+}
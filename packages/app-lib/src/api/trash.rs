@@ -0,0 +1,285 @@
+//! Theseus trash: removed profiles and project files, kept for a while so they
+//! can be restored
+use crate::event::emit::emit_profile;
+use crate::event::ProfilePayloadType;
+use crate::profile::{
+    add_all_recursive_folder_paths, delete_profile_data, get, get_full_path,
+    move_profile_data, sanitize_profile_name,
+};
+use crate::state::{ContentRef, Profile, Settings, TrashItem, TrashItemType};
+use crate::util::io;
+use crate::State;
+use chrono::{Duration, Utc};
+use std::path::Path;
+
+/// Lists everything in the trash, most recently removed first
+#[tracing::instrument]
+pub async fn list() -> crate::Result<Vec<TrashItem>> {
+    let state = State::get().await?;
+    let items = TrashItem::get_all(&state.pool).await?;
+    Ok(items)
+}
+
+/// Puts an item from the trash back where it was removed from
+/// Profiles are restored under a new path if their path was taken since.
+/// Returns the path of the restored profile, or the relative path of the
+/// restored project
+#[tracing::instrument]
+pub async fn restore(id: &str) -> crate::Result<String> {
+    let state = State::get().await?;
+    let item = TrashItem::get(id, &state.pool).await?.ok_or_else(|| {
+        crate::ErrorKind::InputError(format!("Trash item {id} does not exist"))
+    })?;
+    let trash_path = item.trash_path(&state.directories);
+
+    let path = match item.item_type {
+        TrashItemType::Profile => {
+            let mut profile = item.profile.clone().ok_or_else(|| {
+                crate::ErrorKind::InputError(format!(
+                    "Trash item {id} has no profile to restore"
+                ))
+            })?;
+
+            let base_path = sanitize_profile_name(&profile.path);
+            let mut path = base_path.clone();
+            let mut which = 1;
+            while get(&path).await?.is_some()
+                || state.directories.profiles_dir().join(&path).exists()
+            {
+                path = format!("{base_path} ({which})");
+                which += 1;
+            }
+
+            io::rename(
+                &trash_path,
+                state.directories.profiles_dir().join(&path),
+            )
+            .await?;
+
+            profile.path = path.clone();
+            let mut transaction = state.pool.begin().await?;
+            profile.upsert(&mut *transaction).await?;
+            move_profile_data(
+                &item.data_key(),
+                &path,
+                &state.directories,
+                &mut transaction,
+            )
+            .await?;
+            transaction.commit().await?;
+
+            crate::state::fs_watcher::watch_profile(
+                &profile.path,
+                &state.file_watcher,
+                &state.directories,
+            )
+            .await?;
+
+            emit_profile(&profile.path, ProfilePayloadType::Created).await?;
+
+            path
+        }
+        TrashItemType::Project => {
+            let project_path = item.project_path.clone().ok_or_else(|| {
+                crate::ErrorKind::InputError(format!(
+                    "Trash item {id} has no project to restore"
+                ))
+            })?;
+            get(&item.profile_path).await?.ok_or_else(|| {
+                crate::ErrorKind::UnmanagedProfileError(
+                    item.profile_path.clone(),
+                )
+            })?;
+
+            let dest =
+                get_full_path(&item.profile_path).await?.join(&project_path);
+            if dest.exists() {
+                return Err(crate::ErrorKind::InputError(format!(
+                    "{project_path} already exists in {}",
+                    item.profile_path
+                ))
+                .as_error());
+            }
+            if let Some(parent) = dest.parent() {
+                io::create_dir_all(parent).await?;
+            }
+            io::rename(&trash_path, &dest).await?;
+
+            emit_profile(&item.profile_path, ProfilePayloadType::Edited)
+                .await?;
+
+            project_path
+        }
+    };
+
+    TrashItem::remove(id, &state.pool).await?;
+
+    Ok(path)
+}
+
+/// Permanently deletes an item from the trash
+#[tracing::instrument]
+pub async fn delete(id: &str) -> crate::Result<()> {
+    let state = State::get().await?;
+    if let Some(item) = TrashItem::get(id, &state.pool).await? {
+        delete_item(&item).await?;
+    }
+
+    Ok(())
+}
+
+/// Permanently deletes everything in the trash
+#[tracing::instrument]
+pub async fn empty() -> crate::Result<()> {
+    let state = State::get().await?;
+    for item in TrashItem::get_all(&state.pool).await? {
+        delete_item(&item).await?;
+    }
+
+    Ok(())
+}
+
+/// Permanently deletes the items that have been in the trash for longer than
+/// the configured number of days
+#[tracing::instrument]
+pub async fn purge_expired() -> crate::Result<()> {
+    let state = State::get().await?;
+    let settings = Settings::get(&state.pool).await?;
+
+    let expiry =
+        Utc::now() - Duration::days(settings.trash_retention_days as i64);
+    for item in TrashItem::get_removed_before(expiry, &state.pool).await? {
+        delete_item(&item).await?;
+    }
+
+    Ok(())
+}
+
+/// Moves a profile's folder to the trash. Does not remove the profile itself
+/// Returns the trash item, or nothing if the folder was left alone because
+/// the trash is disabled
+pub(crate) async fn trash_profile(
+    profile: &Profile,
+) -> crate::Result<Option<TrashItem>> {
+    let state = State::get().await?;
+    let settings = Settings::get(&state.pool).await?;
+    if settings.trash_retention_days == 0 {
+        return Ok(None);
+    }
+
+    let full_path = get_full_path(&profile.path).await?;
+    let mut paths = Vec::new();
+    add_all_recursive_folder_paths(&full_path, &mut paths).await?;
+
+    let item = TrashItem {
+        id: uuid::Uuid::new_v4().to_string(),
+        item_type: TrashItemType::Profile,
+        profile_path: profile.path.clone(),
+        project_path: None,
+        profile: Some(profile.clone()),
+        removed: Utc::now(),
+        size: paths.iter().map(|x| file_size(x)).sum(),
+    };
+    move_to_trash(&item, &full_path).await?;
+
+    purge_expired_logged().await;
+
+    Ok(Some(item))
+}
+
+/// Moves a project file of a profile to the trash, deleting it instead if the
+/// trash is disabled
+pub(crate) async fn trash_project(
+    profile_path: &str,
+    project_path: &str,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let settings = Settings::get(&state.pool).await?;
+    if settings.trash_retention_days == 0 {
+        return Profile::remove_project(
+            profile_path,
+            project_path,
+            &state.directories,
+            &state.pool,
+        )
+        .await;
+    }
+
+    let full_path = get_full_path(profile_path).await?.join(project_path);
+    let item = TrashItem {
+        id: uuid::Uuid::new_v4().to_string(),
+        item_type: TrashItemType::Project,
+        profile_path: profile_path.to_string(),
+        project_path: Some(project_path.to_string()),
+        profile: None,
+        removed: Utc::now(),
+        size: file_size(&full_path),
+    };
+    move_to_trash(&item, &full_path).await?;
+
+    // The file in the trash keeps its own copy of the data
+    ContentRef::release(
+        profile_path,
+        project_path,
+        &state.directories,
+        &state.pool,
+    )
+    .await?;
+
+    purge_expired_logged().await;
+
+    Ok(())
+}
+
+// Purges the trash after an item was moved into it. The move already
+// happened, so a failed purge is only logged
+async fn purge_expired_logged() {
+    if let Err(err) = purge_expired().await {
+        tracing::warn!("Unable to purge the trash: {err}");
+    }
+}
+
+async fn move_to_trash(item: &TrashItem, path: &Path) -> crate::Result<()> {
+    let state = State::get().await?;
+
+    io::create_dir_all(state.directories.trash_dir()).await?;
+    io::rename(path, item.trash_path(&state.directories)).await?;
+    item.upsert(&state.pool).await?;
+
+    Ok(())
+}
+
+async fn delete_item(item: &TrashItem) -> crate::Result<()> {
+    let state = State::get().await?;
+
+    let trash_path = item.trash_path(&state.directories);
+    if trash_path.is_dir() {
+        io::remove_dir_all(&trash_path).await?;
+    } else if trash_path.is_file() {
+        io::remove_file(&trash_path).await?;
+    }
+
+    // Removed profiles keep their data until they leave the trash
+    let mut transaction = state.pool.begin().await?;
+    let hashes = if item.item_type == TrashItemType::Profile {
+        delete_profile_data(
+            &item.data_key(),
+            &state.directories,
+            &mut transaction,
+        )
+        .await?
+    } else {
+        Vec::new()
+    };
+    TrashItem::remove(&item.id, &mut *transaction).await?;
+    transaction.commit().await?;
+
+    ContentRef::collect_garbage(&hashes, &state.directories, &state.pool)
+        .await?;
+
+    Ok(())
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|x| x.len()).unwrap_or(0)
+}
@@ -13,6 +13,15 @@ pub enum ErrorKind {
     #[error("Serialization error (JSON): {0}")]
     JSONError(#[from] serde_json::Error),
 
+    #[error("Serialization error (TOML): {0}")]
+    TOMLError(#[from] toml::de::Error),
+
+    #[error("Serialization error (TOML): {0}")]
+    TOMLSerError(#[from] toml::ser::Error),
+
+    #[error("Error parsing NBT: {0}")]
+    NBTError(String),
+
     #[error("Error parsing UUID: {0}")]
     UUIDError(#[from] uuid::Error),
 
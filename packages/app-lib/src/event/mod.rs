@@ -181,6 +181,7 @@ pub enum LoadingBarType {
         import_location: PathBuf,
         profile_name: String,
     },
+    DiskUsage,
 }
 
 #[derive(Serialize, Clone)]
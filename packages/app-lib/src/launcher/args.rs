@@ -1,5 +1,5 @@
 //! Minecraft CLI argument logic
-use crate::launcher::parse_rules;
+use crate::launcher::{parse_rules, QuickPlayType};
 use crate::state::Credentials;
 use crate::{
     state::{MemorySettings, WindowSize},
@@ -30,7 +30,7 @@ pub fn get_class_paths(
         .iter()
         .filter_map(|library| {
             if let Some(rules) = &library.rules {
-                if !parse_rules(rules, java_arch, minecraft_updated) {
+                if !parse_rules(rules, java_arch, minecraft_updated, None) {
                     return None;
                 }
             }
@@ -116,6 +116,7 @@ pub fn get_jvm_arguments(
         parse_arguments(
             args,
             &mut parsed_arguments,
+            None,
             |arg| {
                 parse_jvm_argument(
                     arg.to_string(),
@@ -198,6 +199,7 @@ pub fn get_minecraft_arguments(
     arguments: Option<&[Argument]>,
     legacy_arguments: Option<&str>,
     credentials: &Credentials,
+    quick_play: Option<&QuickPlayType>,
     version: &str,
     asset_index_name: &str,
     game_directory: &Path,
@@ -206,18 +208,20 @@ pub fn get_minecraft_arguments(
     resolution: WindowSize,
     java_arch: &str,
 ) -> crate::Result<Vec<String>> {
-    if let Some(arguments) = arguments {
-        let mut parsed_arguments = Vec::new();
+    let mut parsed_arguments = Vec::new();
 
+    if let Some(arguments) = arguments {
         parse_arguments(
             arguments,
             &mut parsed_arguments,
+            quick_play,
             |arg| {
                 parse_minecraft_argument(
                     arg,
                     &credentials.access_token,
                     &credentials.username,
                     credentials.id,
+                    quick_play,
                     version,
                     asset_index_name,
                     game_directory,
@@ -228,16 +232,14 @@ pub fn get_minecraft_arguments(
             },
             java_arch,
         )?;
-
-        Ok(parsed_arguments)
     } else if let Some(legacy_arguments) = legacy_arguments {
-        let mut parsed_arguments = Vec::new();
         for x in legacy_arguments.split(' ') {
             parsed_arguments.push(parse_minecraft_argument(
                 &x.replace(' ', TEMPORARY_REPLACE_CHAR),
                 &credentials.access_token,
                 &credentials.username,
                 credentials.id,
+                quick_play,
                 version,
                 asset_index_name,
                 game_directory,
@@ -246,10 +248,40 @@ pub fn get_minecraft_arguments(
                 resolution,
             )?);
         }
-        Ok(parsed_arguments)
-    } else {
-        Ok(Vec::new())
     }
+
+    // Versions from before Quick Play only support joining a server directly
+    if let Some(quick_play) = quick_play {
+        if !arguments.is_some_and(supports_quick_play) {
+            if let Some((host, port)) = quick_play.server_address() {
+                parsed_arguments.push("--server".to_string());
+                parsed_arguments.push(host.to_string());
+                parsed_arguments.push("--port".to_string());
+                parsed_arguments.push(port.to_string());
+            } else {
+                return Err(crate::ErrorKind::LauncherError(format!(
+                    "Minecraft {version} does not support launching directly into a world or realm"
+                ))
+                .as_error());
+            }
+        }
+    }
+
+    Ok(parsed_arguments)
+}
+
+// Whether a version's game arguments include the Quick Play arguments (23w14a+)
+fn supports_quick_play(arguments: &[Argument]) -> bool {
+    arguments.iter().any(|argument| match argument {
+        Argument::Ruled { rules, .. } => rules.iter().any(|rule| {
+            rule.features.as_ref().is_some_and(|features| {
+                features.is_quick_play_singleplayer.is_some()
+                    || features.is_quick_play_multiplayer.is_some()
+                    || features.is_quick_play_realms.is_some()
+            })
+        }),
+        Argument::Normal(_) => false,
+    })
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -258,6 +290,7 @@ fn parse_minecraft_argument(
     access_token: &str,
     username: &str,
     uuid: Uuid,
+    quick_play: Option<&QuickPlayType>,
     version: &str,
     asset_index_name: &str,
     game_directory: &Path,
@@ -265,6 +298,18 @@ fn parse_minecraft_argument(
     version_type: &VersionType,
     resolution: WindowSize,
 ) -> crate::Result<String> {
+    let (quick_play_singleplayer, quick_play_multiplayer, quick_play_realms) =
+        match quick_play {
+            Some(QuickPlayType::Singleplayer { world }) => {
+                (world.as_str(), "", "")
+            }
+            Some(QuickPlayType::Multiplayer { address }) => {
+                ("", address.as_str(), "")
+            }
+            Some(QuickPlayType::Realm { id }) => ("", "", id.as_str()),
+            None => ("", "", ""),
+        };
+
     Ok(argument
         .replace("${accessToken}", access_token)
         .replace("${auth_access_token}", access_token)
@@ -317,12 +362,16 @@ fn parse_minecraft_argument(
         )
         .replace("${version_type}", version_type.as_str())
         .replace("${resolution_width}", &resolution.0.to_string())
-        .replace("${resolution_height}", &resolution.1.to_string()))
+        .replace("${resolution_height}", &resolution.1.to_string())
+        .replace("${quickPlaySingleplayer}", quick_play_singleplayer)
+        .replace("${quickPlayMultiplayer}", quick_play_multiplayer)
+        .replace("${quickPlayRealms}", quick_play_realms))
 }
 
 fn parse_arguments<F>(
     arguments: &[Argument],
     parsed_arguments: &mut Vec<String>,
+    quick_play: Option<&QuickPlayType>,
     parse_function: F,
     java_arch: &str,
 ) -> crate::Result<()>
@@ -339,7 +388,7 @@ where
                 }
             }
             Argument::Ruled { rules, value } => {
-                if parse_rules(rules, java_arch, true) {
+                if parse_rules(rules, java_arch, true, quick_play) {
                     match value {
                         ArgumentValue::Single(arg) => {
                             parsed_arguments.push(parse_function(
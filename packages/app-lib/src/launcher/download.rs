@@ -289,7 +289,8 @@ pub async fn download_libraries(
         stream::iter(libraries.iter())
             .map(Ok::<&Library, crate::Error>), None, loading_bar,loading_amount,num_files, None,|library| async move {
                 if let Some(rules) = &library.rules {
-                    if !parse_rules(rules, java_arch, minecraft_updated) {
+                    if !parse_rules(rules, java_arch, minecraft_updated, None)
+                    {
                         tracing::trace!("Skipped library {}", &library.name);
                         return Ok(());
                     }
@@ -3,7 +3,11 @@ use crate::data::ModLoader;
 use crate::event::emit::{emit_loading, init_or_edit_loading};
 use crate::event::{LoadingBarId, LoadingBarType};
 use crate::launcher::io::IOError;
-use crate::state::{Credentials, JavaVersion, Process, ProfileInstallStage};
+use crate::launcher::server::ServerVersion;
+use crate::profile::options::{GameOptions, OptionsFile};
+use crate::state::{
+    Credentials, JavaVersion, Process, ProfileInstallStage, ProfileType,
+};
 use crate::util::io;
 use crate::{
     process,
@@ -14,6 +18,7 @@ use chrono::Utc;
 use daedalus as d;
 use daedalus::minecraft::{RuleAction, VersionInfo};
 use daedalus::modded::LoaderVersion;
+use serde::{Deserialize, Serialize};
 use st::Profile;
 use std::collections::HashMap;
 use tokio::process::Command;
@@ -21,6 +26,44 @@ use tokio::process::Command;
 mod args;
 
 pub mod download;
+pub(crate) mod server;
+
+/// Where the game should drop the player once it has started, instead of the
+/// title screen
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuickPlayType {
+    /// Name of a world's folder in saves/
+    Singleplayer { world: String },
+    /// Server address, as `host` or `host:port`
+    Multiplayer { address: String },
+    /// Id of a Realm
+    Realm { id: String },
+}
+
+impl QuickPlayType {
+    /// Splits a multiplayer address into its host and port, defaulting to the
+    /// game's default port
+    pub fn server_address(&self) -> Option<(&str, u16)> {
+        let Self::Multiplayer { address } = self else {
+            return None;
+        };
+
+        let address = address.trim();
+        Some(match address.rsplit_once(':') {
+            Some((host, port))
+                if !host.is_empty()
+                    && (!host.contains(':') || host.ends_with(']')) =>
+            {
+                match port.parse() {
+                    Ok(port) => (host, port),
+                    Err(_) => (address, 25565),
+                }
+            }
+            _ => (address, 25565),
+        })
+    }
+}
 
 // All nones -> disallowed
 // 1+ true -> allowed
@@ -30,10 +73,11 @@ pub fn parse_rules(
     rules: &[d::minecraft::Rule],
     java_version: &str,
     minecraft_updated: bool,
+    quick_play: Option<&QuickPlayType>,
 ) -> bool {
     let mut x = rules
         .iter()
-        .map(|x| parse_rule(x, java_version, minecraft_updated))
+        .map(|x| parse_rule(x, java_version, minecraft_updated, quick_play))
         .collect::<Vec<Option<bool>>>();
 
     if rules
@@ -55,6 +99,7 @@ pub fn parse_rule(
     rule: &d::minecraft::Rule,
     java_version: &str,
     minecraft_updated: bool,
+    quick_play: Option<&QuickPlayType>,
 ) -> Option<bool> {
     use d::minecraft::{Rule, RuleAction};
 
@@ -71,9 +116,18 @@ pub fn parse_rule(
             !features.is_demo_user.unwrap_or(true)
                 || features.has_custom_resolution.unwrap_or(false)
                 || !features.has_quick_plays_support.unwrap_or(true)
-                || !features.is_quick_play_multiplayer.unwrap_or(true)
-                || !features.is_quick_play_realms.unwrap_or(true)
-                || !features.is_quick_play_singleplayer.unwrap_or(true)
+                || (features.is_quick_play_singleplayer.unwrap_or(false)
+                    && matches!(
+                        quick_play,
+                        Some(QuickPlayType::Singleplayer { .. })
+                    ))
+                || (features.is_quick_play_multiplayer.unwrap_or(false)
+                    && matches!(
+                        quick_play,
+                        Some(QuickPlayType::Multiplayer { .. })
+                    ))
+                || (features.is_quick_play_realms.unwrap_or(false)
+                    && matches!(quick_play, Some(QuickPlayType::Realm { .. })))
         }
         _ => return Some(true),
     };
@@ -276,22 +330,43 @@ pub async fn install_minecraft(
         java_version.upsert(&state.pool).await?;
     }
 
-    // Download minecraft (5-90)
-    download::download_minecraft(
-        &state,
-        &version_info,
-        &loading_bar,
-        &java_version.architecture,
-        repairing,
-        minecraft_updated,
-    )
-    .await?;
+    // Download minecraft, or the server for server profiles (5-90)
+    let server = if profile.profile_type == ProfileType::Server {
+        let server = ServerVersion::get(profile).await?;
+        server
+            .download(
+                &instance_path,
+                &java_version.architecture,
+                &loading_bar,
+                85.0,
+            )
+            .await?;
+
+        Some(server)
+    } else {
+        download::download_minecraft(
+            &state,
+            &version_info,
+            &loading_bar,
+            &java_version.architecture,
+            repairing,
+            minecraft_updated,
+        )
+        .await?;
+
+        None
+    };
 
     if let Some(processors) = &version_info.processors {
         let client_path = state
             .directories
             .version_dir(&version_jar)
             .join(format!("{version_jar}.jar"));
+        let server_path = instance_path.join(
+            server
+                .as_ref()
+                .map_or("server.jar", |server| &*server.server_jar),
+        );
 
         let libraries_dir = state.directories.libraries_dir();
 
@@ -300,90 +375,35 @@ pub async fn install_minecraft(
                 data;
                 "SIDE":
                     client => "client",
-                    server => "";
+                    server => "server";
                 "MINECRAFT_JAR" :
                     client => client_path.to_string_lossy(),
-                    server => "";
+                    server => server_path.to_string_lossy();
                 "MINECRAFT_VERSION":
                     client => profile.game_version.clone(),
-                    server => "";
+                    server => profile.game_version.clone();
                 "ROOT":
                     client => instance_path.to_string_lossy(),
-                    server => "";
+                    server => instance_path.to_string_lossy();
                 "LIBRARY_DIR":
                     client => libraries_dir.to_string_lossy(),
-                    server => "";
+                    server => libraries_dir.to_string_lossy();
             }
 
             emit_loading(&loading_bar, 0.0, Some("Running forge processors"))
                 .await?;
-            let total_length = processors.len();
 
             // Forge processors (90-100)
-            for (index, processor) in processors.iter().enumerate() {
-                if let Some(sides) = &processor.sides {
-                    if !sides.contains(&String::from("client")) {
-                        continue;
-                    }
-                }
-
-                let cp = wrap_ref_builder!(cp = processor.classpath.clone() => {
-                    cp.push(processor.jar.clone())
-                });
-
-                let child = Command::new(&java_version.path)
-                    .arg("-cp")
-                    .arg(args::get_class_paths_jar(
-                        &libraries_dir,
-                        &cp,
-                        &java_version.architecture,
-                    )?)
-                    .arg(
-                        args::get_processor_main_class(args::get_lib_path(
-                            &libraries_dir,
-                            &processor.jar,
-                            false,
-                        )?)
-                        .await?
-                        .ok_or_else(|| {
-                            crate::ErrorKind::LauncherError(format!(
-                                "Could not find processor main class for {}",
-                                processor.jar
-                            ))
-                        })?,
-                    )
-                    .args(args::get_processor_arguments(
-                        &libraries_dir,
-                        &processor.args,
-                        data,
-                    )?)
-                    .output()
-                    .await
-                    .map_err(|e| IOError::with_path(e, &java_version.path))
-                    .map_err(|err| {
-                        crate::ErrorKind::LauncherError(format!(
-                            "Error running processor: {err}",
-                        ))
-                    })?;
-
-                if !child.status.success() {
-                    return Err(crate::ErrorKind::LauncherError(format!(
-                        "Processor error: {}",
-                        String::from_utf8_lossy(&child.stderr)
-                    ))
-                    .as_error());
-                }
-
-                emit_loading(
-                    &loading_bar,
-                    30.0 / total_length as f64,
-                    Some(&format!(
-                        "Running forge processor {}/{}",
-                        index, total_length
-                    )),
-                )
-                .await?;
-            }
+            run_processors(
+                processors,
+                data,
+                profile.profile_type.side(),
+                &java_version,
+                &libraries_dir,
+                &loading_bar,
+                30.0,
+            )
+            .await?;
         }
     }
 
@@ -398,6 +418,104 @@ pub async fn install_minecraft(
     Ok(())
 }
 
+/// Runs the processors of a loader version (such as Forge's) for a side,
+/// `client` or `server`, using the data of that side
+#[tracing::instrument(skip(processors, data, loading_bar))]
+pub(crate) async fn run_processors(
+    processors: &[d::modded::Processor],
+    data: &HashMap<String, d::modded::SidedDataEntry>,
+    side: &str,
+    java_version: &JavaVersion,
+    libraries_dir: &std::path::Path,
+    loading_bar: &LoadingBarId,
+    loading_amount: f64,
+) -> crate::Result<()> {
+    // Processor arguments are read from the client values
+    let data = if side == "server" {
+        data.iter()
+            .map(|(key, entry)| {
+                (
+                    key.clone(),
+                    d::modded::SidedDataEntry {
+                        client: entry.server.clone(),
+                        server: entry.server.clone(),
+                    },
+                )
+            })
+            .collect()
+    } else {
+        data.clone()
+    };
+
+    let total_length = processors.len();
+    for (index, processor) in processors.iter().enumerate() {
+        if let Some(sides) = &processor.sides {
+            if !sides.iter().any(|x| x == side) {
+                continue;
+            }
+        }
+
+        let cp = wrap_ref_builder!(cp = processor.classpath.clone() => {
+            cp.push(processor.jar.clone())
+        });
+
+        let child = Command::new(&java_version.path)
+            .arg("-cp")
+            .arg(args::get_class_paths_jar(
+                libraries_dir,
+                &cp,
+                &java_version.architecture,
+            )?)
+            .arg(
+                args::get_processor_main_class(args::get_lib_path(
+                    libraries_dir,
+                    &processor.jar,
+                    false,
+                )?)
+                .await?
+                .ok_or_else(|| {
+                    crate::ErrorKind::LauncherError(format!(
+                        "Could not find processor main class for {}",
+                        processor.jar
+                    ))
+                })?,
+            )
+            .args(args::get_processor_arguments(
+                libraries_dir,
+                &processor.args,
+                &data,
+            )?)
+            .output()
+            .await
+            .map_err(|e| IOError::with_path(e, &java_version.path))
+            .map_err(|err| {
+                crate::ErrorKind::LauncherError(format!(
+                    "Error running processor: {err}",
+                ))
+            })?;
+
+        if !child.status.success() {
+            return Err(crate::ErrorKind::LauncherError(format!(
+                "Processor error: {}",
+                String::from_utf8_lossy(&child.stderr)
+            ))
+            .as_error());
+        }
+
+        emit_loading(
+            loading_bar,
+            loading_amount / total_length as f64,
+            Some(&format!(
+                "Running forge processor {}/{}",
+                index, total_length
+            )),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
 #[tracing::instrument(skip_all)]
 #[allow(clippy::too_many_arguments)]
 pub async fn launch_minecraft(
@@ -408,6 +526,7 @@ pub async fn launch_minecraft(
     memory: &st::MemorySettings,
     resolution: &st::WindowSize,
     credentials: &Credentials,
+    quick_play: Option<&QuickPlayType>,
     post_exit_hook: Option<String>,
     profile: &Profile,
 ) -> crate::Result<Process> {
@@ -541,6 +660,7 @@ pub async fn launch_minecraft(
                     .map(|x| x.as_slice()),
                 version_info.minecraft_arguments.as_deref(),
                 credentials,
+                quick_play,
                 &version.id,
                 &version_info.asset_index.id,
                 &instance_path,
@@ -564,31 +684,39 @@ pub async fn launch_minecraft(
 
     command.envs(env_args);
 
-    // Overwrites the minecraft options.txt file with the settings from the profile
-    // Uses 'a:b' syntax which is not quite yaml
-    use regex::Regex;
-
-    if !mc_set_options.is_empty() {
-        let options_path = instance_path.join("options.txt");
-        let mut options_string = String::new();
-        if options_path.exists() {
-            options_string = io::read_to_string(&options_path).await?;
+    // Sets the profile's default options when it has no options.txt yet,
+    // the shared options if the profile syncs them, and the options the
+    // launcher forces on every launch, in options.txt. Imported profiles
+    // keep the options they came with
+    let default_options = profile
+        .default_options
+        .as_deref()
+        .filter(|_| {
+            !instance_path
+                .join(OptionsFile::Vanilla.file_name())
+                .exists()
+        })
+        .unwrap_or_default();
+    if !default_options.is_empty()
+        || profile.sync_options
+        || !mc_set_options.is_empty()
+    {
+        let mut options =
+            GameOptions::read(&instance_path, OptionsFile::Vanilla).await?;
+        for (key, value) in default_options {
+            options.set(key, value.clone());
+        }
+        if profile.sync_options {
+            crate::api::shared_options::apply(&mut options).await?;
         }
         for (key, value) in mc_set_options {
-            let re = Regex::new(&format!(r"(?m)^{}:.*$", regex::escape(key)))?;
-            // check if the regex exists in the file
-            if !re.is_match(&options_string) {
-                // The key was not found in the file, so append it
-                options_string.push_str(&format!("\n{}:{}", key, value));
-            } else {
-                let replaced_string = re
-                    .replace_all(&options_string, &format!("{}:{}", key, value))
-                    .to_string();
-                options_string = replaced_string;
-            }
+            options.set(key, value.clone());
+        }
+        options.write(&instance_path).await?;
+        if profile.sync_options {
+            crate::api::shared_options::record_applied(&profile.path, &options)
+                .await?;
         }
-
-        io::write(&options_path, options_string).await?;
     }
 
     crate::api::profile::edit(&profile.path, |prof| {
@@ -654,3 +782,132 @@ pub async fn launch_minecraft(
     )
     .await
 }
+
+/// Starts the dedicated server of a server profile. Its console's input is
+/// kept open, so commands can be sent to it
+#[tracing::instrument(skip_all)]
+pub async fn launch_server(
+    java_args: &[String],
+    env_args: &[(String, String)],
+    wrapper: &Option<String>,
+    memory: &st::MemorySettings,
+    post_exit_hook: Option<String>,
+    profile: &Profile,
+) -> crate::Result<Process> {
+    if profile.install_stage == ProfileInstallStage::PackInstalling
+        || profile.install_stage == ProfileInstallStage::Installing
+    {
+        return Err(crate::ErrorKind::LauncherError(
+            "Profile is still installing".to_string(),
+        )
+        .into());
+    }
+
+    if profile.install_stage != ProfileInstallStage::Installed {
+        install_minecraft(profile, None, false).await?;
+    }
+
+    if !crate::api::profile::server_config::get_eula(&profile.path).await? {
+        return Err(crate::ErrorKind::LauncherError(
+            "The Minecraft EULA must be accepted before starting the server"
+                .to_string(),
+        )
+        .into());
+    }
+
+    let state = State::get().await?;
+
+    let instance_path =
+        crate::api::profile::get_full_path(&profile.path).await?;
+
+    let server = ServerVersion::get(profile).await?;
+
+    let java_version =
+        get_java_version_from_profile(profile, &server.version_info)
+            .await?
+            .ok_or_else(|| {
+                crate::ErrorKind::LauncherError(
+                    "Missing correct java installation".to_string(),
+                )
+            })?;
+
+    // Test jre version
+    let java_version =
+        crate::api::jre::check_jre(java_version.path.clone().into())
+            .await?
+            .ok_or_else(|| {
+                crate::ErrorKind::LauncherError(format!(
+                    "Java path invalid or non-functional: {}",
+                    java_version.path
+                ))
+            })?;
+
+    let libraries_dir = state.directories.libraries_dir();
+    let bundled_libraries = server
+        .extract_bundled_libraries(&instance_path, &libraries_dir)
+        .await?;
+    let arguments = server
+        .arguments(
+            profile.loader,
+            &libraries_dir.to_string_lossy(),
+            &bundled_libraries,
+            &java_version.architecture,
+        )?
+        .into_iter()
+        .map(|x| {
+            x.replace(
+                "${classpath_separator}",
+                crate::util::platform::classpath_separator(
+                    &java_version.architecture,
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut command = match wrapper {
+        Some(hook) => {
+            wrap_ref_builder!(it = Command::new(hook) => {it.arg(&java_version.path)})
+        }
+        None => Command::new(&java_version.path),
+    };
+
+    // Check if profile has a running profile, and reject running the command if it does
+    // Done late so a quick double call doesn't launch two instances
+    let existing_processes =
+        process::get_by_profile_path(&profile.path).await?;
+    if let Some(process) = existing_processes.first() {
+        return Err(crate::ErrorKind::LauncherError(format!(
+            "Profile {} is already running at path: {}",
+            profile.path, process.pid
+        ))
+        .as_error());
+    }
+    command
+        .arg(format!("-Xmx{}M", memory.maximum))
+        .args(java_args)
+        .args(arguments)
+        .current_dir(instance_path)
+        .stdin(std::process::Stdio::piped());
+
+    // Java options should be set in instance options (the existence of _JAVA_OPTIONS overwites them)
+    command.env_remove("_JAVA_OPTIONS");
+
+    command.envs(Vec::from(env_args));
+
+    crate::api::profile::edit(&profile.path, |prof| {
+        prof.last_played = Some(Utc::now());
+
+        async { Ok(()) }
+    })
+    .await?;
+
+    // Create the server child by inserting it into the state
+    // This also spawns the process and prepares the subsequent processes
+    Process::insert_new_process(
+        &profile.path,
+        command,
+        post_exit_hook,
+        &state.pool,
+    )
+    .await
+}
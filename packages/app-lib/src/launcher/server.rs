@@ -0,0 +1,400 @@
+//! Logic for installing and launching dedicated Minecraft servers
+use crate::event::emit::emit_loading;
+use crate::event::LoadingBarId;
+use crate::launcher::download::{download_libraries, download_version_info};
+use crate::launcher::{
+    get_java_version_from_profile, get_loader_version_from_profile, parse_rules,
+};
+use crate::state::{JavaVersion, ModLoader, Profile};
+use crate::util::fetch::{fetch, write};
+use crate::util::io::IOError;
+use crate::State;
+use daedalus as d;
+use daedalus::minecraft::{
+    Argument, ArgumentType, DownloadType, Library, VersionInfo,
+};
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::Path;
+
+/// A profile's game and loader versions, as a dedicated server runs them
+pub(crate) struct ServerVersion {
+    pub version_info: VersionInfo,
+    pub vanilla_info: VersionInfo,
+    /// Libraries the loader adds to the vanilla version's
+    pub loader_libraries: Vec<Library>,
+    /// File name of the vanilla server jar, in the server's folder
+    pub server_jar: String,
+    /// Whether the loader is a Forge old enough to have no processors
+    pub legacy_forge: bool,
+    pub minecraft_updated: bool,
+}
+
+impl ServerVersion {
+    pub async fn get(profile: &Profile) -> crate::Result<Self> {
+        let state = State::get().await?;
+
+        let minecraft = crate::api::metadata::get_minecraft_versions().await?;
+        let version_index = minecraft
+            .versions
+            .iter()
+            .position(|it| it.id == profile.game_version)
+            .ok_or(crate::ErrorKind::LauncherError(format!(
+                "Invalid game version: {}",
+                profile.game_version
+            )))?;
+        let version = &minecraft.versions[version_index];
+        let minecraft_updated = version_index
+            <= minecraft
+                .versions
+                .iter()
+                .position(|x| x.id == "22w16a")
+                .unwrap_or(0);
+
+        let loader_version = get_loader_version_from_profile(
+            &profile.game_version,
+            profile.loader,
+            profile.loader_version.as_deref(),
+        )
+        .await?;
+
+        // The loader's libraries and arguments are those the vanilla version
+        // doesn't have
+        let vanilla_info =
+            download_version_info(&state, version, None, None, None).await?;
+        let version_info = if loader_version.is_some() {
+            download_version_info(
+                &state,
+                version,
+                loader_version.as_ref(),
+                None,
+                None,
+            )
+            .await?
+        } else {
+            vanilla_info.clone()
+        };
+
+        let vanilla_libraries = vanilla_info
+            .libraries
+            .iter()
+            .map(|x| &*x.name)
+            .collect::<HashSet<_>>();
+        let loader_libraries = version_info
+            .libraries
+            .iter()
+            .filter(|x| !vanilla_libraries.contains(&*x.name))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // Legacy Forge looks for the vanilla server under its own name
+        let legacy_forge = profile.loader == ModLoader::Forge
+            && version_info.processors.is_none();
+        let server_jar = if legacy_forge {
+            format!("minecraft_server.{}.jar", version.id)
+        } else {
+            "server.jar".to_string()
+        };
+
+        Ok(Self {
+            version_info,
+            vanilla_info,
+            loader_libraries,
+            server_jar,
+            legacy_forge,
+            minecraft_updated,
+        })
+    }
+
+    /// Downloads the vanilla server jar into a server folder, and the loader's
+    /// libraries into the shared libraries folder
+    pub async fn download(
+        &self,
+        server_dir: &Path,
+        java_arch: &str,
+        loading_bar: &LoadingBarId,
+        loading_amount: f64,
+    ) -> crate::Result<()> {
+        let state = State::get().await?;
+
+        let server_download = self
+            .version_info
+            .downloads
+            .get(&DownloadType::Server)
+            .ok_or_else(|| {
+                crate::ErrorKind::LauncherError(format!(
+                    "No server downloads exist for version {}",
+                    self.vanilla_info.id
+                ))
+            })?;
+        let bytes = fetch(
+            &server_download.url,
+            Some(&server_download.sha1),
+            &state.fetch_semaphore,
+            &state.pool,
+        )
+        .await?;
+        write(
+            &server_dir.join(&self.server_jar),
+            &bytes,
+            &state.io_semaphore,
+        )
+        .await?;
+        emit_loading(loading_bar, loading_amount / 3.0, None).await?;
+
+        download_libraries(
+            &state,
+            &self.loader_libraries,
+            &self.version_info.id,
+            Some(loading_bar),
+            loading_amount * 2.0 / 3.0,
+            java_arch,
+            false,
+            self.minecraft_updated,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Extracts the libraries bundled in a 1.18+ vanilla server jar into a
+    /// libraries folder, and returns their paths relative to it. Older server
+    /// jars have their libraries built in, and bundle none
+    pub async fn extract_bundled_libraries(
+        &self,
+        server_dir: &Path,
+        libraries_dir: &Path,
+    ) -> crate::Result<Vec<String>> {
+        let server_jar = server_dir.join(&self.server_jar);
+        let libraries_dir = libraries_dir.to_path_buf();
+
+        let libraries = tokio::task::spawn_blocking(move || {
+            let file = std::fs::File::open(&server_jar)
+                .map_err(|e| IOError::with_path(e, &server_jar))?;
+            let mut archive = zip::ZipArchive::new(file).map_err(|_| {
+                crate::ErrorKind::LauncherError(format!(
+                    "Cannot read server jar at {}",
+                    server_jar.display()
+                ))
+                .as_error()
+            })?;
+
+            let mut list = String::new();
+            match archive.by_name("META-INF/libraries.list") {
+                Ok(mut entry) => {
+                    entry.read_to_string(&mut list).map_err(IOError::from)?;
+                }
+                Err(_) => return Ok(Vec::new()),
+            }
+
+            // Each line is a library's sha1, maven id and path
+            let mut libraries = Vec::new();
+            for line in list.lines() {
+                let mut parts = line.split('\t');
+                let (Some(sha1), Some(_), Some(path)) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    continue;
+                };
+
+                let dest = libraries_dir.join(path);
+                if !dest.exists() {
+                    let mut entry = archive
+                        .by_name(&format!("META-INF/libraries/{path}"))
+                        .map_err(|_| {
+                            crate::ErrorKind::LauncherError(format!(
+                                "Server jar is missing bundled library {path}"
+                            ))
+                            .as_error()
+                        })?;
+                    let mut bytes = Vec::new();
+                    entry.read_to_end(&mut bytes).map_err(IOError::from)?;
+                    if sha1_smol::Sha1::from(&bytes).hexdigest() != sha1 {
+                        return Err(crate::ErrorKind::LauncherError(format!(
+                            "Bundled library {path} does not match its hash"
+                        ))
+                        .as_error());
+                    }
+
+                    if let Some(parent) = dest.parent() {
+                        std::fs::create_dir_all(parent)
+                            .map_err(|e| IOError::with_path(e, parent))?;
+                    }
+                    std::fs::write(&dest, bytes)
+                        .map_err(|e| IOError::with_path(e, &dest))?;
+                }
+
+                libraries.push(path.to_string());
+            }
+
+            Ok::<Vec<String>, crate::Error>(libraries)
+        })
+        .await??;
+
+        Ok(libraries)
+    }
+
+    /// Java arguments that start the server, after the memory settings.
+    /// Library paths are relative to `libraries_dir`, and the classpath
+    /// separator is left as `${classpath_separator}`
+    /// `bundled_libraries` are the vanilla server's libraries, as
+    /// `extract_bundled_libraries` gives them
+    pub fn arguments(
+        &self,
+        loader: ModLoader,
+        libraries_dir: &str,
+        bundled_libraries: &[String],
+        java_arch: &str,
+    ) -> crate::Result<Vec<String>> {
+        let mut classpath = Vec::new();
+        for library in &self.loader_libraries {
+            if !library.include_in_classpath {
+                continue;
+            }
+            if let Some(rules) = &library.rules {
+                if !parse_rules(rules, java_arch, self.minecraft_updated, None)
+                {
+                    continue;
+                }
+            }
+
+            // Libraries the processors build for the client have a server
+            // twin
+            let name = library
+                .name
+                .replace("net.minecraft:client:", "net.minecraft:server:");
+            let name = match name.strip_suffix(":client") {
+                Some(name) => format!("{name}:server"),
+                None => name,
+            };
+            classpath.push(format!(
+                "{libraries_dir}/{}",
+                d::get_path_from_artifact(&name)?
+            ));
+        }
+
+        let server_jar = &self.server_jar;
+        let arguments = match loader {
+            ModLoader::Vanilla => {
+                vec!["-jar".to_string(), server_jar.to_string()]
+            }
+            ModLoader::Fabric | ModLoader::Quilt => {
+                let property = if loader == ModLoader::Fabric {
+                    "fabric.gameJarPath"
+                } else {
+                    "loader.gameJarPath"
+                };
+
+                vec![
+                    format!("-D{property}={server_jar}"),
+                    "-cp".to_string(),
+                    classpath.join("${classpath_separator}"),
+                    self.version_info
+                        .main_class
+                        .replace("KnotClient", "KnotServer"),
+                ]
+            }
+            ModLoader::Forge | ModLoader::NeoForge if self.legacy_forge => {
+                classpath.push(server_jar.to_string());
+
+                vec![
+                    "-cp".to_string(),
+                    classpath.join("${classpath_separator}"),
+                    "net.minecraftforge.fml.relauncher.ServerLaunchWrapper"
+                        .to_string(),
+                ]
+            }
+            ModLoader::Forge | ModLoader::NeoForge => {
+                // Modern Forge and NeoForge load the game from the
+                // libraries folder, with the vanilla server's libraries
+                // from the bundler alongside their own
+                classpath.extend(
+                    bundled_libraries
+                        .iter()
+                        .map(|x| format!("{libraries_dir}/{x}")),
+                );
+                let classpath = classpath.join("${classpath_separator}");
+
+                let version_name = server_jar.trim_end_matches(".jar");
+                let mut arguments = self
+                    .loader_arguments(ArgumentType::Jvm)
+                    .into_iter()
+                    .map(|x| {
+                        x.replace("${library_directory}", libraries_dir)
+                            .replace("${version_name}", version_name)
+                    })
+                    .collect::<Vec<_>>();
+                arguments.push(format!("-DlegacyClassPath={classpath}"));
+                arguments.push("-cp".to_string());
+                arguments.push(classpath);
+                arguments.push(self.version_info.main_class.clone());
+
+                // The same launch target, for the server
+                let mut launch_target = false;
+                for argument in self.loader_arguments(ArgumentType::Game) {
+                    arguments.push(match argument.strip_suffix("client") {
+                        Some(target) if launch_target => {
+                            format!("{target}server")
+                        }
+                        _ => argument.clone(),
+                    });
+                    launch_target = argument == "--launchTarget";
+                }
+
+                arguments
+            }
+        };
+
+        Ok(arguments.into_iter().chain(["nogui".to_string()]).collect())
+    }
+
+    /// Arguments the loader adds to the vanilla version's
+    fn loader_arguments(&self, argument_type: ArgumentType) -> Vec<String> {
+        let normal = |info: &VersionInfo| {
+            info.arguments
+                .as_ref()
+                .and_then(|x| x.get(&argument_type))
+                .into_iter()
+                .flatten()
+                .filter_map(|x| match x {
+                    Argument::Normal(x) => Some(x.clone()),
+                    Argument::Ruled { .. } => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let vanilla = normal(&self.vanilla_info);
+        normal(&self.version_info)
+            .into_iter()
+            .filter(|x| !vanilla.contains(x))
+            .collect()
+    }
+}
+
+/// Java to run a server's loader processors with, installing it if needed
+pub(crate) async fn server_java(
+    profile: &Profile,
+    version_info: &VersionInfo,
+) -> crate::Result<JavaVersion> {
+    if let Some(java_version) =
+        get_java_version_from_profile(profile, version_info).await?
+    {
+        return Ok(java_version);
+    }
+
+    let key = version_info
+        .java_version
+        .as_ref()
+        .map(|it| it.major_version)
+        .unwrap_or(8);
+    let path = crate::api::jre::auto_install_java(key).await?;
+    crate::api::jre::check_jre(path.clone())
+        .await?
+        .ok_or_else(|| {
+            crate::ErrorKind::LauncherError(format!(
+                "Java path invalid or non-functional: {:?}",
+                path
+            ))
+            .into()
+        })
+}
@@ -1,5 +1,6 @@
 use crate::config::{META_URL, MODRINTH_API_URL, MODRINTH_API_URL_V3};
-use crate::util::fetch::{fetch_json, sha1_async, FetchSemaphore};
+use crate::state::ReleaseChannel;
+use crate::util::fetch::{fetch_json, sha1_async, FetchSemaphore, IoSemaphore};
 use chrono::{DateTime, Utc};
 use dashmap::DashSet;
 use reqwest::Method;
@@ -32,6 +33,7 @@ pub enum CacheValueType {
     FileHash,
     FileUpdate,
     SearchResults,
+    ProjectVersions,
 }
 
 impl CacheValueType {
@@ -53,6 +55,7 @@ impl CacheValueType {
             CacheValueType::FileHash => "file_hash",
             CacheValueType::FileUpdate => "file_update",
             CacheValueType::SearchResults => "search_results",
+            CacheValueType::ProjectVersions => "project_versions",
         }
     }
 
@@ -74,6 +77,7 @@ impl CacheValueType {
             "file_hash" => CacheValueType::FileHash,
             "file_update" => CacheValueType::FileUpdate,
             "search_results" => CacheValueType::SearchResults,
+            "project_versions" => CacheValueType::ProjectVersions,
             _ => CacheValueType::Project,
         }
     }
@@ -115,7 +119,8 @@ impl CacheValueType {
             | CacheValueType::File
             | CacheValueType::LoaderManifest
             | CacheValueType::FileUpdate
-            | CacheValueType::SearchResults => None,
+            | CacheValueType::SearchResults
+            | CacheValueType::ProjectVersions => None,
         }
     }
 }
@@ -148,6 +153,7 @@ pub enum CacheValue {
     FileHash(CachedFileHash),
     FileUpdate(CachedFileUpdate),
     SearchResults(SearchResults),
+    ProjectVersions(CachedProjectVersions),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -192,16 +198,98 @@ pub struct SearchEntry {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CachedFileUpdate {
     pub hash: String,
-    pub game_version: String,
+    /// Game version the update is for, or any if none
+    pub game_version: Option<String>,
     pub loader: String,
+    /// Release channels the update may come from
+    #[serde(default)]
+    pub channels: Vec<ReleaseChannel>,
     pub update_version_id: String,
 }
 
+impl CachedFileUpdate {
+    /// Cache key of the update of a file
+    pub fn key(
+        hash: &str,
+        loader: &str,
+        channels: &[ReleaseChannel],
+        game_version: Option<&str>,
+    ) -> String {
+        // The game version goes last, as it may contain dashes
+        format!(
+            "{hash}-{loader}-{}-{}",
+            channels
+                .iter()
+                .map(|x| x.as_str())
+                .collect::<Vec<_>>()
+                .join("+"),
+            game_version.unwrap_or("*")
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CachedProjectVersions {
+    pub project_id: String,
+    /// Every version of the project, newest first
+    pub versions: Vec<Version>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CachedFileHash {
     pub path: String,
     pub size: u64,
     pub hash: String,
+    /// Metadata read from inside the file, for files that have any
+    #[serde(default)]
+    pub manifest: Option<FileManifest>,
+}
+
+/// Metadata a project declares inside its own file, such as `fabric.mod.json`
+/// or `pack.mcmeta`. Available for files that are not on Modrinth too
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileManifest {
+    pub kind: FileManifestKind,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub authors: Vec<String>,
+    /// Path to the icon, extracted into the icon cache
+    pub icon: Option<String>,
+    pub dependencies: Vec<FileManifestDependency>,
+    /// Game versions the file declares support for, as written in the file
+    /// (a version, or a range such as `>=1.20` or `[1.20,1.21)`)
+    pub game_versions: Vec<String>,
+    /// Resource and data pack format, for packs
+    pub pack_format: Option<u32>,
+    /// Range of pack formats the pack also supports, inclusive
+    #[serde(default)]
+    pub supported_formats: Option<(u32, u32)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileManifestKind {
+    /// fabric.mod.json
+    Fabric,
+    /// quilt.mod.json
+    Quilt,
+    /// META-INF/mods.toml
+    Forge,
+    /// META-INF/neoforge.mods.toml
+    NeoForge,
+    /// mcmod.info, used by Forge before 1.13
+    LegacyForge,
+    /// pack.mcmeta
+    Pack,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileManifestDependency {
+    pub id: String,
+    /// Version requirement, as written in the file
+    pub version: Option<String>,
+    pub required: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -444,6 +532,7 @@ impl CacheValue {
             CacheValue::FileHash(_) => CacheValueType::FileHash,
             CacheValue::FileUpdate(_) => CacheValueType::FileUpdate,
             CacheValue::SearchResults(_) => CacheValueType::SearchResults,
+            CacheValue::ProjectVersions(_) => CacheValueType::ProjectVersions,
         }
     }
 
@@ -472,10 +561,16 @@ impl CacheValue {
             CacheValue::FileHash(hash) => {
                 format!("{}-{}", hash.size, hash.path.replace(".disabled", ""))
             }
-            CacheValue::FileUpdate(hash) => {
-                format!("{}-{}-{}", hash.hash, hash.loader, hash.game_version)
-            }
+            CacheValue::FileUpdate(hash) => CachedFileUpdate::key(
+                &hash.hash,
+                &hash.loader,
+                &hash.channels,
+                hash.game_version.as_deref(),
+            ),
             CacheValue::SearchResults(search) => search.search.clone(),
+            CacheValue::ProjectVersions(versions) => {
+                versions.project_id.clone()
+            }
         }
     }
 
@@ -500,7 +595,8 @@ impl CacheValue {
             | CacheValue::File { .. }
             | CacheValue::LoaderManifest { .. }
             | CacheValue::FileUpdate(_)
-            | CacheValue::SearchResults(_) => None,
+            | CacheValue::SearchResults(_)
+            | CacheValue::ProjectVersions(_) => None,
         }
     }
 }
@@ -613,7 +709,8 @@ impl_cache_methods!(
     (LoaderManifest, CachedLoaderManifest),
     (FileHash, CachedFileHash),
     (FileUpdate, CachedFileUpdate),
-    (SearchResults, SearchResults)
+    (SearchResults, SearchResults),
+    (ProjectVersions, CachedProjectVersions)
 );
 
 impl_cache_method_singular!(
@@ -1185,9 +1282,12 @@ impl CachedEntry {
                 // TODO: Replace state call here
                 let state = crate::State::get().await?;
                 let profiles_dir = state.directories.profiles_dir();
+                let caches_dir = state.directories.caches_dir();
 
                 async fn hash_file(
                     profiles_dir: &Path,
+                    caches_dir: &Path,
+                    io_semaphore: &IoSemaphore,
                     key: String,
                 ) -> crate::Result<(CachedEntry, bool)> {
                     let path =
@@ -1212,11 +1312,19 @@ impl CachedEntry {
 
                     let hash = hasher.digest().to_string();
 
+                    let manifest = crate::util::manifest::read_from_path(
+                        full_path,
+                        caches_dir,
+                        io_semaphore,
+                    )
+                    .await;
+
                     Ok((
                         CacheValue::FileHash(CachedFileHash {
                             path: path.to_string(),
                             size,
                             hash,
+                            manifest,
                         })
                         .get_entry(),
                         true,
@@ -1225,7 +1333,14 @@ impl CachedEntry {
 
                 use futures::stream::StreamExt;
                 let results: Vec<_> = futures::stream::iter(keys)
-                    .map(|x| hash_file(&profiles_dir, x.to_string()))
+                    .map(|x| {
+                        hash_file(
+                            &profiles_dir,
+                            &caches_dir,
+                            &state.io_semaphore,
+                            x.to_string(),
+                        )
+                    })
                     .buffer_unordered(64) // hash 64 files at once
                     .collect::<Vec<_>>()
                     .await
@@ -1239,26 +1354,37 @@ impl CachedEntry {
                 let mut vals = Vec::new();
 
                 // TODO: switch to update individual once back-end route exists
-                let mut filtered_keys: Vec<((String, String), Vec<String>)> =
-                    Vec::new();
+                #[allow(clippy::type_complexity)]
+                let mut filtered_keys: Vec<(
+                    (String, Vec<ReleaseChannel>, Option<String>),
+                    Vec<String>,
+                )> = Vec::new();
                 keys.iter().for_each(|x| {
                     let string = x.key().to_string();
-                    let key = string.splitn(3, '-').collect::<Vec<_>>();
+                    let key = string.splitn(4, '-').collect::<Vec<_>>();
 
-                    if key.len() == 3 {
+                    if key.len() == 4 {
                         let hash = key[0];
                         let loader = key[1];
-                        let game_version = key[2];
+                        let channels = key[2]
+                            .split('+')
+                            .filter_map(ReleaseChannel::from_string)
+                            .collect::<Vec<_>>();
+                        let game_version = Some(key[3])
+                            .filter(|x| *x != "*")
+                            .map(String::from);
 
                         if let Some(values) =
                             filtered_keys.iter_mut().find(|x| {
-                                x.0 .0 == loader && x.0 .1 == game_version
+                                x.0 .0 == loader
+                                    && x.0 .1 == channels
+                                    && x.0 .2 == game_version
                             })
                         {
                             values.1.push(hash.to_string());
                         } else {
                             filtered_keys.push((
-                                (loader.to_string(), game_version.to_string()),
+                                (loader.to_string(), channels, game_version),
                                 vec![hash.to_string()],
                             ))
                         }
@@ -1274,17 +1400,28 @@ impl CachedEntry {
                     format!("{}version_files/update", MODRINTH_API_URL);
                 let variations =
                     futures::future::try_join_all(filtered_keys.iter().map(
-                        |((loader, game_version), hashes)| {
+                        |((loader, channels, game_version), hashes)| {
+                            let mut body = serde_json::json!({
+                                "algorithm": "sha1",
+                                "hashes": hashes,
+                                "loaders": [loader],
+                                "version_types": channels
+                                    .iter()
+                                    .map(|x| x.as_str())
+                                    .collect::<Vec<_>>(),
+                            });
+                            // Without game versions, the newest version for any
+                            // game version is returned
+                            if let Some(game_version) = game_version {
+                                body["game_versions"] =
+                                    serde_json::json!([game_version]);
+                            }
+
                             fetch_json::<HashMap<String, Version>>(
                                 Method::POST,
                                 &version_update_url,
                                 None,
-                                Some(serde_json::json!({
-                                    "algorithm": "sha1",
-                                    "hashes": hashes,
-                                    "loaders": [loader],
-                                    "game_versions": [game_version]
-                                })),
+                                Some(body),
                                 fetch_semaphore,
                                 pool,
                             )
@@ -1294,7 +1431,7 @@ impl CachedEntry {
 
                 for (index, mut variation) in variations.into_iter().enumerate()
                 {
-                    let ((loader, game_version), hashes) =
+                    let ((loader, channels, game_version), hashes) =
                         &filtered_keys[index];
 
                     for hash in hashes {
@@ -1307,12 +1444,13 @@ impl CachedEntry {
                                 false,
                             ));
 
-                            println!("found update {hash} {game_version} {loader} {version_id}");
+                            tracing::debug!("found update {hash} {game_version:?} {loader} {version_id}");
                             vals.push((
                                 CacheValue::FileUpdate(CachedFileUpdate {
                                     hash: hash.clone(),
                                     game_version: game_version.clone(),
                                     loader: loader.clone(),
+                                    channels: channels.clone(),
                                     update_version_id: version_id,
                                 })
                                 .get_entry(),
@@ -1321,7 +1459,12 @@ impl CachedEntry {
                         } else {
                             vals.push((
                                 CacheValueType::FileUpdate.get_empty_entry(
-                                    format!("{hash}-{loader}-{game_version}"),
+                                    CachedFileUpdate::key(
+                                        hash,
+                                        loader,
+                                        channels,
+                                        game_version.as_deref(),
+                                    ),
                                 ),
                                 true,
                             ))
@@ -1369,9 +1512,80 @@ impl CachedEntry {
                 })
                 .collect()
             }
+            CacheValueType::ProjectVersions => {
+                let fetch_urls = keys
+                    .iter()
+                    .map(|x| {
+                        (
+                            x.key().to_string(),
+                            format!(
+                                "{MODRINTH_API_URL}project/{}/version",
+                                x.key()
+                            ),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                let results = futures::future::try_join_all(
+                    fetch_urls.iter().map(|(_, url)| {
+                        fetch_json::<Vec<Version>>(
+                            Method::GET,
+                            url,
+                            None,
+                            None,
+                            fetch_semaphore,
+                            pool,
+                        )
+                    }),
+                )
+                .await?;
+
+                let mut values = vec![];
+                for ((project_id, _), versions) in
+                    fetch_urls.into_iter().zip(results)
+                {
+                    for version in &versions {
+                        values.push((
+                            CacheValue::Version(version.clone()).get_entry(),
+                            false,
+                        ));
+                    }
+
+                    values.push((
+                        CacheValue::ProjectVersions(CachedProjectVersions {
+                            project_id,
+                            versions,
+                        })
+                        .get_entry(),
+                        true,
+                    ));
+                }
+
+                values
+            }
         })
     }
 
+    /// Gets the icons extracted from the manifests of cached files
+    pub(crate) async fn get_manifest_icons(
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<String>> {
+        let type_str = CacheValueType::FileHash.as_str();
+
+        let res = sqlx::query!(
+            r#"
+            SELECT json_extract(data, '$.manifest.icon') as "icon?: String"
+            FROM cache
+            WHERE data_type = $1
+            "#,
+            type_str,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(res.into_iter().filter_map(|x| x.icon).collect())
+    }
+
     async fn upsert_many(
         items: &[Self],
         exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
@@ -1416,14 +1630,24 @@ pub async fn cache_file_hash(
     let hash = if let Some(known_hash) = known_hash {
         known_hash.to_string()
     } else {
-        sha1_async(bytes).await?
+        sha1_async(bytes.clone()).await?
     };
 
+    // TODO: Replace state call here
+    let state = crate::State::get().await?;
+    let manifest = crate::util::manifest::read_from_bytes(
+        bytes,
+        &state.directories.caches_dir(),
+        &state.io_semaphore,
+    )
+    .await;
+
     CachedEntry::upsert_many(
         &[CacheValue::FileHash(CachedFileHash {
             path: format!("{}/{}", profile_path, path),
             size: size as u64,
             hash,
+            manifest,
         })
         .get_entry()],
         exec,
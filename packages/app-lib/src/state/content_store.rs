@@ -0,0 +1,379 @@
+//! Content-addressed store of project files, shared between profiles
+use crate::state::DirectoryInfo;
+use crate::util::fetch::{sha1_async, IoSemaphore};
+use crate::util::io;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// A profile file that was placed from the content store
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ContentRef {
+    pub profile_path: String,
+    /// Path of the file relative to the profile, without any `.disabled` suffix
+    pub project_path: String,
+    /// Sha1 of the file
+    pub hash: String,
+}
+
+// Disabling a project renames it, so references are tracked by the enabled path
+fn ref_path(project_path: &str) -> &str {
+    let project_path = project_path.trim_start_matches("./");
+    project_path
+        .strip_suffix(".disabled")
+        .unwrap_or(project_path)
+}
+
+impl ContentRef {
+    /// Path of a file in the content store
+    pub fn object_path(dirs: &DirectoryInfo, hash: &str) -> PathBuf {
+        dirs.content_store_dir()
+            .join(hash.get(..2).unwrap_or(hash))
+            .join(hash)
+    }
+
+    /// Reads a file from the content store, if it is there and intact
+    /// Stored files are hardlinked into profiles, so editing a profile's file
+    /// in place changes them too. Files that don't match their hash anymore
+    /// are deleted, to be stored again
+    pub async fn read_object(
+        dirs: &DirectoryInfo,
+        hash: &str,
+        io_semaphore: &IoSemaphore,
+    ) -> crate::Result<Option<bytes::Bytes>> {
+        let object_path = Self::object_path(dirs, hash);
+        if !object_path.exists() {
+            return Ok(None);
+        }
+
+        let _permit = io_semaphore.0.acquire().await?;
+        let bytes = bytes::Bytes::from(io::read(&object_path).await?);
+        if sha1_async(bytes.clone()).await? != hash {
+            tracing::warn!(
+                "Stored file {} does not match its hash, removing it",
+                object_path.display()
+            );
+            io::remove_file(&object_path).await?;
+            return Ok(None);
+        }
+
+        Ok(Some(bytes))
+    }
+
+    pub async fn get_from_profile(
+        profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<Self>> {
+        let results = sqlx::query_as!(
+            ContentRef,
+            "
+            SELECT profile_path, project_path, hash
+            FROM content_store_refs
+            WHERE profile_path = $1
+            ",
+            profile_path
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(results)
+    }
+
+    /// Number of profile files and update history entries currently pointing
+    /// to a file in the store
+    pub async fn count(
+        hash: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM content_store_refs WHERE hash = $1)
+                + (SELECT COUNT(*) FROM project_update_history WHERE hash = $1)
+                AS "count!: i64"
+            "#,
+            hash
+        )
+        .fetch_one(exec)
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn upsert(
+        &self,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            INSERT INTO content_store_refs (profile_path, project_path, hash)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (profile_path, project_path) DO UPDATE SET
+                hash = $3
+            ",
+            self.profile_path,
+            self.project_path,
+            self.hash,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes the reference of a profile file, returning the hash it pointed to
+    pub async fn remove(
+        profile_path: &str,
+        project_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Option<String>> {
+        let project_path = ref_path(project_path);
+
+        let hash = sqlx::query_scalar!(
+            "
+            DELETE FROM content_store_refs
+            WHERE profile_path = $1 AND project_path = $2
+            RETURNING hash
+            ",
+            profile_path,
+            project_path,
+        )
+        .fetch_optional(exec)
+        .await?;
+
+        Ok(hash)
+    }
+
+    /// Removes every reference of a profile, returning the hashes they pointed to
+    pub async fn remove_from_profile(
+        profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<String>> {
+        let hashes = sqlx::query_scalar!(
+            "
+            DELETE FROM content_store_refs
+            WHERE profile_path = $1
+            RETURNING hash
+            ",
+            profile_path,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(hashes)
+    }
+
+    /// Moves the file references of a profile to another profile path
+    pub async fn move_profile(
+        from_profile_path: &str,
+        to_profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            UPDATE content_store_refs SET profile_path = $2 WHERE profile_path = $1
+            ",
+            from_profile_path,
+            to_profile_path,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Places a file into a profile from the content store, adding it to the
+    /// store first if it is not there yet
+    /// `hash` must be the verified sha1 of `bytes`
+    #[tracing::instrument(skip(bytes, dirs, io_semaphore, exec))]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place<'a, E>(
+        profile_path: &str,
+        project_path: &str,
+        hash: &str,
+        bytes: &[u8],
+        profile_dir: &Path,
+        dirs: &DirectoryInfo,
+        io_semaphore: &IoSemaphore,
+        exec: E,
+    ) -> crate::Result<()>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Sqlite> + Copy,
+    {
+        let object_path = Self::object_path(dirs, hash);
+
+        {
+            let _permit = io_semaphore.0.acquire().await?;
+            if !object_path.exists() {
+                if let Some(parent) = object_path.parent() {
+                    io::create_dir_all(parent).await?;
+                }
+                io::write(&object_path, bytes).await?;
+            }
+        }
+
+        Self::link(
+            profile_path,
+            project_path,
+            hash,
+            profile_dir,
+            dirs,
+            io_semaphore,
+            exec,
+        )
+        .await
+    }
+
+    /// Adds a file from a profile to the content store without referencing it,
+    /// if it is not there yet. The caller must reference the stored file
+    /// before the next garbage collection
+    /// `hash` must be the sha1 of the file
+    #[tracing::instrument(skip(dirs, io_semaphore))]
+    pub async fn store_file(
+        hash: &str,
+        path: &Path,
+        dirs: &DirectoryInfo,
+        io_semaphore: &IoSemaphore,
+    ) -> crate::Result<()> {
+        let object_path = Self::object_path(dirs, hash);
+
+        let _permit = io_semaphore.0.acquire().await?;
+        if object_path.exists() {
+            return Ok(());
+        }
+
+        if let Some(parent) = object_path.parent() {
+            io::create_dir_all(parent).await?;
+        }
+        if let Err(err) = io::hard_link(path, &object_path).await {
+            tracing::debug!(
+                "Unable to hardlink {}, copying instead: {}",
+                path.display(),
+                err
+            );
+            io::copy(path, &object_path).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Places a file that is already in the content store into a profile
+    /// Hardlinks the file where possible, and copies it otherwise. Copies are
+    /// reflinked by the OS on filesystems that support it
+    #[tracing::instrument(skip(dirs, io_semaphore, exec))]
+    pub async fn link<'a, E>(
+        profile_path: &str,
+        project_path: &str,
+        hash: &str,
+        profile_dir: &Path,
+        dirs: &DirectoryInfo,
+        io_semaphore: &IoSemaphore,
+        exec: E,
+    ) -> crate::Result<()>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Sqlite> + Copy,
+    {
+        let object_path = Self::object_path(dirs, hash);
+        let dest = profile_dir.join(project_path);
+
+        {
+            let _permit = io_semaphore.0.acquire().await?;
+
+            if let Some(parent) = dest.parent() {
+                io::create_dir_all(parent).await?;
+            }
+            if dest.is_file() {
+                io::remove_file(&dest).await?;
+            }
+
+            if let Err(err) = io::hard_link(&object_path, &dest).await {
+                tracing::debug!(
+                    "Unable to hardlink {}, copying instead: {}",
+                    dest.display(),
+                    err
+                );
+                io::copy(&object_path, &dest).await?;
+            }
+        }
+
+        // Whatever this path pointed to before was replaced
+        if let Some(old_hash) =
+            Self::remove(profile_path, project_path, exec).await?
+        {
+            if old_hash != hash {
+                Self::collect_garbage(&[old_hash], dirs, exec).await?;
+            }
+        }
+
+        Self {
+            profile_path: profile_path.to_string(),
+            project_path: ref_path(project_path).to_string(),
+            hash: hash.to_string(),
+        }
+        .upsert(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// References a profile file that matches a file already in the content
+    /// store, without replacing it
+    pub async fn adopt(
+        profile_path: &str,
+        project_path: &str,
+        hash: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        Self {
+            profile_path: profile_path.to_string(),
+            project_path: ref_path(project_path).to_string(),
+            hash: hash.to_string(),
+        }
+        .upsert(exec)
+        .await
+    }
+
+    /// Drops the reference of a profile file, deleting the stored file if no
+    /// other profile uses it
+    #[tracing::instrument(skip(dirs, exec))]
+    pub async fn release<'a, E>(
+        profile_path: &str,
+        project_path: &str,
+        dirs: &DirectoryInfo,
+        exec: E,
+    ) -> crate::Result<()>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Sqlite> + Copy,
+    {
+        if let Some(hash) =
+            Self::remove(profile_path, project_path, exec).await?
+        {
+            Self::collect_garbage(&[hash], dirs, exec).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes the given files from the content store, if no profile file
+    /// points to them anymore
+    pub async fn collect_garbage<'a, E>(
+        hashes: &[String],
+        dirs: &DirectoryInfo,
+        exec: E,
+    ) -> crate::Result<()>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Sqlite> + Copy,
+    {
+        for hash in hashes {
+            if Self::count(hash, exec).await? > 0 {
+                continue;
+            }
+
+            let object_path = Self::object_path(dirs, hash);
+            if object_path.exists() {
+                io::remove_file(&object_path).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
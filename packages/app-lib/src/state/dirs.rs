@@ -8,6 +8,9 @@ pub const CACHES_FOLDER_NAME: &str = "caches";
 pub const LAUNCHER_LOGS_FOLDER_NAME: &str = "launcher_logs";
 pub const PROFILES_FOLDER_NAME: &str = "profiles";
 pub const METADATA_FOLDER_NAME: &str = "meta";
+pub const SNAPSHOTS_FOLDER_NAME: &str = "snapshots";
+pub const CONTENT_STORE_FOLDER_NAME: &str = "store";
+pub const TRASH_FOLDER_NAME: &str = "trash";
 
 #[derive(Debug)]
 pub struct DirectoryInfo {
@@ -144,6 +147,26 @@ impl DirectoryInfo {
         self.profiles_dir().join(profile_path).join("crash-reports")
     }
 
+    /// Get the directory containing profile snapshot archives
+    #[inline]
+    pub fn snapshots_dir(&self) -> PathBuf {
+        self.config_dir.join(SNAPSHOTS_FOLDER_NAME)
+    }
+
+    /// Get the content store directory, holding project files shared between
+    /// profiles. Kept next to the profiles so files can be hardlinked
+    #[inline]
+    pub fn content_store_dir(&self) -> PathBuf {
+        self.config_dir.join(CONTENT_STORE_FOLDER_NAME)
+    }
+
+    /// Get the trash directory, holding removed profiles and project files.
+    /// Kept next to the profiles so they can be moved instead of copied
+    #[inline]
+    pub fn trash_dir(&self) -> PathBuf {
+        self.config_dir.join(TRASH_FOLDER_NAME)
+    }
+
     #[inline]
     pub fn launcher_logs_dir() -> Option<PathBuf> {
         Self::get_initial_settings_dir()
@@ -267,6 +290,24 @@ impl DirectoryInfo {
                     io_semaphore,
                 )
                 .await?;
+                move_directory(
+                    &prev_dir.join(SNAPSHOTS_FOLDER_NAME),
+                    &move_dir.join(SNAPSHOTS_FOLDER_NAME),
+                    io_semaphore,
+                )
+                .await?;
+                move_directory(
+                    &prev_dir.join(CONTENT_STORE_FOLDER_NAME),
+                    &move_dir.join(CONTENT_STORE_FOLDER_NAME),
+                    io_semaphore,
+                )
+                .await?;
+                move_directory(
+                    &prev_dir.join(TRASH_FOLDER_NAME),
+                    &move_dir.join(TRASH_FOLDER_NAME),
+                    io_semaphore,
+                )
+                .await?;
 
                 let java_versions = JavaVersion::get_all(exec).await?;
                 for (_, mut java_version) in java_versions {
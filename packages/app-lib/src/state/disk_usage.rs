@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Disk usage computed so far, kept until the file watcher reports changes
+pub type DiskUsageCache = RwLock<DiskUsageCacheInner>;
+
+#[derive(Default)]
+pub struct DiskUsageCacheInner {
+    pub profiles: HashMap<String, ProfileDiskUsage>,
+    pub shared: Option<SharedDiskUsage>,
+}
+
+/// Disk usage of every profile and of the launcher's shared data, in bytes
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DiskUsage {
+    pub profiles: Vec<ProfileDiskUsage>,
+    pub shared: SharedDiskUsage,
+    pub total: u64,
+}
+
+/// Disk usage of a profile's folder, in bytes
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ProfileDiskUsage {
+    pub profile_path: String,
+    pub mods: u64,
+    pub resource_packs: u64,
+    pub shader_packs: u64,
+    pub saves: u64,
+    pub logs: u64,
+    pub crash_reports: u64,
+    pub screenshots: u64,
+    /// Everything else in the profile's folder
+    pub other: u64,
+    pub total: u64,
+}
+
+/// Disk usage of the data shared between profiles, in bytes
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SharedDiskUsage {
+    pub versions: u64,
+    pub libraries: u64,
+    pub assets: u64,
+    pub natives: u64,
+    pub java_versions: u64,
+    pub caches: u64,
+    pub total: u64,
+}
@@ -1,9 +1,11 @@
 use crate::event::emit::{emit_profile, emit_warning};
 use crate::event::ProfilePayloadType;
+use crate::profile::worlds::SAVES_FOLDER_NAME;
 use crate::state::{DirectoryInfo, ProfileInstallStage, ProjectType};
 use futures::{channel::mpsc::channel, SinkExt, StreamExt};
 use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use std::path::Path;
 use std::time::Duration;
 use tokio::sync::RwLock;
 
@@ -30,6 +32,7 @@ pub async fn init_watcher() -> crate::Result<FileWatcher> {
             match res {
                 Ok(events) => {
                     let mut visited_profiles = Vec::new();
+                    let mut new_world_profiles = Vec::new();
 
                     events.iter().for_each(|e| {
                         let mut profile_path = None;
@@ -51,6 +54,17 @@ pub async fn init_watcher() -> crate::Result<FileWatcher> {
                         }
 
                         if let Some(profile_path) = profile_path {
+                            // New worlds have datapacks/ folders to watch
+                            if e.path
+                                .parent()
+                                .and_then(|x| x.file_name())
+                                .is_some_and(|x| x == SAVES_FOLDER_NAME)
+                                && !new_world_profiles.contains(&profile_path)
+                            {
+                                new_world_task(profile_path.to_string());
+                                new_world_profiles.push(profile_path.clone());
+                            }
+
                             if e.path
                                 .components()
                                 .any(|x| x.as_os_str() == "crash-reports")
@@ -59,11 +73,20 @@ pub async fn init_watcher() -> crate::Result<FileWatcher> {
                                     .map(|x| x == "txt")
                                     .unwrap_or(false)
                             {
+                                let path = profile_path.to_string();
+                                tokio::spawn(async move {
+                                    let _ =
+                                        crate::disk_usage::invalidate(&path)
+                                            .await;
+                                });
                                 crash_task(profile_path.to_string());
                             } else if !visited_profiles.contains(&profile_path)
                             {
                                 let path = profile_path.to_string();
                                 tokio::spawn(async move {
+                                    let _ =
+                                        crate::disk_usage::invalidate(&path)
+                                            .await;
                                     let _ = emit_profile(
                                         &path,
                                         ProfilePayloadType::Synced,
@@ -126,9 +149,56 @@ pub(crate) async fn watch_profile(
         watcher.watcher().watch(&path, RecursiveMode::Recursive)?;
     }
 
+    watch_world_datapacks(&profile_path, watcher).await?;
+
     Ok(())
 }
 
+/// Watches a profile's saves/ folder for new worlds, and the datapacks/
+/// folder of each world. Worlds aren't watched as a whole, as the game
+/// writes to them constantly while they are played
+pub(crate) async fn watch_world_datapacks(
+    profile_dir: &Path,
+    watcher: &FileWatcher,
+) -> crate::Result<()> {
+    let saves_dir = profile_dir.join(SAVES_FOLDER_NAME);
+    if !saves_dir.exists() {
+        crate::util::io::create_dir_all(&saves_dir).await?;
+    }
+
+    let mut watcher = watcher.write().await;
+    watcher
+        .watcher()
+        .watch(&saves_dir, RecursiveMode::NonRecursive)?;
+
+    if let Ok(worlds) = std::fs::read_dir(&saves_dir) {
+        for world in worlds.flatten() {
+            let path = world.path().join(ProjectType::DataPack.get_folder());
+            if path.is_dir() {
+                watcher.watcher().watch(&path, RecursiveMode::Recursive)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn new_world_task(path: String) {
+    tokio::task::spawn(async move {
+        let res = async {
+            let state = crate::State::get().await?;
+            let profile_dir = state.directories.profiles_dir().join(&path);
+
+            watch_world_datapacks(&profile_dir, &state.file_watcher).await
+        }
+        .await;
+
+        if let Err(err) = res {
+            tracing::warn!("Unable to watch the worlds of {path}: {err}")
+        }
+    });
+}
+
 fn crash_task(path: String) {
     tokio::task::spawn(async move {
         let res = async {
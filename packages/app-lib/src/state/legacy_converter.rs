@@ -5,7 +5,7 @@ use crate::state;
 use crate::state::{
     Credentials, DefaultPage, DeviceToken, DeviceTokenKey, DeviceTokenPair,
     Hooks, LinkedData, MemorySettings, ModrinthCredentials, Profile,
-    ProfileInstallStage, Theme, WindowSize,
+    ProfileInstallStage, ProfileType, Theme, WindowSize,
 };
 use crate::util::fetch::{read_json, IoSemaphore};
 use chrono::{DateTime, Utc};
@@ -205,6 +205,7 @@ where
                                     ProfileInstallStage::NotInstalled
                                 }
                             },
+                            profile_type: ProfileType::Client,
                             name: profile.metadata.name,
                             icon_path: profile.metadata.icon.map(|x| {
                                 x.replace(
@@ -288,6 +289,8 @@ where
                                     .hooks
                                     .and_then(|x| x.post_exit),
                             },
+                            default_options: None,
+                            sync_options: false,
                         }
                         .upsert(exec)
                         .await?;
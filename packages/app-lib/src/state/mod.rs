@@ -35,6 +35,30 @@ pub use self::minecraft_auth::*;
 mod cache;
 pub use self::cache::*;
 
+mod snapshots;
+pub use self::snapshots::*;
+
+mod content_store;
+pub use self::content_store::*;
+
+mod update_history;
+pub use self::update_history::*;
+
+mod update_policy;
+pub use self::update_policy::*;
+
+mod mod_sets;
+pub use self::mod_sets::*;
+
+mod trash;
+pub use self::trash::*;
+
+mod shared_options;
+pub use self::shared_options::*;
+
+mod disk_usage;
+pub use self::disk_usage::*;
+
 mod db;
 pub mod fs_watcher;
 mod mr_auth;
@@ -64,6 +88,11 @@ pub struct State {
     pub(crate) pool: SqlitePool,
 
     pub(crate) file_watcher: FileWatcher,
+
+    pub(crate) disk_usage: DiskUsageCache,
+
+    /// Console inputs of the running processes that accept commands
+    pub(crate) process_inputs: ProcessInputs,
 }
 
 impl State {
@@ -73,6 +102,10 @@ impl State {
             .await?;
 
         Process::garbage_collect(&state.pool).await?;
+        // Purging the trash is housekeeping, and shouldn't stop the launcher
+        if let Err(err) = crate::trash::purge_expired().await {
+            tracing::warn!("Unable to purge the trash: {err}");
+        }
 
         Ok(())
     }
@@ -145,6 +178,8 @@ impl State {
             discord_rpc,
             pool,
             file_watcher,
+            disk_usage: DiskUsageCache::default(),
+            process_inputs: ProcessInputs::default(),
         }))
     }
 }
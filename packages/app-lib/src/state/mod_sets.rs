@@ -0,0 +1,197 @@
+use crate::state::ProfileFile;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+// A named group of projects of a profile that are enabled or disabled together
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModSet {
+    pub id: String,
+    pub profile_path: String,
+    pub name: String,
+    pub created: DateTime<Utc>,
+
+    /// Whether activating the set disables the profile's mods that are not in it
+    pub exclusive: bool,
+    pub entries: Vec<ModSetEntry>,
+}
+
+/// A project of a mod set, and whether activating the set enables or disables it
+/// Projects are matched by project id, or by hash for files not on Modrinth, so
+/// entries keep matching when files are renamed or updated
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModSetEntry {
+    pub project_id: Option<String>,
+    pub hash: Option<String>,
+    pub enabled: bool,
+}
+
+impl ModSetEntry {
+    pub fn matches(&self, file: &ProfileFile) -> bool {
+        match (&self.project_id, &file.metadata) {
+            (Some(project_id), Some(metadata)) => {
+                &metadata.project_id == project_id
+            }
+            (Some(_), None) => false,
+            (None, _) => self.hash.as_ref() == Some(&file.hash),
+        }
+    }
+}
+
+struct ModSetQueryResult {
+    id: String,
+    profile_path: String,
+    name: String,
+    created: i64,
+    exclusive: i64,
+    entries: serde_json::Value,
+}
+
+impl From<ModSetQueryResult> for ModSet {
+    fn from(x: ModSetQueryResult) -> Self {
+        ModSet {
+            id: x.id,
+            profile_path: x.profile_path,
+            name: x.name,
+            created: Utc
+                .timestamp_opt(x.created, 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+            exclusive: x.exclusive == 1,
+            entries: serde_json::from_value(x.entries).unwrap_or_default(),
+        }
+    }
+}
+
+macro_rules! select_mod_sets_with_predicate {
+    ($predicate:tt, $param:ident) => {
+        sqlx::query_as!(
+            ModSetQueryResult,
+            r#"
+            SELECT
+                id, profile_path, name, created,
+                exclusive,
+                json(entries) as "entries!: serde_json::Value"
+            FROM mod_sets
+            "#
+                + $predicate,
+            $param
+        )
+    };
+}
+
+impl ModSet {
+    pub async fn get(
+        id: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Option<Self>> {
+        let res = select_mod_sets_with_predicate!("WHERE id = $1", id)
+            .fetch_optional(exec)
+            .await?;
+
+        Ok(res.map(|r| r.into()))
+    }
+
+    /// Gets all mod sets of a profile, oldest first
+    pub async fn get_from_profile(
+        profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<Self>> {
+        let results = select_mod_sets_with_predicate!(
+            "WHERE profile_path = $1 ORDER BY created, name",
+            profile_path
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(results.into_iter().map(|r| r.into()).collect())
+    }
+
+    pub async fn upsert(
+        &self,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        let created = self.created.timestamp();
+        let entries = serde_json::to_string(&self.entries)?;
+
+        sqlx::query!(
+            "
+            INSERT INTO mod_sets (
+                id, profile_path, name, created,
+                exclusive, entries
+            )
+            VALUES (
+                $1, $2, $3, $4,
+                $5, jsonb($6)
+            )
+            ON CONFLICT (id) DO UPDATE SET
+                profile_path = $2,
+                name = $3,
+                created = $4,
+
+                exclusive = $5,
+                entries = jsonb($6)
+            ",
+            self.id,
+            self.profile_path,
+            self.name,
+            created,
+            self.exclusive,
+            entries,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove(
+        id: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            DELETE FROM mod_sets WHERE id = $1
+            ",
+            id,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_from_profile(
+        profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            DELETE FROM mod_sets WHERE profile_path = $1
+            ",
+            profile_path,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Moves the mod sets of a profile to another profile path
+    pub async fn move_profile(
+        from_profile_path: &str,
+        to_profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            UPDATE mod_sets SET profile_path = $2 WHERE profile_path = $1
+            ",
+            from_profile_path,
+            to_profile_path,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+}
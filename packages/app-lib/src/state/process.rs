@@ -1,7 +1,11 @@
 use chrono::{DateTime, Utc};
+use dashmap::DashMap;
 use serde::Deserialize;
 use serde::Serialize;
-use tokio::process::Command;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::process::{ChildStdin, Command};
+use tokio::sync::Mutex;
 
 use crate::event::emit::emit_process;
 use crate::event::ProcessPayloadType;
@@ -18,6 +22,10 @@ pub struct Process {
     pub post_exit_command: Option<String>,
 }
 
+/// Console inputs of the processes launched with one since the launcher
+/// started, by pid
+pub type ProcessInputs = DashMap<i64, Arc<Mutex<ChildStdin>>>;
+
 macro_rules! select_process_with_predicate {
     ($predicate:tt, $param:ident) => {
         sqlx::query_as!(
@@ -74,7 +82,7 @@ impl Process {
         post_exit_command: Option<String>, // Command to run after minecraft.
         exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
     ) -> crate::Result<Self> {
-        let mc_proc = mc_command.spawn().map_err(IOError::from)?;
+        let mut mc_proc = mc_command.spawn().map_err(IOError::from)?;
 
         let pid = mc_proc.id().ok_or_else(|| {
             crate::ErrorKind::LauncherError(
@@ -114,6 +122,13 @@ impl Process {
         };
         process.upsert(exec).await?;
 
+        if let Some(stdin) = mc_proc.stdin.take() {
+            let state = crate::State::get().await?;
+            state
+                .process_inputs
+                .insert(process.pid, Arc::new(Mutex::new(stdin)));
+        }
+
         tokio::spawn(process.clone().sequential_process_manager());
 
         emit_process(
@@ -201,6 +216,25 @@ impl Process {
         let _ = state.discord_rpc.clear_to_default(true).await;
 
         Self::remove(self.pid as u32, &state.pool).await?;
+        state.process_inputs.remove(&self.pid);
+
+        // The game writes worlds, logs and screenshots the watcher doesn't see
+        crate::api::disk_usage::invalidate(&self.profile_path).await?;
+
+        // Takes the options the game changed back into the shared options
+        if let Ok(Some(profile)) = profile::get(&self.profile_path).await {
+            if profile.sync_options {
+                if let Err(e) =
+                    crate::api::shared_options::pull(&self.profile_path).await
+                {
+                    tracing::warn!(
+                        "Failed to pull shared options from profile {}: {}",
+                        &self.profile_path,
+                        e
+                    );
+                }
+            }
+        }
 
         // If in tauri, window should show itself again after process exists if it was hidden
         #[cfg(feature = "tauri")]
@@ -269,6 +303,32 @@ impl Process {
         Ok(())
     }
 
+    /// Sends a command to the process's console, such as a server's. Only
+    /// processes launched with their input open since the launcher started
+    /// accept commands
+    pub async fn send_command(&self, command: &str) -> crate::Result<()> {
+        let state = crate::State::get().await?;
+        let input = state
+            .process_inputs
+            .get(&self.pid)
+            .map(|x| x.clone())
+            .ok_or_else(|| {
+                crate::ErrorKind::LauncherError(format!(
+                    "Process {} does not accept commands",
+                    self.pid
+                ))
+            })?;
+
+        let mut input = input.lock().await;
+        input
+            .write_all(format!("{}\n", command.trim_end()).as_bytes())
+            .await
+            .map_err(IOError::from)?;
+        input.flush().await.map_err(IOError::from)?;
+
+        Ok(())
+    }
+
     pub async fn kill(&self) -> crate::Result<()> {
         let mut system = sysinfo::System::new();
         if system.refresh_process(sysinfo::Pid::from_u32(self.pid as u32)) {
@@ -1,5 +1,9 @@
 use super::settings::{Hooks, MemorySettings, WindowSize};
-use crate::state::{cache_file_hash, CacheBehaviour, CachedEntry};
+use crate::profile::worlds::SAVES_FOLDER_NAME;
+use crate::state::{
+    cache_file_hash, CacheBehaviour, CachedEntry, CachedFileUpdate, ContentRef,
+    DirectoryInfo, FileManifest, UpdateMode, UpdatePolicy,
+};
 use crate::util;
 use crate::util::fetch::{write_cached_icon, FetchSemaphore, IoSemaphore};
 use crate::util::io::{self};
@@ -16,6 +20,8 @@ use std::path::{Path, PathBuf};
 pub struct Profile {
     pub path: String,
     pub install_stage: ProfileInstallStage,
+    #[serde(default)]
+    pub profile_type: ProfileType,
 
     pub name: String,
     pub icon_path: Option<String>,
@@ -43,6 +49,14 @@ pub struct Profile {
     pub force_fullscreen: Option<bool>,
     pub game_resolution: Option<WindowSize>,
     pub hooks: Hooks,
+
+    /// options.txt options set on the first launch without an options.txt
+    #[serde(default)]
+    pub default_options: Option<Vec<(String, String)>>,
+    /// Whether the profile shares the options of the categories in the
+    /// settings with the other profiles that do
+    #[serde(default)]
+    pub sync_options: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
@@ -79,6 +93,39 @@ impl ProfileInstallStage {
     }
 }
 
+/// Whether a profile runs the game, or a dedicated server for it
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileType {
+    #[default]
+    Client,
+    Server,
+}
+
+impl ProfileType {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Self::Client => "client",
+            Self::Server => "server",
+        }
+    }
+
+    pub fn from_string(val: &str) -> Self {
+        match val {
+            "client" => Self::Client,
+            "server" => Self::Server,
+            _ => Self::Client,
+        }
+    }
+
+    /// Side of the game the profile runs, as loader processors name it
+    pub fn side(&self) -> &'static str {
+        self.as_str()
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct LinkedData {
     pub project_id: String,
@@ -128,6 +175,12 @@ impl ModLoader {
             _ => Self::Vanilla,
         }
     }
+
+    /// Whether mods built for the given loader run on this loader
+    pub fn can_load(&self, loader: &str) -> bool {
+        // Quilt can load Fabric mods
+        loader == self.as_str() || (*self == Self::Quilt && loader == "fabric")
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -136,8 +189,15 @@ pub struct ProfileFile {
     pub file_name: String,
     pub size: u64,
     pub metadata: Option<FileMetadata>,
+    /// Metadata read from inside the file, available for files not on Modrinth
+    #[serde(default)]
+    pub manifest: Option<FileManifest>,
     pub update_version_id: Option<String>,
     pub project_type: ProjectType,
+    /// Folder name of the world the file is in, for datapacks in a world's
+    /// own datapacks/ folder
+    #[serde(default)]
+    pub world: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -206,6 +266,30 @@ impl ProjectType {
         }
     }
 
+    /// Folder of the projects of this type, relative to the profile.
+    /// Datapacks may be put in a world's own datapacks/ folder, which is the
+    /// only one the game loads them from without a mod
+    pub fn get_world_folder(&self, world: Option<&str>) -> String {
+        match (self, world) {
+            (ProjectType::DataPack, Some(world)) => {
+                format!("{SAVES_FOLDER_NAME}/{world}/{}", self.get_folder())
+            }
+            _ => self.get_folder().to_string(),
+        }
+    }
+
+    /// The world a project is in, from its path relative to the profile
+    pub fn get_world(project_path: &str) -> Option<&str> {
+        let (world, path) = project_path
+            .strip_prefix(SAVES_FOLDER_NAME)?
+            .strip_prefix('/')?
+            .split_once('/')?;
+
+        path.strip_prefix(ProjectType::DataPack.get_folder())?
+            .starts_with('/')
+            .then_some(world)
+    }
+
     pub fn iterator() -> impl Iterator<Item = ProjectType> {
         [
             ProjectType::Mod,
@@ -221,6 +305,7 @@ impl ProjectType {
 struct ProfileQueryResult {
     path: String,
     install_stage: String,
+    profile_type: String,
     name: String,
     icon_path: Option<String>,
     game_version: String,
@@ -245,6 +330,8 @@ struct ProfileQueryResult {
     override_hook_pre_launch: Option<String>,
     override_hook_wrapper: Option<String>,
     override_hook_post_exit: Option<String>,
+    default_options: Option<serde_json::Value>,
+    sync_options: i64,
 }
 
 impl TryFrom<ProfileQueryResult> for Profile {
@@ -254,6 +341,7 @@ impl TryFrom<ProfileQueryResult> for Profile {
         Ok(Profile {
             path: x.path,
             install_stage: ProfileInstallStage::from_str(&x.install_stage),
+            profile_type: ProfileType::from_string(&x.profile_type),
             name: x.name,
             icon_path: x.icon_path,
             game_version: x.game_version,
@@ -310,6 +398,10 @@ impl TryFrom<ProfileQueryResult> for Profile {
                 wrapper: x.override_hook_wrapper,
                 post_exit: x.override_hook_post_exit,
             },
+            default_options: x
+                .default_options
+                .and_then(|x| serde_json::from_value(x).ok()),
+            sync_options: x.sync_options == 1,
         })
     }
 }
@@ -320,7 +412,7 @@ macro_rules! select_profiles_with_predicate {
             ProfileQueryResult,
             r#"
             SELECT
-                path, install_stage, name, icon_path,
+                path, install_stage, profile_type, name, icon_path,
                 game_version, mod_loader, mod_loader_version,
                 json(groups) as "groups!: serde_json::Value",
                 linked_project_id, linked_version_id, locked,
@@ -329,7 +421,9 @@ macro_rules! select_profiles_with_predicate {
                 override_java_path,
                 json(override_extra_launch_args) as "override_extra_launch_args!: serde_json::Value", json(override_custom_env_vars) as "override_custom_env_vars!: serde_json::Value",
                 override_mc_memory_max, override_mc_force_fullscreen, override_mc_game_resolution_x, override_mc_game_resolution_y,
-                override_hook_pre_launch, override_hook_wrapper, override_hook_post_exit
+                override_hook_pre_launch, override_hook_wrapper, override_hook_post_exit,
+                json(default_options) as "default_options: serde_json::Value",
+                sync_options
             FROM profiles
             "#
                 + $predicate,
@@ -383,6 +477,7 @@ impl Profile {
         exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
     ) -> crate::Result<()> {
         let install_stage = self.install_stage.as_str();
+        let profile_type = self.profile_type.as_str();
         let mod_loader = self.loader.as_str();
 
         let groups = serde_json::to_string(&self.groups)?;
@@ -407,6 +502,11 @@ impl Profile {
 
         let extra_launch_args = serde_json::to_string(&self.extra_launch_args)?;
         let custom_env_vars = serde_json::to_string(&self.custom_env_vars)?;
+        let default_options = self
+            .default_options
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
 
         sqlx::query!(
             "
@@ -419,7 +519,8 @@ impl Profile {
                 submitted_time_played, recent_time_played,
                 override_java_path, override_extra_launch_args, override_custom_env_vars,
                 override_mc_memory_max, override_mc_force_fullscreen, override_mc_game_resolution_x, override_mc_game_resolution_y,
-                override_hook_pre_launch, override_hook_wrapper, override_hook_post_exit
+                override_hook_pre_launch, override_hook_wrapper, override_hook_post_exit,
+                profile_type, default_options, sync_options
             )
             VALUES (
                 $1, $2, $3, $4,
@@ -430,7 +531,8 @@ impl Profile {
                 $15, $16,
                 $17, jsonb($18), jsonb($19),
                 $20, $21, $22, $23,
-                $24, $25, $26
+                $24, $25, $26,
+                $27, jsonb($28), $29
             )
             ON CONFLICT (path) DO UPDATE SET
                 install_stage = $2,
@@ -464,7 +566,11 @@ impl Profile {
 
                 override_hook_pre_launch = $24,
                 override_hook_wrapper = $25,
-                override_hook_post_exit = $26
+                override_hook_post_exit = $26,
+
+                profile_type = $27,
+                default_options = jsonb($28),
+                sync_options = $29
             ",
             self.path,
             install_stage,
@@ -492,6 +598,9 @@ impl Profile {
             self.hooks.pre_launch,
             self.hooks.wrapper,
             self.hooks.post_exit,
+            profile_type,
+            default_options,
+            self.sync_options,
         )
             .execute(exec)
             .await?;
@@ -536,6 +645,32 @@ impl Profile {
         Ok(())
     }
 
+    /// Folders of a profile that hold projects, with the world of each
+    /// world's own datapacks folder
+    pub(crate) fn project_folders(
+        profile_dir: &Path,
+    ) -> crate::Result<Vec<(ProjectType, Option<String>)>> {
+        // Worlds' own datapacks are projects of the profile too
+        let mut folders = ProjectType::iterator()
+            .map(|x| (x, None))
+            .collect::<Vec<_>>();
+        let saves_dir = profile_dir.join(SAVES_FOLDER_NAME);
+        if saves_dir.exists() {
+            for world in std::fs::read_dir(&saves_dir)
+                .map_err(|e| io::IOError::with_path(e, &saves_dir))?
+            {
+                let world = world.map_err(io::IOError::from)?.path();
+                if let Some(world) = world.file_name().and_then(|x| x.to_str())
+                {
+                    folders
+                        .push((ProjectType::DataPack, Some(world.to_string())));
+                }
+            }
+        }
+
+        Ok(folders)
+    }
+
     pub async fn get_projects(
         &self,
         cache_behaviour: Option<CacheBehaviour>,
@@ -548,15 +683,16 @@ impl Profile {
             path: String,
             file_name: String,
             project_type: ProjectType,
+            world: Option<String>,
             size: u64,
             cache_key: String,
         }
 
         let mut keys = vec![];
 
-        for project_type in ProjectType::iterator() {
-            let folder = project_type.get_folder();
-            let path = path.join(folder);
+        for (project_type, world) in Self::project_folders(&path)? {
+            let folder = project_type.get_world_folder(world.as_deref());
+            let path = path.join(&folder);
 
             if path.exists() {
                 for subdirectory in std::fs::read_dir(&path)
@@ -581,6 +717,7 @@ impl Profile {
                                 ),
                                 file_name: file_name.to_string(),
                                 project_type,
+                                world: world.clone(),
                                 size: file_size,
                                 cache_key: format!(
                                     "{file_size}-{}/{folder}/{file_name}",
@@ -601,36 +738,81 @@ impl Profile {
         )
         .await?;
 
-        let file_updates = file_hashes
+        let file_hashes_ref =
+            file_hashes.iter().map(|x| &*x.hash).collect::<Vec<_>>();
+        let mut file_info = CachedEntry::get_file_many(
+            &file_hashes_ref,
+            cache_behaviour,
+            pool,
+            fetch_semaphore,
+        )
+        .await?;
+
+        // Updates follow the policy of the project each file belongs to
+        let policies = UpdatePolicy::get_from_profile(&self.path, pool).await?;
+        let file_updates = file_info
             .iter()
-            .map(|x| {
-                format!(
-                    "{}-{}-{}",
-                    x.hash,
+            .filter_map(|file| {
+                let policy = policies
+                    .iter()
+                    .find(|x| x.project_id == file.project_id)
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        UpdatePolicy::default_for(&self.path, &file.project_id)
+                    });
+
+                let game_version = match policy.mode {
+                    UpdateMode::Pin => return None,
+                    UpdateMode::Compatible => Some(&*self.game_version),
+                    UpdateMode::Follow => None,
+                };
+
+                Some(CachedFileUpdate::key(
+                    &file.hash,
                     self.loader.as_str(),
-                    self.game_version
-                )
+                    &policy.channels,
+                    game_version,
+                ))
             })
             .collect::<Vec<_>>();
 
-        let file_hashes_ref =
-            file_hashes.iter().map(|x| &*x.hash).collect::<Vec<_>>();
         let file_updates_ref =
             file_updates.iter().map(|x| &**x).collect::<Vec<_>>();
-        let (mut file_info, file_updates) = tokio::try_join!(
-            CachedEntry::get_file_many(
-                &file_hashes_ref,
-                cache_behaviour,
-                pool,
-                fetch_semaphore,
-            ),
-            CachedEntry::get_file_update_many(
-                &file_updates_ref,
-                cache_behaviour,
-                pool,
-                fetch_semaphore,
+        let file_updates = CachedEntry::get_file_update_many(
+            &file_updates_ref,
+            cache_behaviour,
+            pool,
+            fetch_semaphore,
+        )
+        .await?;
+
+        // Restricted release channels can offer an older version than the
+        // installed one, which isn't an update
+        let version_ids = file_info
+            .iter()
+            .map(|x| &*x.version_id)
+            .chain(file_updates.iter().map(|x| &*x.update_version_id))
+            .collect::<Vec<_>>();
+        let versions = CachedEntry::get_version_many(
+            &version_ids,
+            cache_behaviour,
+            pool,
+            fetch_semaphore,
+        )
+        .await?;
+        let is_newer = |version_id: &str, update_version_id: &str| {
+            let date_published = |id: &str| {
+                versions
+                    .iter()
+                    .find(|x| x.id == id)
+                    .map(|x| x.date_published)
+            };
+
+            matches!(
+                (date_published(version_id), date_published(update_version_id)),
+                (Some(current), Some(update)) if update > current
             )
-        )?;
+        };
 
         let files = DashMap::new();
 
@@ -645,7 +827,9 @@ impl Profile {
 
                 let path = format!(
                     "{}/{}",
-                    initial_file.project_type.get_folder(),
+                    initial_file
+                        .project_type
+                        .get_world_folder(initial_file.world.as_deref()),
                     initial_file.file_name
                 );
 
@@ -655,7 +839,9 @@ impl Profile {
                     .map(|x| x.update_version_id.clone())
                 {
                     if let Some(metadata) = &file {
-                        if metadata.version_id != update {
+                        if metadata.version_id != update
+                            && is_newer(&metadata.version_id, &update)
+                        {
                             Some(update)
                         } else {
                             None
@@ -669,6 +855,7 @@ impl Profile {
 
                 let file = ProfileFile {
                     update_version_id,
+                    manifest: hash.manifest,
                     hash: hash.hash,
                     file_name: initial_file.file_name,
                     size: initial_file.size,
@@ -677,6 +864,7 @@ impl Profile {
                         version_id: x.version_id,
                     }),
                     project_type: initial_file.project_type,
+                    world: initial_file.world,
                 };
                 files.insert(path, file);
             }
@@ -685,10 +873,13 @@ impl Profile {
         Ok(files)
     }
 
+    /// Installs a version's primary file. Datapacks go into the given world's
+    /// datapacks/ folder, or the profile's own without one
     #[tracing::instrument(skip(pool))]
     pub async fn add_project_version(
         profile_path: &str,
         version_id: &str,
+        world: Option<&str>,
         pool: &SqlitePool,
         fetch_semaphore: &FetchSemaphore,
         io_semaphore: &IoSemaphore,
@@ -715,20 +906,30 @@ impl Profile {
             .into());
         };
 
-        let bytes = util::fetch::fetch(
-            &file.url,
-            file.hashes.get("sha1").map(|x| &**x),
-            fetch_semaphore,
-            pool,
-        )
-        .await?;
+        let hash = file.hashes.get("sha1").map(|x| &**x);
+
+        // Files already in the content store don't need to be downloaded again
+        let state = crate::State::get().await?;
+        let stored = if let Some(hash) = hash {
+            ContentRef::read_object(&state.directories, hash, io_semaphore)
+                .await?
+        } else {
+            None
+        };
+
+        let bytes = if let Some(bytes) = stored {
+            bytes
+        } else {
+            util::fetch::fetch(&file.url, hash, fetch_semaphore, pool).await?
+        };
 
         let path = Self::add_project_bytes(
             profile_path,
             &file.filename,
             bytes,
-            file.hashes.get("sha1").map(|x| &**x),
+            hash,
             ProjectType::get_from_loaders(version.loaders.clone()),
+            world,
             io_semaphore,
             pool,
         )
@@ -737,15 +938,16 @@ impl Profile {
     }
 
     #[tracing::instrument(skip(bytes))]
-
+    #[allow(clippy::too_many_arguments)]
     pub async fn add_project_bytes(
         profile_path: &str,
         file_name: &str,
         bytes: bytes::Bytes,
         hash: Option<&str>,
         project_type: Option<ProjectType>,
+        world: Option<&str>,
         io_semaphore: &IoSemaphore,
-        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite> + Copy,
     ) -> crate::Result<String> {
         let project_type = if let Some(project_type) = project_type {
             project_type
@@ -783,13 +985,30 @@ impl Profile {
 
         let path = crate::api::profile::get_full_path(profile_path).await?;
         let project_path =
-            format!("{}/{}", project_type.get_folder(), file_name);
+            format!("{}/{}", project_type.get_world_folder(world), file_name);
 
         cache_file_hash(bytes.clone(), profile_path, &project_path, hash, exec)
             .await?;
 
-        util::fetch::write(&path.join(&project_path), &bytes, io_semaphore)
+        // Files with a known hash are shared with other profiles through the
+        // content store
+        if let Some(hash) = hash {
+            let state = crate::State::get().await?;
+            ContentRef::place(
+                profile_path,
+                &project_path,
+                hash,
+                &bytes,
+                &path,
+                &state.directories,
+                io_semaphore,
+                exec,
+            )
             .await?;
+        } else {
+            util::fetch::write(&path.join(&project_path), &bytes, io_semaphore)
+                .await?;
+        }
 
         Ok(project_path)
     }
@@ -813,16 +1032,20 @@ impl Profile {
         Ok(new_path)
     }
 
-    #[tracing::instrument]
+    #[tracing::instrument(skip(dirs, pool))]
     pub async fn remove_project(
         profile_path: &str,
         project_path: &str,
+        dirs: &DirectoryInfo,
+        pool: &SqlitePool,
     ) -> crate::Result<()> {
         if let Ok(path) = crate::api::profile::get_full_path(profile_path).await
         {
             io::remove_file(path.join(project_path)).await?;
         }
 
+        ContentRef::release(profile_path, project_path, dirs, pool).await?;
+
         Ok(())
     }
 }
@@ -1,4 +1,5 @@
 //! Theseus settings file
+use crate::state::SharedOptionCategory;
 use serde::{Deserialize, Serialize};
 
 // Types
@@ -28,6 +29,20 @@ pub struct Settings {
     pub hide_on_process_start: bool,
     pub hooks: Hooks,
 
+    /// Number of project updates per profile that can be rolled back
+    pub update_history_generations: u32,
+    /// Days removed profiles and projects are kept in the trash. If 0, they
+    /// are deleted right away
+    pub trash_retention_days: u32,
+
+    /// Base URL of the CurseForge API, used to resolve the files of
+    /// CurseForge modpacks
+    pub curseforge_api_url: String,
+    pub curseforge_api_key: Option<String>,
+
+    /// Game options shared by the profiles that sync options
+    pub shared_option_categories: Vec<SharedOptionCategory>,
+
     pub custom_dir: Option<String>,
     pub prev_custom_dir: Option<String>,
     pub migrated: bool,
@@ -47,6 +62,9 @@ impl Settings {
                 json(extra_launch_args) extra_launch_args, json(custom_env_vars) custom_env_vars,
                 mc_memory_max, mc_force_fullscreen, mc_game_resolution_x, mc_game_resolution_y, hide_on_process_start,
                 hook_pre_launch, hook_wrapper, hook_post_exit,
+                update_history_generations, trash_retention_days,
+                curseforge_api_url, curseforge_api_key,
+                json(shared_option_categories) shared_option_categories,
                 custom_dir, prev_custom_dir, migrated
             FROM settings
             "
@@ -88,6 +106,14 @@ impl Settings {
                 wrapper: res.hook_wrapper,
                 post_exit: res.hook_post_exit,
             },
+            update_history_generations: res.update_history_generations as u32,
+            trash_retention_days: res.trash_retention_days as u32,
+            curseforge_api_url: res.curseforge_api_url,
+            curseforge_api_key: res.curseforge_api_key,
+            shared_option_categories: res
+                .shared_option_categories
+                .and_then(|x| serde_json::from_str(&x).ok())
+                .unwrap_or_default(),
             custom_dir: res.custom_dir,
             prev_custom_dir: res.prev_custom_dir,
             migrated: res.migrated == 1,
@@ -104,6 +130,8 @@ impl Settings {
         let default_page = self.default_page.as_str();
         let extra_launch_args = serde_json::to_string(&self.extra_launch_args)?;
         let custom_env_vars = serde_json::to_string(&self.custom_env_vars)?;
+        let shared_option_categories =
+            serde_json::to_string(&self.shared_option_categories)?;
 
         sqlx::query!(
             "
@@ -136,9 +164,17 @@ impl Settings {
                 hook_wrapper = $20,
                 hook_post_exit = $21,
 
-                custom_dir = $22,
-                prev_custom_dir = $23,
-                migrated = $24
+                update_history_generations = $22,
+                trash_retention_days = $23,
+
+                curseforge_api_url = $24,
+                curseforge_api_key = $25,
+
+                shared_option_categories = jsonb($26),
+
+                custom_dir = $27,
+                prev_custom_dir = $28,
+                migrated = $29
             ",
             max_concurrent_writes,
             max_concurrent_downloads,
@@ -161,6 +197,11 @@ impl Settings {
             self.hooks.pre_launch,
             self.hooks.wrapper,
             self.hooks.post_exit,
+            self.update_history_generations,
+            self.trash_retention_days,
+            self.curseforge_api_url,
+            self.curseforge_api_key,
+            shared_option_categories,
             self.custom_dir,
             self.prev_custom_dir,
             self.migrated
@@ -0,0 +1,202 @@
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A game option shared by the profiles that sync options, as it is in
+/// options.txt
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SharedOption {
+    pub key: String,
+    pub value: String,
+    pub modified: DateTime<Utc>,
+}
+
+/// Groups of game options that can be shared between profiles
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SharedOptionCategory {
+    Keybinds,
+    Mouse,
+    Language,
+    Audio,
+    Chat,
+}
+
+impl SharedOptionCategory {
+    /// Whether an options.txt key belongs to this category
+    pub fn contains(&self, key: &str) -> bool {
+        match self {
+            SharedOptionCategory::Keybinds => key.starts_with("key_"),
+            SharedOptionCategory::Mouse => [
+                "mouseSensitivity",
+                "mouseWheelSensitivity",
+                "invertYMouse",
+                "rawMouseInput",
+                "discrete_mouse_scroll",
+                "touchscreen",
+            ]
+            .contains(&key),
+            SharedOptionCategory::Language => {
+                ["lang", "forceUnicodeFont"].contains(&key)
+            }
+            SharedOptionCategory::Audio => {
+                key.starts_with("soundCategory_")
+                    || ["soundDevice", "showSubtitles", "directionalAudio"]
+                        .contains(&key)
+            }
+            SharedOptionCategory::Chat => {
+                key.starts_with("chat")
+                    || [
+                        "textBackgroundOpacity",
+                        "backgroundForChatOnly",
+                        "autoSuggestions",
+                        "hideMatchedNames",
+                        "onlyShowSecureChat",
+                        "narrator",
+                    ]
+                    .contains(&key)
+            }
+        }
+    }
+}
+
+struct SharedOptionQueryResult {
+    key: String,
+    value: String,
+    modified: i64,
+}
+
+impl From<SharedOptionQueryResult> for SharedOption {
+    fn from(x: SharedOptionQueryResult) -> Self {
+        SharedOption {
+            key: x.key,
+            value: x.value,
+            modified: Utc
+                .timestamp_opt(x.modified, 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+impl SharedOption {
+    pub async fn get_all(
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<Self>> {
+        let results = sqlx::query_as!(
+            SharedOptionQueryResult,
+            "
+            SELECT key, value, modified
+            FROM shared_options
+            ORDER BY key
+            "
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(results.into_iter().map(|x| x.into()).collect())
+    }
+
+    pub async fn upsert(
+        &self,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        let modified = self.modified.timestamp();
+
+        sqlx::query!(
+            "
+            INSERT INTO shared_options (key, value, modified)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (key) DO UPDATE SET
+                value = $2,
+                modified = $3
+            ",
+            self.key,
+            self.value,
+            modified,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove(
+        key: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            DELETE FROM shared_options
+            WHERE key = $1
+            ",
+            key
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Values of the shared options in a profile's options.txt when it last
+    /// launched, by key
+    pub async fn get_applied(
+        profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<HashMap<String, String>> {
+        let results = sqlx::query!(
+            "
+            SELECT key, value
+            FROM shared_options_applied
+            WHERE profile_path = $1
+            ",
+            profile_path
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(results.into_iter().map(|x| (x.key, x.value)).collect())
+    }
+
+    /// Replaces the values of the shared options in a profile's options.txt
+    /// recorded when it launched
+    pub async fn set_applied(
+        profile_path: &str,
+        options: &[(String, String)],
+        transaction: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        Self::remove_applied(profile_path, &mut **transaction).await?;
+
+        for (key, value) in options {
+            sqlx::query!(
+                "
+                INSERT INTO shared_options_applied (profile_path, key, value)
+                VALUES ($1, $2, $3)
+                ",
+                profile_path,
+                key,
+                value,
+            )
+            .execute(&mut **transaction)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn remove_applied(
+        profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            DELETE FROM shared_options_applied WHERE profile_path = $1
+            ",
+            profile_path,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+}
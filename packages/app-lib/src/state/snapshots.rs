@@ -0,0 +1,268 @@
+use crate::state::{DirectoryInfo, ModLoader, Profile, ProfileFile};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::convert::TryInto;
+use std::path::PathBuf;
+
+// A point-in-time backup of a profile's folder and database row
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProfileSnapshot {
+    pub id: String,
+    pub profile_path: String,
+    pub name: Option<String>,
+    pub created: DateTime<Utc>,
+
+    pub game_version: String,
+    pub loader: ModLoader,
+    pub loader_version: Option<String>,
+
+    pub profile: Profile,
+    pub projects: HashMap<String, ProfileFile>,
+
+    pub size: u64,
+}
+
+struct ProfileSnapshotQueryResult {
+    id: String,
+    profile_path: String,
+    name: Option<String>,
+    created: i64,
+    game_version: String,
+    mod_loader: String,
+    mod_loader_version: Option<String>,
+    profile: serde_json::Value,
+    projects: serde_json::Value,
+    size: i64,
+}
+
+impl TryFrom<ProfileSnapshotQueryResult> for ProfileSnapshot {
+    type Error = crate::Error;
+
+    fn try_from(x: ProfileSnapshotQueryResult) -> Result<Self, Self::Error> {
+        Ok(ProfileSnapshot {
+            id: x.id,
+            profile_path: x.profile_path,
+            name: x.name,
+            created: Utc
+                .timestamp_opt(x.created, 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+            game_version: x.game_version,
+            loader: ModLoader::from_string(&x.mod_loader),
+            loader_version: x.mod_loader_version,
+            profile: serde_json::from_value(x.profile)?,
+            projects: serde_json::from_value(x.projects).unwrap_or_default(),
+            size: x.size as u64,
+        })
+    }
+}
+
+macro_rules! select_snapshots_with_predicate {
+    ($predicate:tt, $param:ident) => {
+        sqlx::query_as!(
+            ProfileSnapshotQueryResult,
+            r#"
+            SELECT
+                id, profile_path, name, created,
+                game_version, mod_loader, mod_loader_version,
+                json(profile) as "profile!: serde_json::Value",
+                json(projects) as "projects!: serde_json::Value",
+                size
+            FROM profile_snapshots
+            "#
+                + $predicate,
+            $param
+        )
+    };
+}
+
+impl ProfileSnapshot {
+    /// Path of the archive holding this snapshot's files
+    pub fn archive_path(&self, dirs: &DirectoryInfo) -> PathBuf {
+        dirs.snapshots_dir()
+            .join(&self.profile_path)
+            .join(format!("{}.zip", self.id))
+    }
+
+    pub async fn get(
+        id: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Option<Self>> {
+        let res = select_snapshots_with_predicate!("WHERE id = $1", id)
+            .fetch_optional(exec)
+            .await?;
+
+        res.map(|r| r.try_into()).transpose()
+    }
+
+    /// Gets every snapshot of every profile, newest first
+    pub async fn get_all(
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<Self>> {
+        let results = sqlx::query_as!(
+            ProfileSnapshotQueryResult,
+            r#"
+            SELECT
+                id, profile_path, name, created,
+                game_version, mod_loader, mod_loader_version,
+                json(profile) as "profile!: serde_json::Value",
+                json(projects) as "projects!: serde_json::Value",
+                size
+            FROM profile_snapshots
+            ORDER BY created DESC, rowid DESC
+            "#
+        )
+        .fetch_all(exec)
+        .await?;
+
+        results
+            .into_iter()
+            .map(|r| r.try_into())
+            .collect::<crate::Result<Vec<_>>>()
+    }
+
+    /// Gets all snapshots of a profile, newest first
+    pub async fn get_from_profile(
+        profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<Self>> {
+        let results = select_snapshots_with_predicate!(
+            "WHERE profile_path = $1 ORDER BY created DESC, rowid DESC",
+            profile_path
+        )
+        .fetch_all(exec)
+        .await?;
+
+        results
+            .into_iter()
+            .map(|r| r.try_into())
+            .collect::<crate::Result<Vec<_>>>()
+    }
+
+    pub async fn upsert(
+        &self,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        let created = self.created.timestamp();
+        let mod_loader = self.loader.as_str();
+        let profile = serde_json::to_string(&self.profile)?;
+        let projects = serde_json::to_string(&self.projects)?;
+        let size = self.size as i64;
+
+        sqlx::query!(
+            "
+            INSERT INTO profile_snapshots (
+                id, profile_path, name, created,
+                game_version, mod_loader, mod_loader_version,
+                profile, projects,
+                size
+            )
+            VALUES (
+                $1, $2, $3, $4,
+                $5, $6, $7,
+                jsonb($8), jsonb($9),
+                $10
+            )
+            ON CONFLICT (id) DO UPDATE SET
+                profile_path = $2,
+                name = $3,
+                created = $4,
+
+                game_version = $5,
+                mod_loader = $6,
+                mod_loader_version = $7,
+
+                profile = jsonb($8),
+                projects = jsonb($9),
+
+                size = $10
+            ",
+            self.id,
+            self.profile_path,
+            self.name,
+            created,
+            self.game_version,
+            mod_loader,
+            self.loader_version,
+            profile,
+            projects,
+            size,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove(
+        id: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            DELETE FROM profile_snapshots WHERE id = $1
+            ",
+            id,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes every snapshot of a profile, along with their archives
+    pub async fn remove_from_profile(
+        profile_path: &str,
+        dirs: &DirectoryInfo,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            DELETE FROM profile_snapshots WHERE profile_path = $1
+            ",
+            profile_path,
+        )
+        .execute(exec)
+        .await?;
+
+        let dir = dirs.snapshots_dir().join(profile_path);
+        if dir.exists() {
+            crate::util::io::remove_dir_all(&dir).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves every snapshot of a profile to another profile path, along with
+    /// their archives
+    pub async fn move_profile(
+        from_profile_path: &str,
+        to_profile_path: &str,
+        dirs: &DirectoryInfo,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            UPDATE profile_snapshots SET profile_path = $2
+            WHERE profile_path = $1
+            ",
+            from_profile_path,
+            to_profile_path,
+        )
+        .execute(exec)
+        .await?;
+
+        let dir = dirs.snapshots_dir().join(from_profile_path);
+        if dir.exists() {
+            crate::util::io::rename(
+                &dir,
+                &dirs.snapshots_dir().join(to_profile_path),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
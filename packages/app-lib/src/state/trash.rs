@@ -0,0 +1,222 @@
+use crate::state::{DirectoryInfo, Profile};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::convert::TryInto;
+use std::path::PathBuf;
+
+// A removed profile or project file, kept in the trash until it is restored or
+// purged
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TrashItem {
+    pub id: String,
+    pub item_type: TrashItemType,
+    /// Path of the profile the item was removed from, or of the removed profile
+    pub profile_path: String,
+    /// Path of the removed file relative to its profile, for projects
+    pub project_path: Option<String>,
+    /// The removed profile's row, for profiles
+    pub profile: Option<Profile>,
+    pub removed: DateTime<Utc>,
+
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashItemType {
+    Profile,
+    Project,
+}
+
+impl TrashItemType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrashItemType::Profile => "profile",
+            TrashItemType::Project => "project",
+        }
+    }
+
+    pub fn from_string(string: &str) -> Self {
+        match string {
+            "profile" => TrashItemType::Profile,
+            _ => TrashItemType::Project,
+        }
+    }
+}
+
+struct TrashItemQueryResult {
+    id: String,
+    item_type: String,
+    profile_path: String,
+    project_path: Option<String>,
+    profile: Option<serde_json::Value>,
+    removed: i64,
+    size: i64,
+}
+
+impl TryFrom<TrashItemQueryResult> for TrashItem {
+    type Error = crate::Error;
+
+    fn try_from(x: TrashItemQueryResult) -> Result<Self, Self::Error> {
+        Ok(TrashItem {
+            id: x.id,
+            item_type: TrashItemType::from_string(&x.item_type),
+            profile_path: x.profile_path,
+            project_path: x.project_path,
+            profile: x.profile.map(serde_json::from_value).transpose()?,
+            removed: Utc
+                .timestamp_opt(x.removed, 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+            size: x.size as u64,
+        })
+    }
+}
+
+macro_rules! select_trash_with_predicate {
+    ($predicate:tt, $param:ident) => {
+        sqlx::query_as!(
+            TrashItemQueryResult,
+            r#"
+            SELECT
+                id, item_type, profile_path, project_path,
+                json(profile) as "profile?: serde_json::Value",
+                removed, size
+            FROM trash_items
+            "#
+                + $predicate,
+            $param
+        )
+    };
+}
+
+impl TrashItem {
+    /// Path of the removed profile folder or file in the trash
+    pub fn trash_path(&self, dirs: &DirectoryInfo) -> PathBuf {
+        dirs.trash_dir().join(&self.id)
+    }
+
+    /// Profile path the snapshots, update history, update policies, mod sets
+    /// and file references of a removed profile are kept under while it is
+    /// in the trash, so a new profile created at its path doesn't get them
+    pub fn data_key(&self) -> String {
+        format!(".trash-{}", self.id)
+    }
+
+    pub async fn get(
+        id: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Option<Self>> {
+        let res = select_trash_with_predicate!("WHERE id = $1", id)
+            .fetch_optional(exec)
+            .await?;
+
+        res.map(|r| r.try_into()).transpose()
+    }
+
+    /// Gets every item in the trash, newest first
+    pub async fn get_all(
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<Self>> {
+        let results = sqlx::query_as!(
+            TrashItemQueryResult,
+            r#"
+            SELECT
+                id, item_type, profile_path, project_path,
+                json(profile) as "profile?: serde_json::Value",
+                removed, size
+            FROM trash_items
+            ORDER BY removed DESC
+            "#
+        )
+        .fetch_all(exec)
+        .await?;
+
+        results
+            .into_iter()
+            .map(|r| r.try_into())
+            .collect::<crate::Result<Vec<_>>>()
+    }
+
+    /// Gets every item in the trash removed before a time, newest first
+    pub async fn get_removed_before(
+        time: DateTime<Utc>,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<Self>> {
+        let time = time.timestamp();
+        let results = select_trash_with_predicate!(
+            "WHERE removed < $1 ORDER BY removed DESC",
+            time
+        )
+        .fetch_all(exec)
+        .await?;
+
+        results
+            .into_iter()
+            .map(|r| r.try_into())
+            .collect::<crate::Result<Vec<_>>>()
+    }
+
+    pub async fn upsert(
+        &self,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        let item_type = self.item_type.as_str();
+        let profile = self
+            .profile
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let removed = self.removed.timestamp();
+        let size = self.size as i64;
+
+        sqlx::query!(
+            "
+            INSERT INTO trash_items (
+                id, item_type, profile_path, project_path,
+                profile, removed, size
+            )
+            VALUES (
+                $1, $2, $3, $4,
+                jsonb($5), $6, $7
+            )
+            ON CONFLICT (id) DO UPDATE SET
+                item_type = $2,
+                profile_path = $3,
+                project_path = $4,
+
+                profile = jsonb($5),
+                removed = $6,
+                size = $7
+            ",
+            self.id,
+            item_type,
+            self.profile_path,
+            self.project_path,
+            profile,
+            removed,
+            size,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove(
+        id: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            DELETE FROM trash_items WHERE id = $1
+            ",
+            id,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+}
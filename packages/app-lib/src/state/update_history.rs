@@ -0,0 +1,232 @@
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+// A project file that was replaced by an update, kept so the update can be
+// rolled back. The replaced file itself is kept in the content store
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProjectUpdate {
+    /// Shared by every project replaced by the same update
+    pub generation: String,
+    pub profile_path: String,
+    pub created: DateTime<Utc>,
+
+    /// Path of the replaced file, relative to the profile
+    pub project_path: String,
+    /// Path of the file that replaced it, relative to the profile
+    pub new_project_path: String,
+    /// Sha1 of the replaced file
+    pub hash: String,
+
+    pub old_version_id: Option<String>,
+    pub new_version_id: String,
+}
+
+struct ProjectUpdateQueryResult {
+    generation: String,
+    profile_path: String,
+    created: i64,
+    project_path: String,
+    new_project_path: String,
+    hash: String,
+    old_version_id: Option<String>,
+    new_version_id: String,
+}
+
+impl From<ProjectUpdateQueryResult> for ProjectUpdate {
+    fn from(x: ProjectUpdateQueryResult) -> Self {
+        ProjectUpdate {
+            generation: x.generation,
+            profile_path: x.profile_path,
+            created: Utc
+                .timestamp_opt(x.created, 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+            project_path: x.project_path,
+            new_project_path: x.new_project_path,
+            hash: x.hash,
+            old_version_id: x.old_version_id,
+            new_version_id: x.new_version_id,
+        }
+    }
+}
+
+macro_rules! select_updates_with_predicate {
+    ($predicate:tt, $($param:ident),+) => {
+        sqlx::query_as!(
+            ProjectUpdateQueryResult,
+            "
+            SELECT
+                generation, profile_path, created,
+                project_path, new_project_path, hash,
+                old_version_id, new_version_id
+            FROM project_update_history
+            "
+                + $predicate,
+            $($param),+
+        )
+    };
+}
+
+impl ProjectUpdate {
+    /// Gets the update history of a profile, newest first
+    pub async fn get_from_profile(
+        profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<Self>> {
+        let results = select_updates_with_predicate!(
+            "WHERE profile_path = $1 ORDER BY created DESC, rowid DESC",
+            profile_path
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(results.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Gets every project replaced by an update
+    pub async fn get_generation(
+        profile_path: &str,
+        generation: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<Self>> {
+        let results = select_updates_with_predicate!(
+            "WHERE profile_path = $1 AND generation = $2 ORDER BY project_path",
+            profile_path,
+            generation
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(results.into_iter().map(|r| r.into()).collect())
+    }
+
+    pub async fn upsert(
+        &self,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        let created = self.created.timestamp();
+
+        sqlx::query!(
+            "
+            INSERT INTO project_update_history (
+                generation, profile_path, created,
+                project_path, new_project_path, hash,
+                old_version_id, new_version_id
+            )
+            VALUES (
+                $1, $2, $3,
+                $4, $5, $6,
+                $7, $8
+            )
+            ON CONFLICT (generation, project_path) DO UPDATE SET
+                profile_path = $2,
+                created = $3,
+
+                new_project_path = $5,
+                hash = $6,
+
+                old_version_id = $7,
+                new_version_id = $8
+            ",
+            self.generation,
+            self.profile_path,
+            created,
+            self.project_path,
+            self.new_project_path,
+            self.hash,
+            self.old_version_id,
+            self.new_version_id,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove(
+        generation: &str,
+        project_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            DELETE FROM project_update_history
+            WHERE generation = $1 AND project_path = $2
+            ",
+            generation,
+            project_path,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes the whole update history of a profile, returning the hashes of
+    /// the replaced files
+    pub async fn remove_from_profile(
+        profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<String>> {
+        let hashes = sqlx::query_scalar!(
+            "
+            DELETE FROM project_update_history
+            WHERE profile_path = $1
+            RETURNING hash
+            ",
+            profile_path,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(hashes)
+    }
+
+    /// Moves the update history of a profile to another profile path
+    pub async fn move_profile(
+        from_profile_path: &str,
+        to_profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            UPDATE project_update_history SET profile_path = $2 WHERE profile_path = $1
+            ",
+            from_profile_path,
+            to_profile_path,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes all but the newest `generations` updates of a profile,
+    /// returning the hashes of the replaced files
+    pub async fn prune(
+        profile_path: &str,
+        generations: u32,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<String>> {
+        let hashes = sqlx::query_scalar!(
+            "
+            DELETE FROM project_update_history
+            WHERE profile_path = $1 AND generation NOT IN (
+                SELECT generation
+                FROM project_update_history
+                WHERE profile_path = $1
+                GROUP BY generation
+                ORDER BY MAX(created) DESC, MAX(rowid) DESC
+                LIMIT $2
+            )
+            RETURNING hash
+            ",
+            profile_path,
+            generations,
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(hashes)
+    }
+}
@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+
+// How a project of a profile is updated. Projects without a stored policy use
+// the default one, which takes the newest compatible version of any channel
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UpdatePolicy {
+    pub profile_path: String,
+    pub project_id: String,
+
+    /// Release channels updates may come from
+    pub channels: Vec<ReleaseChannel>,
+    pub mode: UpdateMode,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleaseChannel {
+    Release,
+    Beta,
+    Alpha,
+}
+
+impl ReleaseChannel {
+    pub fn iterator() -> impl Iterator<Item = ReleaseChannel> {
+        [
+            ReleaseChannel::Release,
+            ReleaseChannel::Beta,
+            ReleaseChannel::Alpha,
+        ]
+        .iter()
+        .copied()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReleaseChannel::Release => "release",
+            ReleaseChannel::Beta => "beta",
+            ReleaseChannel::Alpha => "alpha",
+        }
+    }
+
+    pub fn from_string(string: &str) -> Option<Self> {
+        match string {
+            "release" => Some(ReleaseChannel::Release),
+            "beta" => Some(ReleaseChannel::Beta),
+            "alpha" => Some(ReleaseChannel::Alpha),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateMode {
+    /// Update to the newest version for the profile's game version
+    Compatible,
+    /// Never update
+    Pin,
+    /// Update to the newest version, even if it is for another game version
+    Follow,
+}
+
+impl UpdateMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpdateMode::Compatible => "compatible",
+            UpdateMode::Pin => "pin",
+            UpdateMode::Follow => "follow",
+        }
+    }
+
+    pub fn from_string(string: &str) -> Self {
+        match string {
+            "pin" => UpdateMode::Pin,
+            "follow" => UpdateMode::Follow,
+            _ => UpdateMode::Compatible,
+        }
+    }
+}
+
+struct UpdatePolicyQueryResult {
+    profile_path: String,
+    project_id: String,
+    channels: serde_json::Value,
+    mode: String,
+}
+
+impl From<UpdatePolicyQueryResult> for UpdatePolicy {
+    fn from(x: UpdatePolicyQueryResult) -> Self {
+        UpdatePolicy {
+            profile_path: x.profile_path,
+            project_id: x.project_id,
+            channels: serde_json::from_value(x.channels)
+                .unwrap_or_else(|_| ReleaseChannel::iterator().collect()),
+            mode: UpdateMode::from_string(&x.mode),
+        }
+    }
+}
+
+impl UpdatePolicy {
+    /// The policy of projects without a stored one
+    pub fn default_for(profile_path: &str, project_id: &str) -> Self {
+        UpdatePolicy {
+            profile_path: profile_path.to_string(),
+            project_id: project_id.to_string(),
+            channels: ReleaseChannel::iterator().collect(),
+            mode: UpdateMode::Compatible,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.mode == UpdateMode::Compatible
+            && ReleaseChannel::iterator().all(|x| self.channels.contains(&x))
+    }
+
+    /// Gets the stored policies of a profile
+    pub async fn get_from_profile(
+        profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<Vec<Self>> {
+        let results = sqlx::query_as!(
+            UpdatePolicyQueryResult,
+            r#"
+            SELECT
+                profile_path, project_id,
+                json(channels) as "channels!: serde_json::Value",
+                mode
+            FROM project_update_policies
+            WHERE profile_path = $1
+            "#,
+            profile_path
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(results.into_iter().map(|r| r.into()).collect())
+    }
+
+    pub async fn upsert(
+        &self,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        let channels = serde_json::to_string(&self.channels)?;
+        let mode = self.mode.as_str();
+
+        sqlx::query!(
+            "
+            INSERT INTO project_update_policies (
+                profile_path, project_id, channels, mode
+            )
+            VALUES ($1, $2, jsonb($3), $4)
+            ON CONFLICT (profile_path, project_id) DO UPDATE SET
+                channels = jsonb($3),
+                mode = $4
+            ",
+            self.profile_path,
+            self.project_id,
+            channels,
+            mode,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove(
+        profile_path: &str,
+        project_id: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            DELETE FROM project_update_policies
+            WHERE profile_path = $1 AND project_id = $2
+            ",
+            profile_path,
+            project_id,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_from_profile(
+        profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            DELETE FROM project_update_policies WHERE profile_path = $1
+            ",
+            profile_path,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Moves the update policies of a profile to another profile path
+    pub async fn move_profile(
+        from_profile_path: &str,
+        to_profile_path: &str,
+        exec: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "
+            UPDATE project_update_policies SET profile_path = $2 WHERE profile_path = $1
+            ",
+            from_profile_path,
+            to_profile_path,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+}
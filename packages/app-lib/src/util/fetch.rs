@@ -256,6 +256,12 @@ pub async fn write<'a>(
         io::create_dir_all(parent).await?;
     }
 
+    // Replace rather than truncate existing files, so files hardlinked from the
+    // content store are never written through
+    if path.is_file() {
+        io::remove_file(path).await?;
+    }
+
     let mut file = File::create(path)
         .await
         .map_err(|e| IOError::with_path(e, path))?;
@@ -311,6 +317,53 @@ pub async fn write_cached_icon(
     Ok(path)
 }
 
+/// CurseForge's fingerprint of a file: MurmurHash2 with a seed of 1, over the
+/// file without whitespace bytes
+pub fn murmur2_fingerprint(bytes: &[u8]) -> u32 {
+    const M: u32 = 0x5bd1e995;
+
+    let data = bytes
+        .iter()
+        .copied()
+        .filter(|x| !matches!(x, 9 | 10 | 13 | 32))
+        .collect::<Vec<u8>>();
+
+    let mut hash = 1 ^ data.len() as u32;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k =
+            u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> 24;
+        k = k.wrapping_mul(M);
+
+        hash = hash.wrapping_mul(M);
+        hash ^= k;
+    }
+
+    let rest = chunks.remainder();
+    if !rest.is_empty() {
+        for (i, byte) in rest.iter().enumerate() {
+            hash ^= (*byte as u32) << (8 * i);
+        }
+        hash = hash.wrapping_mul(M);
+    }
+
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(M);
+    hash ^= hash >> 15;
+
+    hash
+}
+
+pub async fn fingerprint_async(bytes: Bytes) -> crate::Result<u64> {
+    let fingerprint =
+        tokio::task::spawn_blocking(move || murmur2_fingerprint(&bytes) as u64)
+            .await?;
+
+    Ok(fingerprint)
+}
+
 pub async fn sha1_async(bytes: Bytes) -> crate::Result<String> {
     let hash = tokio::task::spawn_blocking(move || {
         sha1_smol::Sha1::from(bytes).hexdigest()
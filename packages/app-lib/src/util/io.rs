@@ -180,6 +180,21 @@ pub async fn copy(
         })
 }
 
+// hard link
+pub async fn hard_link(
+    from: impl AsRef<std::path::Path>,
+    to: impl AsRef<std::path::Path>,
+) -> Result<(), IOError> {
+    let from: &Path = from.as_ref();
+    let to = to.as_ref();
+    tokio::fs::hard_link(from, to)
+        .await
+        .map_err(|e| IOError::IOPathError {
+            source: e,
+            path: from.to_string_lossy().to_string(),
+        })
+}
+
 // remove file
 pub async fn remove_file(
     path: impl AsRef<std::path::Path>,
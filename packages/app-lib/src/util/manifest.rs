@@ -0,0 +1,444 @@
+//! Reading of the metadata projects declare inside their own files
+use crate::state::{FileManifest, FileManifestDependency, FileManifestKind};
+use crate::util::fetch::{write_cached_icon, IoSemaphore};
+use bytes::Bytes;
+use serde_json::Value;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+
+// Dependencies that are the game or loader itself, rather than other projects
+const PLATFORM_DEPENDENCIES: [&str; 7] = [
+    "minecraft",
+    "java",
+    "fabricloader",
+    "quilt_loader",
+    "forge",
+    "neoforge",
+    "fml",
+];
+
+/// Reads the manifest of a project file held in memory
+pub async fn read_from_bytes(
+    bytes: Bytes,
+    cache_dir: &Path,
+    semaphore: &IoSemaphore,
+) -> Option<FileManifest> {
+    let parsed = tokio::task::spawn_blocking(move || {
+        parse(std::io::Cursor::new(&*bytes))
+    })
+    .await
+    .ok()??;
+
+    finish(parsed, cache_dir, semaphore).await
+}
+
+/// Reads the manifest of a project file on disk
+pub async fn read_from_path(
+    path: PathBuf,
+    cache_dir: &Path,
+    semaphore: &IoSemaphore,
+) -> Option<FileManifest> {
+    let parsed = tokio::task::spawn_blocking(move || {
+        parse(std::fs::File::open(path).ok()?)
+    })
+    .await
+    .ok()??;
+
+    finish(parsed, cache_dir, semaphore).await
+}
+
+// A manifest, along with the icon it points to
+struct ParsedManifest {
+    manifest: FileManifest,
+    icon: Option<(String, Vec<u8>)>,
+}
+
+// Extracts the icon into the icon cache
+async fn finish(
+    parsed: ParsedManifest,
+    cache_dir: &Path,
+    semaphore: &IoSemaphore,
+) -> Option<FileManifest> {
+    let mut manifest = parsed.manifest;
+
+    if let Some((icon_path, icon)) = parsed.icon {
+        match write_cached_icon(&icon_path, cache_dir, icon.into(), semaphore)
+            .await
+        {
+            Ok(path) => {
+                manifest.icon = Some(path.to_string_lossy().to_string())
+            }
+            Err(err) => {
+                tracing::warn!("Unable to cache icon {}: {}", icon_path, err)
+            }
+        }
+    }
+
+    Some(manifest)
+}
+
+fn parse<R: Read + Seek>(reader: R) -> Option<ParsedManifest> {
+    let mut archive = zip::ZipArchive::new(reader).ok()?;
+
+    let mut read_file = |name: &str| {
+        let mut file = archive.by_name(name).ok()?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).ok()?;
+        Some(content)
+    };
+
+    let mut manifest = if let Some(file) = read_file("quilt.mod.json") {
+        parse_quilt(&serde_json::from_slice(&file).ok()?)
+    } else if let Some(file) = read_file("fabric.mod.json") {
+        parse_fabric(&serde_json::from_slice(&file).ok()?)
+    } else if let Some(file) = read_file("META-INF/neoforge.mods.toml") {
+        parse_mods_toml(
+            FileManifestKind::NeoForge,
+            &String::from_utf8_lossy(&file),
+            read_file("META-INF/MANIFEST.MF"),
+        )?
+    } else if let Some(file) = read_file("META-INF/mods.toml") {
+        parse_mods_toml(
+            FileManifestKind::Forge,
+            &String::from_utf8_lossy(&file),
+            read_file("META-INF/MANIFEST.MF"),
+        )?
+    } else if let Some(file) = read_file("mcmod.info") {
+        parse_mcmod_info(&serde_json::from_slice(&file).ok()?)?
+    } else if let Some(file) = read_file("pack.mcmeta") {
+        parse_pack_mcmeta(&serde_json::from_slice(&file).ok()?)
+    } else {
+        return None;
+    };
+
+    let icon = manifest.icon.take().and_then(|icon_path| {
+        let icon_path = icon_path.trim_start_matches('/').to_string();
+        read_file(&icon_path).map(|icon| (icon_path, icon))
+    });
+
+    Some(ParsedManifest { manifest, icon })
+}
+
+fn new_manifest(kind: FileManifestKind) -> FileManifest {
+    FileManifest {
+        kind,
+        id: None,
+        name: None,
+        version: None,
+        authors: Vec::new(),
+        icon: None,
+        dependencies: Vec::new(),
+        game_versions: Vec::new(),
+        pack_format: None,
+        supported_formats: None,
+    }
+}
+
+fn as_string(value: Option<&Value>) -> Option<String> {
+    value.and_then(Value::as_str).map(|x| x.to_string())
+}
+
+// A version requirement, written either as a single string or as a list of them
+fn as_requirements(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(x) => vec![x.clone()],
+        Value::Array(x) => x
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|x| x.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn add_dependency(
+    manifest: &mut FileManifest,
+    id: &str,
+    versions: Vec<String>,
+    required: bool,
+) {
+    if id == "minecraft" {
+        manifest.game_versions.extend(versions);
+    } else if !PLATFORM_DEPENDENCIES.contains(&id) {
+        manifest.dependencies.push(FileManifestDependency {
+            id: id.to_string(),
+            version: if versions.is_empty() {
+                None
+            } else {
+                Some(versions.join(" || "))
+            },
+            required,
+        });
+    }
+}
+
+// https://fabricmc.net/wiki/documentation:fabric_mod_json
+fn parse_fabric(json: &Value) -> FileManifest {
+    let mut manifest = new_manifest(FileManifestKind::Fabric);
+    manifest.id = as_string(json.get("id"));
+    manifest.name = as_string(json.get("name"));
+    manifest.version = as_string(json.get("version"));
+
+    manifest.authors = json
+        .get("authors")
+        .and_then(Value::as_array)
+        .map(|authors| {
+            authors
+                .iter()
+                .filter_map(|x| {
+                    x.as_str()
+                        .or_else(|| x.get("name").and_then(Value::as_str))
+                        .map(|x| x.to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // The icon is either a path, or a map of sizes to paths
+    manifest.icon = match json.get("icon") {
+        Some(Value::String(icon)) => Some(icon.clone()),
+        Some(Value::Object(icons)) => icons
+            .iter()
+            .max_by_key(|(size, _)| size.parse::<u32>().unwrap_or(0))
+            .and_then(|(_, icon)| as_string(Some(icon))),
+        _ => None,
+    };
+
+    for (key, required) in [
+        ("depends", true),
+        ("recommends", false),
+        ("suggests", false),
+    ] {
+        if let Some(dependencies) = json.get(key).and_then(Value::as_object) {
+            for (id, versions) in dependencies {
+                add_dependency(
+                    &mut manifest,
+                    id,
+                    as_requirements(versions),
+                    required,
+                );
+            }
+        }
+    }
+
+    manifest
+}
+
+// https://github.com/QuiltMC/rfcs/blob/main/specification/0002-quilt.mod.json.md
+fn parse_quilt(json: &Value) -> FileManifest {
+    let mut manifest = new_manifest(FileManifestKind::Quilt);
+    let Some(loader) = json.get("quilt_loader") else {
+        return manifest;
+    };
+
+    manifest.id = as_string(loader.get("id"));
+    manifest.version = as_string(loader.get("version"));
+
+    if let Some(metadata) = loader.get("metadata") {
+        manifest.name = as_string(metadata.get("name"));
+        manifest.authors = metadata
+            .get("contributors")
+            .and_then(Value::as_object)
+            .map(|x| x.keys().cloned().collect())
+            .unwrap_or_default();
+        manifest.icon = match metadata.get("icon") {
+            Some(Value::String(icon)) => Some(icon.clone()),
+            Some(Value::Object(icons)) => icons
+                .iter()
+                .max_by_key(|(size, _)| size.parse::<u32>().unwrap_or(0))
+                .and_then(|(_, icon)| as_string(Some(icon))),
+            _ => None,
+        };
+    }
+
+    for (key, required) in [("depends", true), ("recommends", false)] {
+        let Some(dependencies) = loader.get(key).and_then(Value::as_array)
+        else {
+            continue;
+        };
+
+        for dependency in dependencies {
+            match dependency {
+                Value::String(id) => {
+                    add_dependency(&mut manifest, id, Vec::new(), required)
+                }
+                Value::Object(_) => {
+                    if let Some(id) =
+                        dependency.get("id").and_then(Value::as_str)
+                    {
+                        let optional = dependency
+                            .get("optional")
+                            .and_then(Value::as_bool)
+                            .unwrap_or(false);
+                        add_dependency(
+                            &mut manifest,
+                            id,
+                            dependency
+                                .get("versions")
+                                .map(as_requirements)
+                                .unwrap_or_default(),
+                            required && !optional,
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    manifest
+}
+
+// https://docs.minecraftforge.net/en/latest/gettingstarted/modfiles/
+fn parse_mods_toml(
+    kind: FileManifestKind,
+    file: &str,
+    jar_manifest: Option<Vec<u8>>,
+) -> Option<FileManifest> {
+    let toml: toml::Value = toml::from_str(file).ok()?;
+    let mut manifest = new_manifest(kind);
+
+    let get_string = |value: &toml::Value, key: &str| {
+        value
+            .get(key)
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_string())
+    };
+
+    // Only the first mod of a file is described
+    let first_mod = toml
+        .get("mods")
+        .and_then(|x| x.as_array())
+        .and_then(|x| x.first())?;
+
+    manifest.id = get_string(first_mod, "modId");
+    manifest.name = get_string(first_mod, "displayName");
+    manifest.authors = get_string(first_mod, "authors")
+        .or_else(|| get_string(&toml, "authors"))
+        .map(|x| {
+            x.split(',')
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    manifest.icon = get_string(first_mod, "logoFile")
+        .or_else(|| get_string(&toml, "logoFile"));
+
+    // The version is usually filled in from the jar's manifest at build time
+    manifest.version = get_string(first_mod, "version").and_then(|version| {
+        if version == "${file.jarVersion}" {
+            let jar_manifest = String::from_utf8(jar_manifest?).ok()?;
+            jar_manifest
+                .lines()
+                .find_map(|x| x.strip_prefix("Implementation-Version:"))
+                .map(|x| x.trim().to_string())
+        } else {
+            Some(version)
+        }
+    });
+
+    let dependencies = manifest
+        .id
+        .as_ref()
+        .and_then(|id| toml.get("dependencies")?.get(id)?.as_array());
+    for dependency in dependencies.into_iter().flatten() {
+        let Some(id) = get_string(dependency, "modId") else {
+            continue;
+        };
+
+        // Forge uses `mandatory`, NeoForge uses `type`
+        let required = match get_string(dependency, "type")
+            .map(|x| x.to_lowercase())
+            .as_deref()
+        {
+            Some("required") => true,
+            Some("optional") => false,
+            // Incompatible and discouraged mods aren't dependencies
+            Some(_) => continue,
+            None => dependency
+                .get("mandatory")
+                .and_then(|x| x.as_bool())
+                .unwrap_or(true),
+        };
+
+        add_dependency(
+            &mut manifest,
+            &id,
+            get_string(dependency, "versionRange").into_iter().collect(),
+            required,
+        );
+    }
+
+    Some(manifest)
+}
+
+// https://docs.minecraftforge.net/en/1.12.x/gettingstarted/structuring/#the-mcmodinfo-file
+fn parse_mcmod_info(json: &Value) -> Option<FileManifest> {
+    let mut manifest = new_manifest(FileManifestKind::LegacyForge);
+
+    // Either a list of mods, or an object holding one
+    let first_mod = match json {
+        Value::Array(mods) => mods.first(),
+        _ => json
+            .get("modList")
+            .and_then(Value::as_array)
+            .and_then(|x| x.first()),
+    }?;
+
+    manifest.id = as_string(first_mod.get("modid"));
+    manifest.name = as_string(first_mod.get("name"));
+    manifest.version = as_string(first_mod.get("version"));
+    manifest.authors = first_mod
+        .get("authorList")
+        .or_else(|| first_mod.get("authors"))
+        .map(as_requirements)
+        .unwrap_or_default();
+    manifest.icon =
+        as_string(first_mod.get("logoFile")).filter(|x| !x.is_empty());
+    manifest.game_versions =
+        as_string(first_mod.get("mcversion")).into_iter().collect();
+
+    // Entries are written as `id` or `id@versionRange`
+    for dependency in first_mod
+        .get("requiredMods")
+        .or_else(|| first_mod.get("dependencies"))
+        .map(as_requirements)
+        .unwrap_or_default()
+    {
+        let (id, version) = match dependency.split_once('@') {
+            Some((id, version)) => (id, vec![version.to_string()]),
+            None => (&*dependency, Vec::new()),
+        };
+        add_dependency(&mut manifest, id, version, true);
+    }
+
+    Some(manifest)
+}
+
+// https://minecraft.wiki/w/Pack.mcmeta
+pub(crate) fn parse_pack_mcmeta(json: &Value) -> FileManifest {
+    let mut manifest = new_manifest(FileManifestKind::Pack);
+
+    let pack = json.get("pack");
+    manifest.pack_format = pack
+        .and_then(|x| x.get("pack_format"))
+        .and_then(Value::as_u64)
+        .map(|x| x as u32);
+    // Written as a format, `[min, max]` or
+    // `{ "min_inclusive": min, "max_inclusive": max }`
+    let as_format = |x: Option<&Value>| x?.as_u64().map(|x| x as u32);
+    manifest.supported_formats = match pack
+        .and_then(|x| x.get("supported_formats"))
+    {
+        Some(Value::Array(range)) => {
+            as_format(range.first()).zip(as_format(range.get(1)))
+        }
+        Some(range @ Value::Object(_)) => as_format(range.get("min_inclusive"))
+            .zip(as_format(range.get("max_inclusive"))),
+        format => as_format(format).map(|x| (x, x)),
+    };
+    manifest.icon = Some("pack.png".to_string());
+
+    manifest
+}
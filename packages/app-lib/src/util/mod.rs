@@ -2,6 +2,8 @@
 pub mod fetch;
 pub mod io;
 pub mod jre;
+pub mod manifest;
+pub mod nbt;
 pub mod platform;
 
 /// Wrap a builder which uses a mut reference into one which outputs an owned value
@@ -0,0 +1,426 @@
+//! Minimal reader/writer for Minecraft's NBT format (level.dat, servers.dat, etc.)
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+
+const TAG_END: u8 = 0;
+const TAG_BYTE: u8 = 1;
+const TAG_SHORT: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_LONG: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_DOUBLE: u8 = 6;
+const TAG_BYTE_ARRAY: u8 = 7;
+const TAG_STRING: u8 = 8;
+const TAG_LIST: u8 = 9;
+const TAG_COMPOUND: u8 = 10;
+const TAG_INT_ARRAY: u8 = 11;
+const TAG_LONG_ARRAY: u8 = 12;
+
+// Guards against malformed files making us recurse forever
+const MAX_DEPTH: usize = 512;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtTag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<NbtTag>),
+    Compound(NbtCompound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+/// A compound tag. Entries keep their original order, so that files we rewrite
+/// stay as close as possible to what the game wrote
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NbtCompound(pub Vec<(String, NbtTag)>);
+
+impl NbtTag {
+    fn id(&self) -> u8 {
+        match self {
+            NbtTag::Byte(_) => TAG_BYTE,
+            NbtTag::Short(_) => TAG_SHORT,
+            NbtTag::Int(_) => TAG_INT,
+            NbtTag::Long(_) => TAG_LONG,
+            NbtTag::Float(_) => TAG_FLOAT,
+            NbtTag::Double(_) => TAG_DOUBLE,
+            NbtTag::ByteArray(_) => TAG_BYTE_ARRAY,
+            NbtTag::String(_) => TAG_STRING,
+            NbtTag::List(_) => TAG_LIST,
+            NbtTag::Compound(_) => TAG_COMPOUND,
+            NbtTag::IntArray(_) => TAG_INT_ARRAY,
+            NbtTag::LongArray(_) => TAG_LONG_ARRAY,
+        }
+    }
+
+    /// Gets any integer tag as an i64
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            NbtTag::Byte(x) => Some(x as i64),
+            NbtTag::Short(x) => Some(x as i64),
+            NbtTag::Int(x) => Some(x as i64),
+            NbtTag::Long(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            NbtTag::String(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&NbtCompound> {
+        match self {
+            NbtTag::Compound(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound_mut(&mut self) -> Option<&mut NbtCompound> {
+        match self {
+            NbtTag::Compound(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&Vec<NbtTag>> {
+        match self {
+            NbtTag::List(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl NbtCompound {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&NbtTag> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut NbtTag> {
+        self.0.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Sets a value, replacing the existing one in place if the key exists
+    pub fn insert(&mut self, key: &str, value: NbtTag) {
+        if let Some(existing) = self.get_mut(key) {
+            *existing = value;
+        } else {
+            self.0.push((key.to_string(), value));
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<NbtTag> {
+        let index = self.0.iter().position(|(k, _)| k == key)?;
+        Some(self.0.remove(index).1)
+    }
+
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.get(key).and_then(|x| x.as_i64())
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(|x| x.as_str())
+    }
+
+    pub fn get_compound(&self, key: &str) -> Option<&NbtCompound> {
+        self.get(key).and_then(|x| x.as_compound())
+    }
+
+    pub fn get_compound_mut(&mut self, key: &str) -> Option<&mut NbtCompound> {
+        self.get_mut(key).and_then(|x| x.as_compound_mut())
+    }
+}
+
+/// Reads an NBT file, transparently handling gzip compression
+/// Returns the root tag's name and contents
+pub fn from_bytes(bytes: &[u8]) -> crate::Result<(String, NbtCompound)> {
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(bytes)
+            .read_to_end(&mut decompressed)
+            .map_err(|e| nbt_error(format!("invalid gzip stream: {e}")))?;
+
+        read_root(&decompressed)
+    } else {
+        read_root(bytes)
+    }
+}
+
+/// Writes an NBT file, gzip compressed if `compress` is set
+pub fn to_bytes(
+    name: &str,
+    root: &NbtCompound,
+    compress: bool,
+) -> crate::Result<Vec<u8>> {
+    let mut data = vec![TAG_COMPOUND];
+    write_string(&mut data, name);
+    write_compound(&mut data, root);
+
+    if compress {
+        let mut encoder =
+            GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&data)?;
+        Ok(encoder.finish()?)
+    } else {
+        Ok(data)
+    }
+}
+
+fn nbt_error(message: String) -> crate::Error {
+    crate::ErrorKind::NBTError(message).as_error()
+}
+
+fn read_root(bytes: &[u8]) -> crate::Result<(String, NbtCompound)> {
+    let mut reader = Reader { bytes, pos: 0 };
+
+    let tag = reader.u8()?;
+    if tag != TAG_COMPOUND {
+        return Err(nbt_error(format!(
+            "root tag must be a compound, found type {tag}"
+        )));
+    }
+    let name = reader.string()?;
+    let root = reader.compound(0)?;
+
+    Ok((name, root))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> crate::Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|x| *x <= self.bytes.len())
+            .ok_or_else(|| nbt_error("unexpected end of data".to_string()))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> crate::Result<[u8; N]> {
+        let mut arr = [0; N];
+        arr.copy_from_slice(self.take(N)?);
+        Ok(arr)
+    }
+
+    fn u8(&mut self) -> crate::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i16(&mut self) -> crate::Result<i16> {
+        Ok(i16::from_be_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> crate::Result<i32> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> crate::Result<i64> {
+        Ok(i64::from_be_bytes(self.array()?))
+    }
+
+    fn array_len(&mut self) -> crate::Result<usize> {
+        let len = self.i32()?;
+        usize::try_from(len)
+            .map_err(|_| nbt_error(format!("negative length {len}")))
+    }
+
+    fn string(&mut self) -> crate::Result<String> {
+        let len = u16::from_be_bytes(self.array()?) as usize;
+        Ok(decode_mutf8(self.take(len)?))
+    }
+
+    fn compound(&mut self, depth: usize) -> crate::Result<NbtCompound> {
+        let mut compound = NbtCompound::new();
+        loop {
+            let tag = self.u8()?;
+            if tag == TAG_END {
+                break;
+            }
+            let name = self.string()?;
+            let value = self.payload(tag, depth + 1)?;
+            compound.0.push((name, value));
+        }
+        Ok(compound)
+    }
+
+    fn payload(&mut self, tag: u8, depth: usize) -> crate::Result<NbtTag> {
+        if depth > MAX_DEPTH {
+            return Err(nbt_error("tags are nested too deeply".to_string()));
+        }
+
+        Ok(match tag {
+            TAG_BYTE => NbtTag::Byte(self.u8()? as i8),
+            TAG_SHORT => NbtTag::Short(self.i16()?),
+            TAG_INT => NbtTag::Int(self.i32()?),
+            TAG_LONG => NbtTag::Long(self.i64()?),
+            TAG_FLOAT => NbtTag::Float(f32::from_be_bytes(self.array()?)),
+            TAG_DOUBLE => NbtTag::Double(f64::from_be_bytes(self.array()?)),
+            TAG_BYTE_ARRAY => {
+                let len = self.array_len()?;
+                NbtTag::ByteArray(
+                    self.take(len)?.iter().map(|x| *x as i8).collect(),
+                )
+            }
+            TAG_STRING => NbtTag::String(self.string()?),
+            TAG_LIST => {
+                let element = self.u8()?;
+                let len = self.array_len()?;
+                let mut list = Vec::with_capacity(len.min(4096));
+                for _ in 0..len {
+                    list.push(self.payload(element, depth + 1)?);
+                }
+                NbtTag::List(list)
+            }
+            TAG_COMPOUND => NbtTag::Compound(self.compound(depth)?),
+            TAG_INT_ARRAY => {
+                let len = self.array_len()?;
+                let mut list = Vec::with_capacity(len.min(4096));
+                for _ in 0..len {
+                    list.push(self.i32()?);
+                }
+                NbtTag::IntArray(list)
+            }
+            TAG_LONG_ARRAY => {
+                let len = self.array_len()?;
+                let mut list = Vec::with_capacity(len.min(4096));
+                for _ in 0..len {
+                    list.push(self.i64()?);
+                }
+                NbtTag::LongArray(list)
+            }
+            _ => return Err(nbt_error(format!("unknown tag type {tag}"))),
+        })
+    }
+}
+
+fn write_string(out: &mut Vec<u8>, value: &str) {
+    let encoded = encode_mutf8(value, u16::MAX as usize);
+    out.extend_from_slice(&(encoded.len() as u16).to_be_bytes());
+    out.extend_from_slice(&encoded);
+}
+
+fn write_compound(out: &mut Vec<u8>, compound: &NbtCompound) {
+    for (name, value) in &compound.0 {
+        out.push(value.id());
+        write_string(out, name);
+        write_payload(out, value);
+    }
+    out.push(TAG_END);
+}
+
+fn write_payload(out: &mut Vec<u8>, tag: &NbtTag) {
+    match tag {
+        NbtTag::Byte(x) => out.push(*x as u8),
+        NbtTag::Short(x) => out.extend_from_slice(&x.to_be_bytes()),
+        NbtTag::Int(x) => out.extend_from_slice(&x.to_be_bytes()),
+        NbtTag::Long(x) => out.extend_from_slice(&x.to_be_bytes()),
+        NbtTag::Float(x) => out.extend_from_slice(&x.to_be_bytes()),
+        NbtTag::Double(x) => out.extend_from_slice(&x.to_be_bytes()),
+        NbtTag::ByteArray(x) => {
+            out.extend_from_slice(&(x.len() as i32).to_be_bytes());
+            out.extend(x.iter().map(|x| *x as u8));
+        }
+        NbtTag::String(x) => write_string(out, x),
+        NbtTag::List(x) => {
+            out.push(x.first().map(|x| x.id()).unwrap_or(TAG_END));
+            out.extend_from_slice(&(x.len() as i32).to_be_bytes());
+            for value in x {
+                write_payload(out, value);
+            }
+        }
+        NbtTag::Compound(x) => write_compound(out, x),
+        NbtTag::IntArray(x) => {
+            out.extend_from_slice(&(x.len() as i32).to_be_bytes());
+            for value in x {
+                out.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        NbtTag::LongArray(x) => {
+            out.extend_from_slice(&(x.len() as i32).to_be_bytes());
+            for value in x {
+                out.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+    }
+}
+
+// NBT strings use Java's "modified UTF-8": NUL is encoded as two bytes, and
+// characters outside the BMP are encoded as a UTF-16 surrogate pair
+fn decode_mutf8(bytes: &[u8]) -> String {
+    if let Ok(value) = std::str::from_utf8(bytes) {
+        return value.to_string();
+    }
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i] as u16;
+        let cont = |j: usize| bytes.get(i + j).map(|x| *x as u16 & 0x3f);
+
+        if b & 0x80 == 0 {
+            units.push(b);
+            i += 1;
+        } else if b & 0xe0 == 0xc0 {
+            units.push(((b & 0x1f) << 6) | cont(1).unwrap_or(0));
+            i += 2;
+        } else {
+            units.push(
+                ((b & 0x0f) << 12)
+                    | (cont(1).unwrap_or(0) << 6)
+                    | cont(2).unwrap_or(0),
+            );
+            i += 3;
+        }
+    }
+
+    String::from_utf16_lossy(&units)
+}
+
+// Encodes a string as modified UTF-8, cutting it at the last whole character
+// that fits in `max_len` bytes
+fn encode_mutf8(value: &str, max_len: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(value.len().min(max_len));
+    let mut units = [0; 2];
+    for c in value.chars() {
+        let start = out.len();
+        for unit in c.encode_utf16(&mut units).iter().copied() {
+            match unit {
+                0x01..=0x7f => out.push(unit as u8),
+                0x00 | 0x80..=0x7ff => {
+                    out.push(0xc0 | (unit >> 6) as u8);
+                    out.push(0x80 | (unit & 0x3f) as u8);
+                }
+                _ => {
+                    out.push(0xe0 | (unit >> 12) as u8);
+                    out.push(0x80 | ((unit >> 6) & 0x3f) as u8);
+                    out.push(0x80 | (unit & 0x3f) as u8);
+                }
+            }
+        }
+
+        if out.len() > max_len {
+            out.truncate(start);
+            break;
+        }
+    }
+    out
+}